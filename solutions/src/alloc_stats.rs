@@ -0,0 +1,226 @@
+// Counting allocations, for finding out which part of a program allocates how much. With the
+// `alloc-stats` feature, all allocations go through `CountingAllocator`, which attributes them to
+// the innermost scope that is active on the current thread. Scopes are entered with `alloc_scope!`:
+//
+//     let n = alloc_scope!("factorial", { BigUint::factorial(1000) });
+//     println!("{:?}", alloc_stats::stats("factorial"));
+//
+// The allocator itself must not allocate, so the statistics live in a fixed table of atomic
+// counters, and the current scope is a plain index in a thread-local `Cell`.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::{ptr, slice, str};
+
+// The maximal number of different labels.
+const MAX_SCOPES: usize = 64;
+// The scope index meaning "not in any scope".
+const NO_SCOPE: usize = usize::MAX;
+
+// One entry of the table. The label is stored as a pointer and a length, since that's what we can
+// put into atomics.
+struct Scope {
+    label: AtomicPtr<u8>,
+    label_len: AtomicUsize,
+    allocations: AtomicUsize,
+    bytes: AtomicUsize,
+}
+
+#[allow(clippy::declare_interior_mutable_const)]
+const EMPTY_SCOPE: Scope = Scope {
+    label: AtomicPtr::new(ptr::null_mut()),
+    label_len: AtomicUsize::new(0),
+    allocations: AtomicUsize::new(0),
+    bytes: AtomicUsize::new(0),
+};
+
+static SCOPES: [Scope; MAX_SCOPES] = [EMPTY_SCOPE; MAX_SCOPES];
+// Serializes adding new labels to the table. Counting does not need this lock.
+static REGISTER: Mutex<()> = Mutex::new(());
+
+thread_local! {
+    static CURRENT: Cell<usize> = const { Cell::new(NO_SCOPE) };
+}
+
+impl Scope {
+    fn label(&self) -> Option<&'static str> {
+        let label = self.label.load(Ordering::Acquire);
+        if label.is_null() {
+            None
+        } else {
+            // Labels only ever come from `&'static str`.
+            Some(unsafe { str::from_utf8_unchecked(slice::from_raw_parts(label, self.label_len.load(Ordering::Relaxed))) })
+        }
+    }
+}
+
+// Find the table entry for `label`, adding one if necessary.
+fn scope_index(label: &'static str) -> usize {
+    let find = || SCOPES.iter().position(|scope| scope.label() == Some(label));
+    if let Some(index) = find() {
+        return index;
+    }
+    let _lock = REGISTER.lock().unwrap();
+    // Somebody else might have added it in the mean time.
+    if let Some(index) = find() {
+        return index;
+    }
+    let index = SCOPES.iter().position(|scope| scope.label().is_none())
+        .expect("Too many different allocation scopes");
+    SCOPES[index].label_len.store(label.len(), Ordering::Relaxed);
+    SCOPES[index].label.store(label.as_ptr() as *mut u8, Ordering::Release);
+    index
+}
+
+/// The global allocator counting the allocations of every scope. It forwards all requests to the
+/// system allocator.
+pub struct CountingAllocator;
+
+impl CountingAllocator {
+    fn count(&self, size: usize) {
+        // During thread teardown, the thread-local may already be gone; we just don't count then.
+        let current = CURRENT.try_with(|current| current.get()).unwrap_or(NO_SCOPE);
+        if current != NO_SCOPE {
+            SCOPES[current].allocations.fetch_add(1, Ordering::Relaxed);
+            SCOPES[current].bytes.fetch_add(size, Ordering::Relaxed);
+        }
+    }
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        self.count(layout.size());
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        self.count(layout.size());
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    // Growing or shrinking counts as an allocation of the new size, since that's what it may cost.
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        self.count(new_size);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// What was allocated within a scope.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AllocStats {
+    /// The number of allocations, including reallocations.
+    pub allocations: usize,
+    /// The number of bytes requested by these allocations.
+    pub bytes: usize,
+}
+
+/// Restores the previous scope when dropped. Returned by `enter_scope`.
+pub struct ScopeGuard {
+    previous: usize,
+}
+
+impl Drop for ScopeGuard {
+    fn drop(&mut self) {
+        CURRENT.with(|current| current.set(self.previous));
+    }
+}
+
+/// Attribute all allocations of the current thread to `label`, until the returned guard is dropped.
+/// Usually, you want to use `alloc_scope!` instead.
+pub fn enter_scope(label: &'static str) -> ScopeGuard {
+    let index = scope_index(label);
+    ScopeGuard { previous: CURRENT.with(|current| current.replace(index)) }
+}
+
+/// Return what was allocated so far in all scopes labeled `label`.
+pub fn stats(label: &str) -> AllocStats {
+    SCOPES.iter().find(|scope| scope.label() == Some(label)).map_or(AllocStats::default(), |scope| {
+        AllocStats {
+            allocations: scope.allocations.load(Ordering::Relaxed),
+            bytes: scope.bytes.load(Ordering::Relaxed),
+        }
+    })
+}
+
+/// Return the statistics of all labels that were used so far.
+pub fn all_stats() -> Vec<(&'static str, AllocStats)> {
+    SCOPES.iter().filter_map(|scope| scope.label()).map(|label| (label, stats(label))).collect()
+}
+
+/// Reset the counters of all scopes to 0.
+pub fn reset() {
+    for scope in SCOPES.iter() {
+        scope.allocations.store(0, Ordering::Relaxed);
+        scope.bytes.store(0, Ordering::Relaxed);
+    }
+}
+
+/// Run a block with all its allocations attributed to the given label, and return the block's value.
+/// Scopes can be nested; allocations are counted for the innermost one only.
+#[macro_export]
+macro_rules! alloc_scope {
+    ($label:expr, $body:block) => {{
+        let _guard = $crate::alloc_stats::enter_scope($label);
+        $body
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{stats, all_stats, AllocStats};
+    use bigint::BigUint;
+
+    #[test]
+    fn test_alloc_scope() {
+        let v = alloc_scope!("test_alloc_scope", { vec![0u8; 100] });
+        assert_eq!(stats("test_alloc_scope"), AllocStats { allocations: 1, bytes: 100 });
+        drop(v);
+        assert_eq!(stats("test_alloc_scope").allocations, 1);
+        assert_eq!(stats("never used"), AllocStats::default());
+        assert!(all_stats().iter().any(|&(label, _)| label == "test_alloc_scope"));
+    }
+
+    #[test]
+    fn test_nested_scopes() {
+        alloc_scope!("test_nested_outer", {
+            let _a = Box::new(1u64);
+            alloc_scope!("test_nested_inner", {
+                let _b = Box::new([1u32; 4]);
+                let _c = Box::new([1u16; 4]);
+            });
+            let _d = Box::new(2u32);
+        });
+        assert_eq!(stats("test_nested_outer"), AllocStats { allocations: 2, bytes: 12 });
+        assert_eq!(stats("test_nested_inner"), AllocStats { allocations: 2, bytes: 24 });
+    }
+
+    #[test]
+    fn test_bigint_allocations() {
+        let b = BigUint::new(3);
+        // `pow` keeps its temporaries in an arena, so it only allocates for that (the list of chunks
+        // and the single chunk), and for the result.
+        let p = alloc_scope!("test_pow", { b.pow(1000) });
+        assert_eq!(stats("test_pow").allocations, 3);
+        let mut expected = BigUint::new(1);
+        alloc_scope!("test_repeated_mul", {
+            for _ in 0..1000 {
+                expected = &expected * &b;
+            }
+        });
+        // Multiplying by `b` allocates for every product, except for the first 41: Up to 3^40, the
+        // factors have only one digit, so the product fits into the inline storage. With 32-bit
+        // limbs, that is only up to 3^20.
+        let inline = if cfg!(feature = "u32-limbs") { 21 } else { 41 };
+        assert_eq!(stats("test_repeated_mul").allocations, 1000 - inline);
+        assert_eq!(p, expected);
+    }
+}
@@ -1,6 +1,49 @@
+#[cfg(not(feature = "std"))]
+use std::prelude::v1::*;
 use std::ops;
 use std::cmp;
+#[cfg(feature = "std")]
+use std::cell::RefCell;
+use std::sync::Arc;
+#[cfg(feature = "std")]
+use std::sync::Mutex;
+use std::mem;
+use std::iter::{self, FromIterator};
 use std::fmt;
+use std::fmt::Write;
+use std::str::FromStr;
+use std::convert::TryFrom;
+use std::error::Error;
+use locale::Locale;
+use self::storage::{small_limb, limb_to_u64, Digits, Limb, DoubleLimb, LIMB_BITS};
+use self::limbs::{add_with_carry, sub_with_borrow, mul_add_carry, add_slices_in_place, mul_limb_in_place};
+
+pub mod limbs;
+pub mod stats;
+mod numerals;
+mod factor;
+mod arithmetic;
+mod arena;
+mod storage;
+mod shared;
+mod constant_time;
+mod encode;
+mod builder;
+mod signed;
+#[cfg(feature = "num")]
+mod num;
+#[cfg(any(feature = "quickcheck", feature = "proptest"))]
+mod arbitrary;
+#[cfg(any(feature = "num-bigint", feature = "ibig"))]
+mod interop;
+pub use self::factor::PrimeFactors;
+#[cfg(feature = "std")]
+pub use self::factor::PrimeCache;
+pub use self::arithmetic::Divisors;
+pub use self::arena::BigIntArena;
+pub use self::shared::SharedBigInt;
+pub use self::builder::BigIntBuilder;
+pub use self::signed::{BigInt, Sign};
 
 pub trait Minimum {
     /// Return the smaller of the two
@@ -19,46 +62,180 @@ pub fn vec_min<T: Minimum>(v: &Vec<T>) -> Option<&T> {
     min
 }
 
-pub struct BigInt {
-    data: Vec<u64>, // least significant digits first. The last block will *not* be 0.
+/// Return a pointer to the minimal value of a slice, the first one if there are several. Like
+/// `vec_min`, this never copies a number: the result borrows from `v`, so it lives as long as the
+/// slice does, not just as long as the call.
+pub fn min_ref<T: Minimum>(v: &[T]) -> Option<&T> {
+    v.iter().fold(None, |min, e| Some(match min {
+        None => e,
+        Some(n) => n.min(e)
+    }))
+}
+
+/// Return a copy of the minimal value of a slice. Only the minimum is cloned, not the candidates.
+pub fn min_cloned<T: Minimum + Clone>(v: &[T]) -> Option<T> {
+    min_ref(v).cloned()
+}
+
+/// Sort the numbers in increasing order. This is faster than `sort_unstable`, which has to follow the
+/// pointers to the digits for every comparison: We first sort by the number of digits and the
+/// highest digit, which we keep in a compact array. Only numbers agreeing in these need to be
+/// compared in full.
+pub fn sort_bigints(v: &mut [BigUint]) {
+    let mut keys: Vec<(usize, Limb, usize)> = v.iter().enumerate()
+        .map(|(i, b)| (b.data.len(), b.data.last().cloned().unwrap_or(0), i))
+        .collect();
+    keys.sort_unstable();
+    // Move the numbers into the order we found. Taking out a `BigUint` leaves behind a 0, with nothing
+    // allocated.
+    let mut unsorted: Vec<BigUint> = v.iter_mut().map(mem::take).collect();
+    for (slot, &(_, _, i)) in v.iter_mut().zip(keys.iter()) {
+        *slot = mem::take(&mut unsorted[i]);
+    }
+    // Now sort the runs of numbers with the same key.
+    let mut start = 0;
+    while start < keys.len() {
+        let key = (keys[start].0, keys[start].1);
+        let end = start + keys[start..].iter().take_while(|k| (k.0, k.1) == key).count();
+        if end - start > 1 {
+            v[start..end].sort_unstable();
+        }
+        start = end;
+    }
+}
+
+// The largest power of 10 that fits into a digit, used to convert the number to decimal in chunks.
+#[cfg(not(feature = "u32-limbs"))]
+const DECIMAL_CHUNK: u64 = 10_000_000_000_000_000_000;
+#[cfg(feature = "u32-limbs")]
+const DECIMAL_CHUNK: u64 = 1_000_000_000;
+// The number of decimal digits in `DECIMAL_CHUNK`.
+const DECIMAL_CHUNK_LEN: usize = DECIMAL_CHUNK.ilog10() as usize;
+// Up to this many digits, converting to decimal chunk by chunk beats splitting the number.
+const DECIMAL_SPLIT_THRESHOLD: usize = 32;
+
+// The part of `src` that is left after trimming the whitespace on both ends.
+fn trimmed_bounds(src: &str) -> (usize, usize) {
+    let start = src.len() - src.trim_start().len();
+    (start, src.trim_end().len().max(start))
 }
 
-// Add with carry, returning the sum and the carry
-fn overflowing_add(a: u64, b: u64, carry: bool) -> (u64, bool) {
-    let sum = u64::wrapping_add(a, b);
-    let carry_n = if carry { 1 } else { 0 };
-    if sum >= a { // the first sum did not overflow
-        let sum_total = u64::wrapping_add(sum, carry_n);
-        let had_overflow = sum_total < sum;
-        (sum_total, had_overflow)
-    } else { // the first sum did overflow
-        // it is impossible for this to overflow again, as we are just adding 0 or 1
-        (sum + carry_n, true)
+// The powers `DECIMAL_CHUNK^(2^i)`, by which we split numbers in halves when converting from and to
+// decimal. Computing them takes a good part of the conversion, so we keep them around: the table
+// only grows, up to what the largest number converted so far needed.
+#[cfg(feature = "std")]
+static DECIMAL_POWERS: Mutex<Option<Arc<Vec<BigUint>>>> = Mutex::new(None);
+
+// Get the table of the powers `DECIMAL_CHUNK^(2^i)`, with at least `levels` of them.
+fn decimal_powers(levels: usize) -> Arc<Vec<BigUint>> {
+    #[cfg(feature = "std")]
+    {
+        let mut cache = DECIMAL_POWERS.lock().unwrap_or_else(|e| e.into_inner());
+        let known: &[BigUint] = match *cache {
+            Some(ref powers) if powers.len() >= levels => return powers.clone(),
+            Some(ref powers) => powers,
+            None => &[],
+        };
+        let powers = Arc::new(extend_decimal_powers(known, levels));
+        *cache = Some(powers.clone());
+        powers
     }
+    #[cfg(not(feature = "std"))]
+    Arc::new(extend_decimal_powers(&[], levels))
 }
 
-// Subtract with carry, returning the difference and the carry
-fn overflowing_sub(a: u64, b: u64, carry: bool) -> (u64, bool) {
-    let diff = u64::wrapping_sub(a, b);
-    let carry_n = if carry { 1 } else { 0 };
-    if diff <= a { // the first diff did not wrap
-        let diff_total = u64::wrapping_sub(diff, carry_n);
-        let had_wrap = diff_total > diff;
-        (diff_total, had_wrap)
-    } else { // the first diff did wrap
-        // it is impossible for this to wrap again, as we are just substracting 0 or 1
-        (diff - carry_n, true)
+fn extend_decimal_powers(known: &[BigUint], levels: usize) -> Vec<BigUint> {
+    let mut powers = known.to_vec();
+    if powers.is_empty() {
+        powers.push(BigUint::new(DECIMAL_CHUNK));
     }
+    while powers.len() < levels {
+        let top = &powers[powers.len() - 1];
+        let square = top * top;
+        powers.push(square);
+    }
+    powers
 }
 
-impl BigInt {
-    /// Construct a BigInt from a "small" one.
-    pub fn new(x: u64) -> Self {
-        if x == 0 { // take care of our invariant!
-            BigInt { data: vec![] }
-        } else {
-            BigInt { data: vec![x] }
+// Determine how many digits of the given base fit into a digit of a `BigUint`, and the base raised to
+// that power. A base that does not fit itself comes back as is.
+fn radix_chunk(radix: u64) -> (u32, u64) {
+    let (mut len, mut factor) = (1, radix);
+    while let Some(next) = factor.checked_mul(radix).filter(|&next| small_limb(next).is_some()) {
+        len += 1;
+        factor = next;
+    }
+    (len, factor)
+}
+
+// `product_range` multiplies up to this many factors one after the other.
+const PRODUCT_LEAF_SIZE: u64 = 16;
+
+// Compute the product of all numbers in `lo..=hi`. Multiplying them one by one would mean multiplying
+// an ever larger number by a tiny one; instead we build a product tree: First we compute the products
+// of short runs of factors, and then we repeatedly multiply neighbours with each other. That way, the
+// factors of each multiplication have roughly the same size, which is what makes computing `100000!`
+// feasible. We go through the tree level by level, so there is no recursion whose depth would depend
+// on the input.
+fn product_range(lo: u64, hi: u64) -> BigUint {
+    debug_assert!(lo >= 1 && lo <= hi);
+    let mut level = Vec::new();
+    let mut start = lo;
+    loop {
+        let end = if hi - start < PRODUCT_LEAF_SIZE { hi } else { start + PRODUCT_LEAF_SIZE - 1 };
+        let mut leaf = BigUint::new(start);
+        for factor in start + 1..end + 1 {
+            leaf.mul_add_u64(factor, 0);
+        }
+        level.push(leaf);
+        if end == hi {
+            break;
+        }
+        start = end + 1;
+    }
+    while level.len() > 1 {
+        let mut next = Vec::with_capacity(level.len().div_ceil(2));
+        let mut nodes = level.into_iter();
+        while let Some(left) = nodes.next() {
+            next.push(match nodes.next() {
+                Some(right) => left * right,
+                None => left,
+            });
         }
+        level = next;
+    }
+    level.pop().unwrap()
+}
+
+pub struct BigUint {
+    data: Digits, // least significant digits first. The last block will *not* be 0.
+}
+
+impl BigUint {
+    /// Construct a BigUint from a "small" one. This works in constants, too.
+    pub const fn new(x: u64) -> Self {
+        // `from_u64` takes care of our invariant: 0 has no digits.
+        BigUint { data: Digits::from_u64(x) }
+    }
+
+    /// The number 0.
+    pub const fn zero() -> Self {
+        BigUint { data: Digits::new() }
+    }
+
+    /// The number 1.
+    pub const fn one() -> Self {
+        BigUint { data: Digits::from_u64(1) }
+    }
+
+    /// Check whether the number is 0.
+    pub fn is_zero(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Check whether the number is 1.
+    pub fn is_one(&self) -> bool {
+        *self.data == [1]
     }
 
     fn test_invariant(&self) -> bool {
@@ -69,13 +246,70 @@ impl BigInt {
         }
     }
 
-    /// Construct a BigInt from a vector of 64-bit "digits", with the last significant digit being first. Solution to 05.1.
+    /// Construct a BigUint from a vector of 64-bit "digits", with the last significant digit being first. Solution to 05.1.
     pub fn from_vec(mut v: Vec<u64>) -> Self {
         // remove trailing zeros
         while v.len() > 0 && v[v.len()-1] == 0 {
             v.pop();
         }
-        BigInt { data: v }
+        // Splitting the top digit into narrower limbs may leave another zero.
+        BigUint::from_digits(Digits::from_u64_vec(v))
+    }
+
+    /// Construct a BigUint from digits that are known not to have trailing zeros, without checking.
+    ///
+    /// # Safety
+    ///
+    /// The last element of `v` (the most significant digit) must not be 0. The other functions rely
+    /// on that; for example, comparisons and printing go wrong otherwise.
+    pub unsafe fn from_vec_unchecked(v: Vec<Limb>) -> Self {
+        let result = BigUint { data: Digits::from_vec(v) };
+        debug_assert!(result.test_invariant(), "BigUint::from_vec_unchecked called with trailing zeros");
+        result
+    }
+
+    /// The digits of the number, least significant first. They are 64 bits wide, unless the
+    /// `u32-limbs` feature is enabled. The last digit is never 0; in particular, 0 has no digits at
+    /// all.
+    pub fn as_limbs(&self) -> &[Limb] {
+        &self.data
+    }
+
+    /// Take the number apart into its digits, as returned by `as_limbs`.
+    pub fn into_limbs(self) -> Vec<Limb> {
+        self.data.into_vec()
+    }
+
+    /// Iterate over the digits, least significant first. The iterator runs from both ends, so
+    /// `iter().rev()` (or `iter_msf`) walks the digits from the top.
+    pub fn iter(&self) -> Iter<'_> {
+        Iter { digits: &self.data, front: 0, back: self.data.len() }
+    }
+
+    /// Iterate over the digits, most significant first. That is the order in which they are printed.
+    pub fn iter_msf(&self) -> iter::Rev<Iter<'_>> {
+        self.iter().rev()
+    }
+
+    // The digits of the number in base `2^64`, least significant first, whatever the width of our
+    // limbs. Like the limbs, the last one is never 0.
+    fn u64_digits(&self) -> impl ExactSizeIterator<Item = u64> + '_ {
+        self.data.chunks(64 / LIMB_BITS as usize).map(|limbs| {
+            limbs.iter().rev().fold(0, |acc: u64, &limb| acc.checked_shl(LIMB_BITS).unwrap_or(0) | limb_to_u64(limb))
+        })
+    }
+
+    // The lowest 64 bits of the number.
+    fn low_u64(&self) -> u64 {
+        self.u64_digits().next().unwrap_or(0)
+    }
+
+    // Like `from_vec`, for digits that are already in our own storage.
+    fn from_digits(mut digits: Digits) -> Self {
+        while digits.last() == Some(&0) {
+            digits.pop();
+        }
+        BigUint { data: digits }
     }
 
     /// Increments the number by 1.
@@ -84,7 +318,7 @@ impl BigInt {
         // This loop adds "(1 << idx)". If there is no more carry, we leave.
         while idx < self.data.len() {
             let cur = self.data[idx];
-            let sum = u64::wrapping_add(cur, 1);
+            let sum = Limb::wrapping_add(cur, 1);
             self.data[idx] = sum;
             if sum >= cur {
                 // No overflow, we are done.
@@ -99,290 +333,2753 @@ impl BigInt {
     }
 
     /// Increments the number by "by".
-    pub fn inc(&mut self, mut by: u64) {
-        let mut idx = 0;
-        // This loop adds "by * (1 << idx)". Think of "by" as the carry from incrementing the last digit.
-        while idx < self.data.len() {
-            let cur = self.data[idx];
-            let sum = u64::wrapping_add(cur, by);
-            self.data[idx] = sum;
-            if sum >= cur {
-                // No overflow, we are done.
-                return;
-            } else {
-                // We need to add a carry.
-                by = 1;
-                idx += 1;
-            }
+    pub fn inc(&mut self, by: u64) {
+        // "by" may take more than one digit, if they are narrower than 64 bits.
+        let by = Digits::from_u64(by);
+        if self.data.len() < by.len() {
+            self.data.resize(by.len(), 0);
+        }
+        // If there is a last carry, we add it as a new digit.
+        if add_slices_in_place(&mut self.data, &by) {
+            self.data.push(1);
         }
-        // If we came here, there is a last carry to add
-        self.data.push(by);
     }
 
-    /// Return the nth power-of-2 as BigInt
-    pub fn power_of_2(mut power: u64) -> BigInt {
-        let mut v = Vec::new();
-        while power >= 64 {
-            v.push(0);
-            power -= 64;
+    // Multiply by a single digit and add another one, in place.
+    fn mul_add_digit(&mut self, factor: Limb, addend: Limb) {
+        debug_assert!(factor != 0);
+        let carry = mul_limb_in_place(&mut self.data, factor, addend);
+        if carry != 0 {
+            self.data.push(carry);
         }
-        v.push(1 << power);
-        BigInt::from_vec(v)
     }
-}
 
-impl Clone for BigInt {
-    fn clone(&self) -> Self {
-        BigInt { data: self.data.clone() }
+    // Like `mul_add_digit`, for numbers that may not fit into a single digit.
+    fn mul_add_u64(&mut self, factor: u64, addend: u64) {
+        match (small_limb(factor), small_limb(addend)) {
+            (Some(factor), Some(addend)) => self.mul_add_digit(factor, addend),
+            _ => *self = self.mul_u64(factor).add_u64(addend),
+        }
     }
-}
 
-impl PartialEq for BigInt {
-    fn eq(&self, other: &BigInt) -> bool {
-        debug_assert!(self.test_invariant() && other.test_invariant());
-        self.data == other.data
+    /// Parse a number from its digits in the given base, which must lie between 2 and 36. Letters are
+    /// accepted in both cases. The input may be surrounded by whitespace, start with a `+`, and use
+    /// underscores as separators after the first digit, like `1_000_000`; see `from_str_radix_strict`
+    /// for a parser that only accepts digits.
+    pub fn from_str_radix(src: &str, radix: u32) -> Result<BigUint, ParseBigIntError> {
+        BigUint::parse_radix(src, radix, true)
     }
-}
 
-impl Minimum for BigInt {
-    // This is essentially the solution to 06.1.
-    fn min<'a>(&'a self, other: &'a Self) -> &'a Self {
-        debug_assert!(self.test_invariant() && other.test_invariant());
-        if self.data.len() < other.data.len() {
-            self
-        } else if self.data.len() > other.data.len() {
-            other
-        } else {
-            // compare back-to-front, i.e., most significant digit first
-            let mut idx = self.data.len();
-            while idx > 0 {
-                idx = idx-1;
-                if self.data[idx] < other.data[idx] {
-                    return self;
-                } else if self.data[idx] > other.data[idx] {
-                    return other;
+    /// Parse a number that consists of nothing but its digits in the given base, which must lie
+    /// between 2 and 36.
+    pub fn from_str_radix_strict(src: &str, radix: u32) -> Result<BigUint, ParseBigIntError> {
+        BigUint::parse_radix(src, radix, false)
+    }
+
+    fn parse_radix(src: &str, radix: u32, lenient: bool) -> Result<BigUint, ParseBigIntError> {
+        if !lenient {
+            return BigUint::parse_digits(src, 0, src.len(), radix, false);
+        }
+        let (mut start, end) = trimmed_bounds(src);
+        if src[start..end].starts_with('+') {
+            start += 1;
+        }
+        BigUint::parse_digits(src, start, end, radix, true)
+    }
+
+    // Parse the digits in `src[start..end]`, skipping separators if `lenient` is set. The errors
+    // always refer to the complete input, even if the caller skipped parts of it.
+    fn parse_digits(src: &str, start: usize, end: usize, radix: u32, lenient: bool) -> Result<BigUint, ParseBigIntError> {
+        assert!((2..=36).contains(&radix), "from_str_radix: radix must lie in the range `[2, 36]` - found {}", radix);
+        if start == end {
+            return Err(ParseBigIntError::new(ParseErrorKind::Empty, src, start, 0, None));
+        }
+        // We accumulate as many digits as fit into a digit of the `BigUint`, and only then touch it.
+        let (chunk_len, chunk_factor) = radix_chunk(radix as u64);
+        let (chunk_factor, radix_limb) = (chunk_factor as Limb, radix as Limb);
+        let mut result = BigUint::new(0);
+        let (mut chunk, mut chunk_digits): (Limb, _) = (0, 0);
+        // Decimal chunks are collected, to be put together by splitting, like we do for printing.
+        let mut decimal_chunks = Vec::new();
+        for (offset, c) in src[start..end].char_indices() {
+            if lenient && c == '_' && offset > 0 {
+                continue;
+            }
+            let digit = match c.to_digit(radix) {
+                Some(digit) => digit as Limb,
+                None => return Err(ParseBigIntError::invalid_digit(src, start + offset, c, radix)),
+            };
+            chunk = chunk * radix_limb + digit;
+            chunk_digits += 1;
+            if chunk_digits == chunk_len {
+                if radix == 10 {
+                    decimal_chunks.push(chunk);
+                } else {
+                    result.mul_add_digit(chunk_factor, chunk);
                 }
+                chunk = 0;
+                chunk_digits = 0;
             }
-            // the two are equal
-            return self;
         }
+        if !decimal_chunks.is_empty() {
+            decimal_chunks.reverse();
+            result = BigUint::from_decimal_chunks(&decimal_chunks);
+        }
+        if chunk_digits > 0 {
+            result.mul_add_digit(radix_limb.pow(chunk_digits), chunk);
+        }
+        Ok(result)
     }
-}
 
-impl fmt::Debug for BigInt {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        self.data.fmt(f)
+    /// Multiply by `rhs` in place, computing the product in `scratch`. The number then takes over
+    /// that buffer, and leaves its old one behind in `scratch`, so a loop that always passes the same
+    /// buffer does not allocate once the numbers stop growing. The contents of `scratch` afterwards
+    /// are unspecified.
+    pub fn mul_assign_with_scratch(&mut self, rhs: &BigUint, scratch: &mut Vec<Limb>) {
+        if self.data.is_empty() || rhs.data.is_empty() {
+            self.data.truncate(0);
+            return;
+        }
+        scratch.clear();
+        scratch.resize(self.data.len() + rhs.data.len(), 0);
+        mul_digits(scratch, &self.data, &rhs.data);
+        while scratch.last() == Some(&0) {
+            scratch.pop();
+        }
+        self.data.replace_with_vec(scratch);
     }
-}
 
-impl<'a, 'b> ops::Add<&'a BigInt> for &'b BigInt {
-    type Output = BigInt;
-    fn add(self, rhs: &'a BigInt) -> Self::Output {
-        let max_len = cmp::max(self.data.len(), rhs.data.len());
-        let mut result_vec:Vec<u64> = Vec::with_capacity(max_len);
-        let mut carry:bool = false; // the carry bit
-        for i in 0..max_len {
-            // compute next digit and carry
-            let lhs_val = if i < self.data.len() { self.data[i] } else { 0 };
-            let rhs_val = if i < rhs.data.len() { rhs.data[i] } else { 0 };
-            let (sum, new_carry) = overflowing_add(lhs_val, rhs_val, carry);
-            // store them
-            result_vec.push(sum);
-            carry = new_carry;
+    // Put together a number from its digits in base `DECIMAL_CHUNK`, least significant first.
+    fn from_decimal_chunks(chunks: &[Limb]) -> BigUint {
+        let mut levels = 0;
+        while 1 << levels < chunks.len() {
+            levels += 1;
         }
-        if carry {
-            result_vec.push(1);
+        BigUint::from_decimal_chunks_with(chunks, &decimal_powers(levels))
+    }
+
+    // Split the chunks such that the lower part is `2^level` chunks long, compute both parts, and
+    // combine them with `powers[level] = DECIMAL_CHUNK^(2^level)`.
+    fn from_decimal_chunks_with(chunks: &[Limb], powers: &[BigUint]) -> BigUint {
+        if chunks.len() <= DECIMAL_SPLIT_THRESHOLD {
+            let mut result = BigUint::zero();
+            for &chunk in chunks.iter().rev() {
+                result.mul_add_digit(DECIMAL_CHUNK as Limb, chunk);
+            }
+            return result;
+        }
+        let mut level = 0;
+        while 2 << level < chunks.len() {
+            level += 1;
         }
-        // We know that the invariant holds: overflowing_add would only return (0, false) if
-        // the arguments are (0, 0, false), but we know that in the last iteration, one od the two digits
-        // is the last of its number and hence not 0.
-        BigInt { data: result_vec }
+        let (low, high) = chunks.split_at(1 << level);
+        let high = BigUint::from_decimal_chunks_with(high, powers);
+        high * &powers[level] + BigUint::from_decimal_chunks_with(low, powers)
     }
-}
 
-impl<'a> ops::Add<BigInt> for &'a BigInt {
-    type Output = BigInt;
-    #[inline]
-    fn add(self, rhs: BigInt) -> Self::Output {
-        self + &rhs
+    /// Multiply by a `u64`, without first turning it into a `BigUint`.
+    pub fn mul_u64(&self, factor: u64) -> BigUint {
+        if factor == 0 || self.data.is_empty() {
+            return BigUint::zero();
+        }
+        let factor = match small_limb(factor) {
+            Some(factor) => factor,
+            None => return self * &BigUint::new(factor),
+        };
+        let mut result = Digits::with_capacity(self.data.len() + 1);
+        let mut carry = 0;
+        for &digit in self.data.iter() {
+            let (low, high) = mul_add_carry(digit, factor, 0, carry);
+            result.push(low);
+            carry = high;
+        }
+        if carry != 0 {
+            result.push(carry);
+        }
+        BigUint { data: result }
     }
-}
 
-impl<'a> ops::Add<&'a BigInt> for BigInt {
-    type Output = BigInt;
-    #[inline]
-    fn add(self, rhs: &'a BigInt) -> Self::Output {
-        &self + rhs
+    /// Add a `u64`, without first turning it into a `BigUint`.
+    pub fn add_u64(&self, addend: u64) -> BigUint {
+        let addend = Digits::from_u64(addend);
+        let len = cmp::max(self.data.len(), addend.len());
+        let mut result = Digits::with_capacity(len + 1);
+        result.extend_from_slice(&self.data);
+        result.resize(len, 0);
+        if add_slices_in_place(&mut result, &addend) {
+            result.push(1);
+        }
+        BigUint { data: result }
     }
-}
 
-impl ops::Add<BigInt> for BigInt {
-    type Output = BigInt;
-    #[inline]
-    fn add(self, rhs: BigInt) -> Self::Output {
-        &self + &rhs
+    /// Divide by a `u64`, returning the quotient and the remainder. Panics if `divisor` is 0.
+    pub fn div_rem_u64(&self, divisor: u64) -> (BigUint, u64) {
+        assert!(divisor != 0, "Division of BigUint by zero");
+        let divisor_limb = match small_limb(divisor) {
+            Some(divisor) => divisor,
+            None => {
+                let (quotient, rem) = self.div_rem(&BigUint::new(divisor));
+                return (quotient, rem.low_u64());
+            }
+        };
+        let mut quotient = Digits::zeroed(self.data.len());
+        let mut rem: Limb = 0;
+        // Go most significant digit first; the remainder so far becomes the upper half of the next
+        // two-digit dividend.
+        for i in (0..self.data.len()).rev() {
+            let cur = ((rem as DoubleLimb) << LIMB_BITS) | (self.data[i] as DoubleLimb);
+            quotient[i] = (cur / divisor_limb as DoubleLimb) as Limb;
+            rem = (cur % divisor_limb as DoubleLimb) as Limb;
+        }
+        (BigUint::from_digits(quotient), limb_to_u64(rem))
     }
-}
 
-impl<'a, 'b> ops::Sub<&'a BigInt> for &'b BigInt {
-    type Output = BigInt;
-    fn sub(self, rhs: &'a BigInt) -> Self::Output {
-        let max_len = cmp::max(self.data.len(), rhs.data.len());
-        let mut result_vec:Vec<u64> = Vec::with_capacity(max_len);
-        let mut carry:bool = false; // the carry bit
-        for i in 0..max_len {
-            // compute next digit and carry
-            let lhs_val = if i < self.data.len() { self.data[i] } else { 0 };
-            let rhs_val = if i < rhs.data.len() { rhs.data[i] } else { 0 };
-            let (sum, new_carry) = overflowing_sub(lhs_val, rhs_val, carry);
-            // store them
-            result_vec.push(sum);
-            carry = new_carry;
+    /// Iterate over the digits of the number in the given base, least significant first. The digits
+    /// are computed as they are asked for, so a long number can be streamed without building the
+    /// whole string. 0 has no digits at all. Panics if `base` is less than 2.
+    pub fn digits(&self, base: u64) -> impl Iterator<Item = u64> {
+        assert!(base >= 2, "digits: base must be at least 2 - found {}", base);
+        // Every division by a single digit is a pass over the whole number, so we split off as many
+        // digits of the base as fit into one at once, and hand them out one by one.
+        let (chunk_len, chunk_factor) = radix_chunk(base);
+        let mut rest = self.clone();
+        let (mut chunk, mut chunk_digits) = (0u64, 0);
+        iter::from_fn(move || {
+            if chunk_digits == 0 {
+                if rest.is_zero() {
+                    return None;
+                }
+                let (quotient, rem) = rest.div_rem_u64(chunk_factor);
+                rest = quotient;
+                chunk = rem;
+                chunk_digits = chunk_len;
+            }
+            // The topmost chunk is not padded with zeros.
+            if rest.is_zero() && chunk == 0 {
+                return None;
+            }
+            chunk_digits -= 1;
+            let digit = chunk % base;
+            chunk /= base;
+            Some(digit)
+        })
+    }
+
+    // Render the number in base 10, most significant digit first. Peeling off digits one chunk at a
+    // time takes a pass over the whole number per chunk, which is quadratic with a large constant.
+    // Instead, we split the number in two halves by dividing by a power of 10 whose length is about
+    // half of ours, and convert the halves recursively. The divisors `DECIMAL_CHUNK^(2^k)` are
+    // computed by repeated squaring.
+    fn to_decimal_string(&self) -> String {
+        if self.data.is_empty() {
+            return "0".to_string();
         }
-        if carry {
-            panic!("Wrapping subtraction of BigInt");
+        // We need `DECIMAL_CHUNK^(2^levels)` to be larger than the number. A bit is worth less than
+        // 0.31 decimal digits.
+        let max_len = self.data.len() * LIMB_BITS as usize * 31 / 100 + 1;
+        let mut levels = 1;
+        while DECIMAL_CHUNK_LEN << levels < max_len {
+            levels += 1;
         }
-        // We may have trailing zeroes, so get rid of them
-        BigInt::from_vec(result_vec)
+        let mut s = String::new();
+        self.push_decimal(&decimal_powers(levels), levels, false, &mut s);
+        s
     }
-}
 
-impl<'a> ops::Sub<BigInt> for &'a BigInt {
-    type Output = BigInt;
-    #[inline]
-    fn sub(self, rhs: BigInt) -> Self::Output {
-        self - &rhs
+    // Append the decimal digits of the number, which must be less than `powers[level - 1]^2`, that is,
+    // `DECIMAL_CHUNK^(2^level)`. If `pad` is set, the result is padded with zeros to exactly that many
+    // digits, since it is the lower half of a larger number.
+    fn push_decimal(&self, powers: &[BigUint], level: usize, pad: bool, out: &mut String) {
+        if level == 0 || self.data.len() <= DECIMAL_SPLIT_THRESHOLD {
+            let width = if pad { DECIMAL_CHUNK_LEN << level } else { 0 };
+            self.push_decimal_chunks(width, out);
+            return;
+        }
+        let (high, low) = self.div_rem(&powers[level - 1]);
+        if pad || !high.data.is_empty() {
+            high.push_decimal(powers, level - 1, pad, out);
+            low.push_decimal(powers, level - 1, true, out);
+        } else {
+            low.push_decimal(powers, level - 1, false, out);
+        }
     }
-}
 
-impl<'a> ops::Sub<&'a BigInt> for BigInt {
-    type Output = BigInt;
-    #[inline]
-    fn sub(self, rhs: &'a BigInt) -> Self::Output {
-        &self - rhs
+    // Append the decimal digits by peeling off one chunk at a time, padded with zeros to `width`.
+    // This is faster than splitting for small numbers.
+    fn push_decimal_chunks(&self, width: usize, out: &mut String) {
+        let mut chunks = Vec::new();
+        let mut rest = self.clone();
+        while !rest.data.is_empty() {
+            let (quotient, chunk) = rest.div_rem_u64(DECIMAL_CHUNK);
+            chunks.push(chunk);
+            rest = quotient;
+        }
+        let start = out.len();
+        if let Some(top) = chunks.pop() {
+            write!(out, "{}", top).unwrap();
+        }
+        // All but the most significant chunk have to be padded with zeros.
+        for chunk in chunks.iter().rev() {
+            write!(out, "{:01$}", chunk, DECIMAL_CHUNK_LEN).unwrap();
+        }
+        let len = out.len() - start;
+        if len < width {
+            out.insert_str(start, &"0".repeat(width - len));
+        }
     }
-}
 
-impl ops::Sub<BigInt> for BigInt {
-    type Output = BigInt;
-    #[inline]
-    fn sub(self, rhs: BigInt) -> Self::Output {
-        &self - &rhs
+    /// Render the number in base 10, with digits grouped according to the given locale.
+    pub fn format_localized(&self, locale: &Locale) -> String {
+        locale.group_digits(&self.to_decimal_string())
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use std::u64;
-    use super::{overflowing_add,overflowing_sub,BigInt,Minimum,vec_min};
+    // Render the number in base 16, most significant digit first.
+    fn to_hex_string(&self, upper: bool) -> String {
+        let mut s = String::new();
+        match self.data.split_last() {
+            None => s.push('0'),
+            Some((top, rest)) => {
+                if upper { write!(s, "{:X}", top) } else { write!(s, "{:x}", top) }.unwrap();
+                let width = LIMB_BITS as usize / 4;
+                for digit in rest.iter().rev() {
+                    if upper { write!(s, "{:01$X}", digit, width) } else { write!(s, "{:01$x}", digit, width) }.unwrap();
+                }
+            }
+        }
+        s
+    }
 
-    #[test]
-    fn test_min() {
-        let b1 = BigInt::new(1);
-        let b2 = BigInt::new(42);
-        let b3 = BigInt::from_vec(vec![0, 1]);
+    // Render the number in scientific notation. This mimics what the standard library does for
+    // the primitive integer types: Trailing zeros are dropped, and an explicit precision rounds
+    // half-to-even.
+    fn fmt_exp(&self, f: &mut fmt::Formatter, upper: bool) -> fmt::Result {
+        let digits = self.to_decimal_string().into_bytes();
+        let mut exponent = digits.len() - 1;
+        let mantissa = match f.precision() {
+            None => {
+                let mut m = digits;
+                while m.len() > 1 && m[m.len() - 1] == b'0' {
+                    m.pop();
+                }
+                m
+            }
+            Some(prec) if prec + 1 >= digits.len() => {
+                let mut m = digits;
+                m.resize(prec + 1, b'0');
+                m
+            }
+            Some(prec) => {
+                let (kept, dropped) = digits.split_at(prec + 1);
+                let mut m = kept.to_vec();
+                let round_up = match dropped[0] {
+                    b'0'..=b'4' => false,
+                    b'5' if dropped[1..].iter().all(|&d| d == b'0') => (m[prec] - b'0') % 2 == 1,
+                    _ => true,
+                };
+                if round_up {
+                    // Propagate the carry. If it runs off the front, we gain a digit and drop one at
+                    // the back to keep the precision.
+                    let mut idx = m.len();
+                    loop {
+                        if idx == 0 {
+                            m.insert(0, b'1');
+                            m.pop();
+                            exponent += 1;
+                            break;
+                        }
+                        idx -= 1;
+                        if m[idx] == b'9' {
+                            m[idx] = b'0';
+                        } else {
+                            m[idx] += 1;
+                            break;
+                        }
+                    }
+                }
+                m
+            }
+        };
+        let mut s = String::with_capacity(mantissa.len() + 8);
+        s.push(mantissa[0] as char);
+        if mantissa.len() > 1 {
+            s.push('.');
+            s.extend(mantissa[1..].iter().map(|&d| d as char));
+        }
+        s.push(if upper { 'E' } else { 'e' });
+        write!(s, "{}", exponent)?;
+        f.pad_integral(true, "", &s)
+    }
 
-        assert_eq!(b1.min(&b2), &b1);
-        assert_eq!(b2.min(&b1), &b1);
-        assert_eq!(b3.min(&b2), &b2);
-        assert_eq!(b2.min(&b3), &b2);
+    /// Return the nth power-of-2 as BigUint
+    pub fn power_of_2(mut power: u64) -> BigUint {
+        let mut data = Digits::new();
+        while power >= LIMB_BITS as u64 {
+            data.push(0);
+            power -= LIMB_BITS as u64;
+        }
+        data.push(1 << power);
+        BigUint { data }
     }
 
-    #[test]
-    fn test_vec_min() {
-    let b1 = BigInt::new(1);
-        let b2 = BigInt::new(42);
-        let b3 = BigInt::from_vec(vec![0, 1]);
+    /// Check whether the number is even. 0 is even.
+    pub fn is_even(&self) -> bool {
+        self.data.first().map_or(0, |&d| d & 1) == 0
+    }
 
-        assert_eq!(vec_min(&vec![b2.clone(), b1.clone(), b3.clone()]), Some(&b1));
-        assert_eq!(vec_min(&vec![b2.clone(), b3.clone()]), Some(&b2));
-        assert_eq!(vec_min(&vec![b3.clone()]), Some(&b3));
-        assert_eq!(vec_min::<BigInt>(&vec![]), None);
+    /// Check whether the number is odd.
+    pub fn is_odd(&self) -> bool {
+        !self.is_even()
     }
 
-    #[test]
-    fn test_overflowing_add() {
-        assert_eq!(overflowing_add(10, 100, false), (110, false));
-        assert_eq!(overflowing_add(10, 100, true), (111, false));
-        assert_eq!(overflowing_add(1 << 63, 1 << 63, false), (0, true));
-        assert_eq!(overflowing_add(1 << 63, 1 << 63, true), (1, true));
-        assert_eq!(overflowing_add(1 << 63, (1 << 63) -1 , true), (0, true));
+    /// The number of bits needed to represent this number, i.e., the position of the highest set
+    /// bit plus one. This is 0 for 0.
+    pub fn bit_length(&self) -> u64 {
+        match self.data.last() {
+            None => 0,
+            Some(top) => (self.data.len() as u64) * LIMB_BITS as u64 - (top.leading_zeros() as u64),
+        }
     }
 
-    #[test]
-    fn test_overflowing_sub() {
-        assert_eq!(overflowing_sub(100, 10, false), (90, false));
-        assert_eq!(overflowing_sub(100, 10, true), (89, false));
-        assert_eq!(overflowing_sub(10, 1 << 63, false), ((1 << 63) + 10, true));
-        assert_eq!(overflowing_sub(10, 1 << 63, true), ((1 << 63) + 9, true));
-        assert_eq!(overflowing_sub(42, 42 , true), (u64::max_value(), true));
+    /// The number of bits that are set.
+    pub fn count_ones(&self) -> u64 {
+        self.data.iter().map(|digit| digit.count_ones() as u64).sum()
     }
 
-    #[test]
-    fn test_add() {
-        let b1 = BigInt::new(1 << 32);
-        let b2 = BigInt::from_vec(vec![0, 1]);
-        let b3 = BigInt::from_vec(vec![0, 0, 1]);
-        let b4 = BigInt::new(1 << 63);
+    /// The number of zero bits below the lowest set bit, or `None` if the number is 0 (which has no
+    /// set bit at all).
+    pub fn trailing_zeros(&self) -> Option<u64> {
+        // Thanks to our invariant, a non-zero number has a non-zero digit somewhere.
+        let idx = self.data.iter().position(|&digit| digit != 0)?;
+        Some((idx as u64) * LIMB_BITS as u64 + (self.data[idx].trailing_zeros() as u64))
+    }
 
-        assert_eq!(&b1 + &b2, BigInt::from_vec(vec![1 << 32, 1]));
-        assert_eq!(&b2 + &b1, BigInt::from_vec(vec![1 << 32, 1]));
-        assert_eq!(&b2 + &b3, BigInt::from_vec(vec![0, 1, 1]));
-        assert_eq!(&b2 + &b3 + &b4 + &b4, BigInt::from_vec(vec![0, 2, 1]));
-        assert_eq!(&b2 + &b4 + &b3 + &b4, BigInt::from_vec(vec![0, 2, 1]));
-        assert_eq!(&b4 + &b2 + &b3 + &b4, BigInt::from_vec(vec![0, 2, 1]));
+    /// Check whether the number is a power of two, that is, whether exactly one bit is set.
+    pub fn is_power_of_two(&self) -> bool {
+        match self.data.split_last() {
+            None => false,
+            Some((top, rest)) => top.is_power_of_two() && rest.iter().all(|&digit| digit == 0),
+        }
     }
 
-    #[test]
-    fn test_sub() {
-        let b1 = BigInt::new(1 << 32);
-        let b2 = BigInt::from_vec(vec![0, 1]);
-        let b3 = BigInt::from_vec(vec![0, 0, 1]);
-        let b4 = BigInt::new(1 << 63);
+    /// The base-2 logarithm, rounded down. Like for the primitive integers, this panics for 0.
+    pub fn ilog2(&self) -> u64 {
+        assert!(!self.data.is_empty(), "argument of integer logarithm must be positive");
+        self.bit_length() - 1
+    }
 
-        assert_eq!(&b2 - &b1, BigInt::from_vec(vec![u64::max_value() - (1 << 32) + 1]));
-        assert_eq!(&b3 - &b2, BigInt::from_vec(vec![0, u64::max_value(), 0]));
-        assert_eq!(&b2 - &b4 - &b4, BigInt::new(0));
-        assert_eq!(&b3 - &b2 - &b4 - &b4, BigInt::from_vec(vec![0, u64::max_value() - 1]));
-        assert_eq!(&b3 - &b4 - &b2 - &b4, BigInt::from_vec(vec![0, u64::max_value() - 1]));
-        assert_eq!(&b3 - &b4 - &b4 - &b2, BigInt::from_vec(vec![0, u64::max_value() - 1]));
+    /// The base-10 logarithm, rounded down, that is, the number of decimal digits minus one. Panics
+    /// for 0.
+    pub fn ilog10(&self) -> u64 {
+        // With `b` bits, the number lies in `[2^(b-1), 2^b)`, and its logarithm in
+        // `[(b-1) * log10(2), b * log10(2))`. This range is shorter than 1, so rounding down the
+        // lower end is at most one off. We use a slightly too small `log10(2)`, which keeps the
+        // estimate from being too large, and then check whether the number reaches the next power.
+        let bits = self.ilog2();
+        let estimate = (bits as u128 * 30_102_999_566_398_119 / 100_000_000_000_000_000) as u64;
+        if *self >= BigUint::new(10).pow(estimate as u32 + 1) {
+            estimate + 1
+        } else {
+            estimate
+        }
     }
 
-    #[test]
-    #[should_panic(expected = "Wrapping subtraction of BigInt")]
-    fn test_sub_panic1() {
-        let _ = BigInt::new(1) - BigInt::new(5);
+    /// Check whether bit `i` is set. Bits beyond the `bit_length` are all 0.
+    pub fn get_bit(&self, i: u64) -> bool {
+        match self.data.get((i / LIMB_BITS as u64) as usize) {
+            None => false,
+            Some(digit) => (digit >> (i % LIMB_BITS as u64)) & 1 == 1,
+        }
     }
 
-    #[test]
-    #[should_panic(expected = "Wrapping subtraction of BigInt")]
-    fn test_sub_panic2() {
-        let _ = BigInt::from_vec(vec![5,8,3,33,1<<13,46,1<<49, 1, 583,1<<60,2533]) - BigInt::from_vec(vec![5,8,3,33,1<<13,46,1<<49, 5, 583,1<<60,2533]);
+    /// Set bit `i` to the given value, growing the number if necessary.
+    pub fn set_bit(&mut self, i: u64, value: bool) {
+        let idx = (i / LIMB_BITS as u64) as usize;
+        let mask = 1 << (i % LIMB_BITS as u64);
+        if value {
+            if idx >= self.data.len() {
+                self.data.resize(idx + 1, 0);
+            }
+            self.data[idx] |= mask;
+        } else if idx < self.data.len() {
+            self.data[idx] &= !mask;
+            // We may have cleared the highest bit, so restore our invariant.
+            while self.data.last() == Some(&0) {
+                self.data.pop();
+            }
+        }
     }
 
-    #[test]
-    fn test_inc1() {
-        let mut b = BigInt::new(0);
-        b.inc1();
-        assert_eq!(b, BigInt::new(1));
-        b.inc1();
-        assert_eq!(b, BigInt::new(2));
+    /// Flip bit `i`.
+    pub fn flip_bit(&mut self, i: u64) {
+        let value = !self.get_bit(i);
+        self.set_bit(i, value);
+    }
 
-        b = BigInt::new(u64::MAX);
-        b.inc1();
-        assert_eq!(b, BigInt::from_vec(vec![0, 1]));
-        b.inc1();
-        assert_eq!(b, BigInt::from_vec(vec![1, 1]));
+    /// Convert to the nearest `f64`, rounding ties to even like `as` does for primitive integers.
+    /// Numbers too large for `f64` become `f64::INFINITY`.
+    pub fn to_f64(&self) -> f64 {
+        let bits = self.bit_length();
+        if bits <= 64 {
+            return self.low_u64() as f64;
+        }
+        if bits > 1024 {
+            return f64::INFINITY;
+        }
+        // Take the top 64 bits, and let the conversion of that `u64` do the rounding. That only works
+        // if it knows whether any of the bits below are set, so we put that into the lowest bit: we
+        // keep 11 more bits than `f64` has, and it does not matter which of these is set.
+        let shift = bits - 64;
+        let mut top = (self >> (shift as usize)).low_u64();
+        if self.trailing_zeros().unwrap() < shift {
+            top |= 1;
+        }
+        // Multiplying by a power of 2 is exact, unless it overflows to infinity. We build the power
+        // from its bits, since `powi` is not available without the standard library.
+        let scale = if shift < 1024 { f64::from_bits((shift + 1023) << 52) } else { f64::INFINITY };
+        top as f64 * scale
     }
 
-    #[test]
-    fn test_power_of_2() {
-        assert_eq!(BigInt::power_of_2(0), BigInt::new(1));
-        assert_eq!(BigInt::power_of_2(13), BigInt::new(1 << 13));
-        assert_eq!(BigInt::power_of_2(64), BigInt::from_vec(vec![0, 1]));
-        assert_eq!(BigInt::power_of_2(96), BigInt::from_vec(vec![0, 1 << 32]));
-        assert_eq!(BigInt::power_of_2(128), BigInt::from_vec(vec![0, 0, 1]));
+    /// Divide by `divisor`, returning the quotient and the remainder. Panics if `divisor` is 0.
+    pub fn div_rem(&self, divisor: &BigUint) -> (BigUint, BigUint) {
+        assert!(!divisor.data.is_empty(), "Division of BigUint by zero");
+        if *self < *divisor {
+            return (BigUint::new(0), self.clone());
+        }
+        if divisor.data.len() == 1 {
+            let (quotient, rem) = self.div_rem_u64(limb_to_u64(divisor.data[0]));
+            return (quotient, BigUint::new(rem));
+        }
+        // This is Knuth's "Algorithm D" (The Art of Computer Programming, Vol. 2, 4.3.1). We first
+        // normalize the numbers by shifting them such that the most significant bit of the divisor is
+        // set; this makes guessing the next digit of the quotient much more precise.
+        let shift = divisor.data[divisor.data.len() - 1].leading_zeros() as usize;
+        let v = (divisor << shift).data;
+        let mut u = (self << shift).data;
+        if u.len() == self.data.len() {
+            u.push(0);
+        }
+        let n = v.len();
+        let m = u.len() - n - 1;
+        let mut quotient = vec![0; m + 1];
+        for j in (0..m + 1).rev() {
+            // Estimate the next digit from the top two digits of the current remainder and the top
+            // digit of the divisor. The estimate is at most 2 too large, and the loop fixes most of
+            // these cases by also taking the second digit of the divisor into account.
+            let top = ((u[j + n] as DoubleLimb) << LIMB_BITS) | (u[j + n - 1] as DoubleLimb);
+            let mut qhat = top / (v[n - 1] as DoubleLimb);
+            let mut rhat = top % (v[n - 1] as DoubleLimb);
+            while qhat > Limb::MAX as DoubleLimb
+                || qhat * (v[n - 2] as DoubleLimb) > ((rhat << LIMB_BITS) | (u[j + n - 2] as DoubleLimb)) {
+                qhat -= 1;
+                rhat += v[n - 1] as DoubleLimb;
+                if rhat > Limb::MAX as DoubleLimb {
+                    break;
+                }
+            }
+            // Subtract `qhat * v` from the current window of the remainder.
+            let mut mul_carry: Limb = 0;
+            let mut borrow = false;
+            for i in 0..n {
+                let prod = qhat * (v[i] as DoubleLimb) + (mul_carry as DoubleLimb);
+                mul_carry = (prod >> LIMB_BITS) as Limb;
+                let (diff, new_borrow) = sub_with_borrow(u[j + i], prod as Limb, borrow);
+                u[j + i] = diff;
+                borrow = new_borrow;
+            }
+            let (diff, new_borrow) = sub_with_borrow(u[j + n], mul_carry, borrow);
+            u[j + n] = diff;
+            if new_borrow {
+                // Our estimate was still one too large, so we have to add back one `v`. This is rare.
+                qhat -= 1;
+                let carry = add_slices_in_place(&mut u[j..j + n], &v);
+                u[j + n] = u[j + n].wrapping_add(carry as Limb);
+            }
+            quotient[j] = qhat as Limb;
+        }
+        // What is left in `u` is the remainder, we just have to undo the normalization.
+        u.truncate(n);
+        (BigUint::from_digits(Digits::from_vec(quotient)), BigUint::from_digits(u) >> shift)
+    }
+
+    /// Subtract `other`, or return `None` if the result would be negative.
+    pub fn checked_sub(&self, other: &BigUint) -> Option<BigUint> {
+        if *self < *other { None } else { Some(self - other) }
+    }
+
+    /// Compute the distance `|self - other|`.
+    pub fn abs_diff(&self, other: &BigUint) -> BigUint {
+        if *self < *other { other - self } else { self - other }
+    }
+
+    /// Subtract `other`, or return 0 if the result would be negative.
+    pub fn saturating_sub(&self, other: &BigUint) -> BigUint {
+        self.checked_sub(other).unwrap_or_default()
+    }
+
+    /// Divide by `divisor`, or return `None` if `divisor` is 0.
+    pub fn checked_div(&self, divisor: &BigUint) -> Option<BigUint> {
+        if divisor.data.is_empty() { None } else { Some(self / divisor) }
+    }
+
+    /// Compute the remainder modulo `divisor`, or return `None` if `divisor` is 0.
+    pub fn checked_rem(&self, divisor: &BigUint) -> Option<BigUint> {
+        if divisor.data.is_empty() { None } else { Some(self % divisor) }
+    }
+
+    /// Raise the number to the given power.
+    pub fn pow(&self, exp: u32) -> BigUint {
+        // The temporaries add up to at most twice the size of the result, per sequence. Allocating
+        // all of that up front means we do not have to go back to the allocator at all.
+        let result_len = (self.bit_length() * exp as u64 / LIMB_BITS as u64 + 1) as usize;
+        self.pow_in(exp, &BigIntArena::with_capacity(4 * result_len + 2))
+    }
+
+    /// Raise the number to the given power, taking the temporaries from `arena`.
+    pub fn pow_in(&self, mut exp: u32, arena: &BigIntArena) -> BigUint {
+        // Binary exponentiation: `base` runs through `self^(2^i)`, and we multiply those into the
+        // result for which bit `i` of the exponent is set.
+        if self.data.is_empty() {
+            return if exp == 0 { BigUint::new(1) } else { BigUint::new(0) };
+        }
+        let mut result: &[Limb] = &[1];
+        let mut base: &[Limb] = &self.data;
+        while exp > 0 {
+            if exp & 1 == 1 {
+                let product = arena.alloc(result.len() + base.len());
+                mul_digits(product, result, base);
+                result = trim_digits(product);
+            }
+            exp >>= 1;
+            if exp > 0 {
+                let square = arena.alloc(2 * base.len());
+                mul_digits(square, base, base);
+                base = trim_digits(square);
+            }
+        }
+        BigUint::from_digits(Digits::from_slice(result))
+    }
+
+    /// Compute `n!`.
+    pub fn factorial(n: u64) -> BigUint {
+        if n < 2 {
+            return BigUint::new(1);
+        }
+        product_range(2, n)
     }
-}
 
+    /// Compute the binomial coefficient "`n` choose `k`".
+    pub fn binomial(n: u64, k: u64) -> BigUint {
+        if k > n {
+            return BigUint::new(0);
+        }
+        // Use the symmetry to keep the products short.
+        let k = cmp::min(k, n - k);
+        if k == 0 {
+            return BigUint::new(1);
+        }
+        // `n * (n-1) * ... * (n-k+1)` is always divisible by `k!`.
+        product_range(n - k + 1, n) / BigUint::factorial(k)
+    }
 
+    /// Compute the `n`-th Fibonacci number, with `fibonacci(0) == 0` and `fibonacci(1) == 1`.
+    pub fn fibonacci(n: u64) -> BigUint {
+        // We use the "fast doubling" identities
+        //     F(2k)   = F(k) * (2*F(k+1) - F(k))
+        //     F(2k+1) = F(k+1)^2 + F(k)^2
+        // to walk through the bits of `n`, most significant first. `a` and `b` are `F(k)` and `F(k+1)`
+        // for the prefix `k` of `n` that we have seen so far. Notice that `2*F(k+1) - F(k)` cannot
+        // underflow.
+        let mut a = BigUint::new(0);
+        let mut b = BigUint::new(1);
+        for bit in (0..64 - n.leading_zeros()).rev() {
+            let c = &a * &((&b << 1) - &a);
+            let d = &a * &a + &b * &b;
+            if (n >> bit) & 1 == 1 {
+                b = &c + &d;
+                a = d;
+            } else {
+                a = c;
+                b = d;
+            }
+        }
+        a
+    }
+
+    /// Compute the greatest common divisor. By convention, `gcd(0, 0)` is 0.
+    pub fn gcd(&self, other: &BigUint) -> BigUint {
+        // This is Euclid's algorithm.
+        let mut a = self.clone();
+        let mut b = other.clone();
+        while !b.data.is_empty() {
+            let r = &a % &b;
+            a = b;
+            b = r;
+        }
+        a
+    }
+
+    /// Compute `self^exp mod modulus`. Panics if `modulus` is 0.
+    pub fn mod_pow(&self, exp: &BigUint, modulus: &BigUint) -> BigUint {
+        assert!(!modulus.data.is_empty(), "Division of BigUint by zero");
+        // Square-and-multiply, going through the bits of the exponent from the top. Reducing after
+        // every step keeps the numbers small.
+        let base = self % modulus;
+        let mut result = BigUint::new(1) % modulus;
+        for bit in (0..exp.bit_length()).rev() {
+            result = &result * &result % modulus;
+            if exp.get_bit(bit) {
+                result = &result * &base % modulus;
+            }
+        }
+        result
+    }
+
+    /// Compute the Jacobi symbol `(self / n)`, which is 1, -1 or 0. For a prime `n`, this is the
+    /// Legendre symbol: 1 if `self` is a nonzero square modulo `n`, -1 if it is not a square, and 0
+    /// if `n` divides `self`. Panics if `n` is even.
+    pub fn jacobi(&self, n: &BigUint) -> i8 {
+        assert!(n.is_odd(), "The Jacobi symbol is only defined for odd n");
+        // The lowest three bits are all that the rules below look at.
+        fn low_bits(x: &BigUint) -> u64 {
+            x.low_u64() & 7
+        }
+        let mut a = self % n;
+        let mut n = n.clone();
+        let mut result = 1;
+        while !a.data.is_empty() {
+            // Take out the factors of 2: `(2 / n)` is -1 exactly if `n` is 3 or 5 modulo 8.
+            let twos = a.trailing_zeros().unwrap();
+            a >>= twos as usize;
+            if twos % 2 == 1 && (low_bits(&n) == 3 || low_bits(&n) == 5) {
+                result = -result;
+            }
+            // Quadratic reciprocity: Swapping changes the sign exactly if both are 3 modulo 4.
+            if low_bits(&a) & 3 == 3 && low_bits(&n) & 3 == 3 {
+                result = -result;
+            }
+            let r = &n % &a;
+            n = a;
+            a = r;
+        }
+        if n.is_one() { result } else { 0 }
+    }
+
+    /// Compute the inverse of `self` modulo `modulus`: the `x < modulus` with `self * x = 1 (mod
+    /// modulus)`. Returns `None` if `self` and `modulus` are not coprime, in which case there is no
+    /// inverse. Panics if `modulus` is 0.
+    pub fn mod_inverse(&self, modulus: &BigUint) -> Option<BigUint> {
+        assert!(!modulus.data.is_empty(), "Division of BigUint by zero");
+        // This is the extended Euclidean algorithm. Along with the remainders, we keep track of the
+        // factors `t` such that `remainder = self * t (mod modulus)`. We do so modulo `modulus`, so
+        // that they never become negative.
+        let (mut r0, mut r1) = (modulus.clone(), self % modulus);
+        let (mut t0, mut t1) = (BigUint::zero(), BigUint::new(1));
+        while !r1.data.is_empty() {
+            let (q, r) = r0.div_rem(&r1);
+            let t = (&t0 + modulus - &q * &t1 % modulus) % modulus;
+            r0 = r1;
+            r1 = r;
+            t0 = t1;
+            t1 = t;
+        }
+        if r0.is_one() { Some(t0 % modulus) } else { None }
+    }
+
+    /// Solve a system of congruences `x = residue (mod modulus)` with the Chinese Remainder Theorem,
+    /// given as pairs `(residue, modulus)`. The result is the unique solution modulo the product of
+    /// the moduli. Returns `None` if the moduli are not pairwise coprime, or one of them is 0.
+    pub fn crt(residues: &[(BigUint, BigUint)]) -> Option<BigUint> {
+        // We solve the congruences one at a time: If `x` solves the ones so far, whose moduli
+        // multiply to `product`, then `x + product * t` still does, and we choose `t` to also solve
+        // the next one.
+        let mut x = BigUint::zero();
+        let mut product = BigUint::new(1);
+        for (residue, modulus) in residues {
+            if modulus.data.is_empty() {
+                return None;
+            }
+            let inverse = product.mod_inverse(modulus)?;
+            let diff = (residue % modulus + modulus - &x % modulus) % modulus;
+            x = x + &product * &(diff * inverse % modulus);
+            product *= modulus;
+        }
+        Some(x)
+    }
+
+    /// Return the floor of the square root.
+    pub fn isqrt(&self) -> BigUint {
+        if self.data.is_empty() {
+            return BigUint::new(0);
+        }
+        // Newton's iteration `x' = (x + self/x) / 2` decreases monotonically towards the root, as
+        // long as we start above it. `2^ceil(bits/2)` is such a starting point.
+        let mut x = BigUint::power_of_2(self.bit_length().div_ceil(2));
+        loop {
+            let y = (&x + self / &x) >> 1;
+            if y >= x {
+                return x;
+            }
+            x = y;
+        }
+    }
+
+    /// Return the floor of the `n`-th root. Panics if `n` is 0.
+    pub fn nth_root(&self, n: u32) -> BigUint {
+        assert!(n != 0, "The 0th root is not defined");
+        let bits = self.bit_length();
+        if n == 1 || bits <= 1 {
+            return self.clone();
+        }
+        if (n as u64) >= bits {
+            // The number is less than 2^n, so the root is less than 2.
+            return BigUint::new(1);
+        }
+        // This is again Newton's iteration, now for `x^n - self`: `x' = ((n-1)*x + self/x^(n-1)) / n`.
+        // We start above the root, at `2^ceil(bits/n)`.
+        let n_minus_1 = BigUint::new((n - 1) as u64);
+        let mut x = BigUint::power_of_2(bits.div_ceil(n as u64));
+        loop {
+            let (y, _) = (&n_minus_1 * &x + self / x.pow(n - 1)).div_rem_u64(n as u64);
+            if y >= x {
+                return x;
+            }
+            x = y;
+        }
+    }
+
+    /// Check whether the number is the square of some integer.
+    pub fn is_perfect_square(&self) -> bool {
+        // Squares modulo 16 can only be 0, 1, 4 or 9. This filters out most numbers cheaply.
+        let low = self.data.first().map_or(0, |&d| d & 15);
+        if low != 0 && low != 1 && low != 4 && low != 9 {
+            return false;
+        }
+        let root = self.isqrt();
+        &root * &root == *self
+    }
+}
+
+impl Clone for BigUint {
+    fn clone(&self) -> Self {
+        BigUint { data: self.data.clone() }
+    }
+}
+
+impl Default for BigUint {
+    fn default() -> Self {
+        BigUint::zero()
+    }
+}
+
+impl PartialEq for BigUint {
+    fn eq(&self, other: &BigUint) -> bool {
+        debug_assert!(self.test_invariant() && other.test_invariant());
+        self.data == other.data
+    }
+}
+
+impl Minimum for BigUint {
+    // This is essentially the solution to 06.1.
+    fn min<'a>(&'a self, other: &'a Self) -> &'a Self {
+        debug_assert!(self.test_invariant() && other.test_invariant());
+        if self.data.len() < other.data.len() {
+            self
+        } else if self.data.len() > other.data.len() {
+            other
+        } else {
+            // compare back-to-front, i.e., most significant digit first
+            let mut idx = self.data.len();
+            while idx > 0 {
+                idx = idx-1;
+                if self.data[idx] < other.data[idx] {
+                    return self;
+                } else if self.data[idx] > other.data[idx] {
+                    return other;
+                }
+            }
+            // the two are equal
+            return self;
+        }
+    }
+}
+
+// We show the digits in base `2^64` even when the limbs are narrower, so that the output does not
+// depend on the target.
+impl fmt::Debug for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.u64_digits()).finish()
+    }
+}
+
+impl FromStr for BigUint {
+    type Err = ParseBigIntError;
+    /// Parse a number in base 10.
+    fn from_str(s: &str) -> Result<BigUint, ParseBigIntError> {
+        BigUint::from_str_radix(s, 10)
+    }
+}
+
+/// What went wrong while parsing a number.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// There was nothing to parse.
+    Empty,
+    /// A character is not a digit in the requested base.
+    InvalidDigit { found: char, radix: u32 },
+    /// The exponent of a `BigDecimal` is not a number, or out of range.
+    InvalidExponent,
+    /// The denominator of a `BigRational` is 0.
+    ZeroDenominator,
+}
+
+/// An error that occurred while parsing a number. Besides what went wrong, it also records *where* it
+/// went wrong and, if possible, a suggestion how to fix the input. Its `Display` implementation
+/// points at the problem with a caret, the way rustc does:
+///
+/// ```text
+/// invalid digit 'f' for base 10 at byte 2
+///   |
+///   | 12f4
+///   |   ^
+///   = help: did you mean base 16? found 'f'
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseBigIntError {
+    kind: ParseErrorKind,
+    input: String,
+    offset: usize,
+    len: usize,
+    suggestion: Option<String>,
+}
+
+// How many characters of context we show on either side of the problem.
+const ERROR_CONTEXT: usize = 30;
+
+impl ParseBigIntError {
+    // The problem is `len` bytes at byte `offset` of `input`, which must both lie on character
+    // boundaries. The parsers of the other number types build their errors with this, too.
+    pub(crate) fn new(kind: ParseErrorKind, input: &str, offset: usize, len: usize, suggestion: Option<String>) -> Self {
+        debug_assert!(input.is_char_boundary(offset) && input.is_char_boundary(offset + len));
+        ParseBigIntError { kind, input: input.to_string(), offset, len, suggestion }
+    }
+
+    pub(crate) fn invalid_digit(input: &str, offset: usize, found: char, radix: u32) -> Self {
+        let suggestion = suggest_fix(input, offset, found, radix);
+        ParseBigIntError::new(ParseErrorKind::InvalidDigit { found, radix }, input, offset, found.len_utf8(), suggestion)
+    }
+
+    // Turn an error for a part of `input`, starting at byte `start`, into one for all of it.
+    pub(crate) fn within(self, input: &str, start: usize) -> Self {
+        debug_assert!(input[start..].starts_with(&self.input[..]));
+        ParseBigIntError { input: input.to_string(), offset: start + self.offset, ..self }
+    }
+
+    /// What went wrong.
+    pub fn kind(&self) -> &ParseErrorKind {
+        &self.kind
+    }
+
+    /// The complete input that failed to parse.
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+
+    /// The byte offset of the problem within the input.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The part of the input that caused the problem. This is empty if something is *missing*.
+    pub fn snippet(&self) -> &str {
+        &self.input[self.offset..self.offset + self.len]
+    }
+
+    /// A human-readable hint how to fix the input, if we have one.
+    pub fn suggestion(&self) -> Option<&str> {
+        self.suggestion.as_ref().map(|s| &s[..])
+    }
+}
+
+// Try to guess what the user meant when we stumbled over `found` at `offset`.
+fn suggest_fix(input: &str, offset: usize, found: char, radix: u32) -> Option<String> {
+    if found == '-' && offset == 0 {
+        return Some("BigUint is unsigned, so negative numbers cannot be represented; use BigInt for them".to_string());
+    }
+    if found == '+' && offset == 0 {
+        return Some("remove the leading '+'".to_string());
+    }
+    if found == '_' {
+        return Some("remove the digit separators".to_string());
+    }
+    if found.is_whitespace() {
+        return Some("remove the whitespace".to_string());
+    }
+    // Maybe this was written with a Rust-style prefix: a lone `0` as the first digit, possibly after
+    // whitespace and a sign.
+    let before_zero = input[..offset].strip_suffix('0');
+    if before_zero.is_some_and(|rest| rest.chars().all(|c| c.is_whitespace() || c == '+' || c == '-')) {
+        let prefixed = match found {
+            'x' | 'X' => Some(16),
+            'o' | 'O' => Some(8),
+            'b' | 'B' => Some(2),
+            _ => None,
+        };
+        if let Some(base) = prefixed {
+            return Some(format!("drop the '0{}' prefix and parse in base {}", found, base));
+        }
+    }
+    // Maybe all the characters are digits, just in a larger base. We suggest the smallest of the
+    // common bases that would work.
+    let needed = input.chars().try_fold(2, |acc, c| c.to_digit(36).map(|d| cmp::max(acc, d + 1)));
+    match needed {
+        Some(needed) if needed > radix => {
+            let base = [8, 10, 16, 36].iter().cloned().find(|&base| base >= needed && base > radix).unwrap_or(36);
+            Some(format!("did you mean base {}? found '{}'", base, found))
+        }
+        _ => None,
+    }
+}
+
+impl fmt::Display for ParseBigIntError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            ParseErrorKind::Empty => write!(f, "cannot parse number from empty string")?,
+            ParseErrorKind::InvalidDigit { found, radix } =>
+                write!(f, "invalid digit {:?} for base {} at byte {}", found, radix, self.offset)?,
+            ParseErrorKind::InvalidExponent => write!(f, "invalid exponent at byte {}", self.offset)?,
+            ParseErrorKind::ZeroDenominator => write!(f, "zero denominator at byte {}", self.offset)?,
+        }
+        // Show (a window of) the input, and underline the problem. We count characters, not bytes,
+        // to get the caret in the right column.
+        let before: Vec<char> = self.input[..self.offset].chars().collect();
+        let after: Vec<char> = self.input[self.offset..].chars().collect();
+        let snippet_chars = self.snippet().chars().count();
+        let skip = before.len().saturating_sub(ERROR_CONTEXT);
+        let take = cmp::min(after.len(), snippet_chars + ERROR_CONTEXT);
+        let mut line = String::new();
+        if skip > 0 {
+            line.push_str("...");
+        }
+        line.extend(before[skip..].iter());
+        let caret_col = line.chars().count();
+        line.extend(after[..take].iter());
+        if take < after.len() {
+            line.push_str("...");
+        }
+        write!(f, "\n  |\n  | {}\n  | {}{}", line, " ".repeat(caret_col), "^".repeat(cmp::max(snippet_chars, 1)))?;
+        if let Some(ref suggestion) = self.suggestion {
+            write!(f, "\n  = help: {}", suggestion)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for ParseBigIntError {}
+
+/// The error returned when converting an `f64` that is NaN or infinite into a `BigUint` or `BigInt`,
+/// or one that is negative into a `BigUint`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TryFromF64Error {
+    value: f64,
+    target: &'static str,
+}
+
+impl TryFromF64Error {
+    /// The number that could not be converted.
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+}
+
+impl fmt::Display for TryFromF64Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "cannot convert {} to {}", self.value, self.target)
+    }
+}
+
+impl Error for TryFromF64Error {}
+
+/// Convert a finite, non-negative `f64`, dropping the fractional part.
+impl TryFrom<f64> for BigUint {
+    type Error = TryFromF64Error;
+
+    fn try_from(value: f64) -> Result<Self, TryFromF64Error> {
+        if !value.is_finite() || value < 0.0 {
+            return Err(TryFromF64Error { value, target: "BigUint" });
+        }
+        // Take apart the number into `mantissa * 2^exponent`; see the docs of `f64::to_bits`.
+        let bits = value.to_bits();
+        let biased_exponent = ((bits >> 52) & 0x7ff) as i64;
+        let fraction = bits & ((1 << 52) - 1);
+        let (mantissa, exponent) = if biased_exponent == 0 {
+            // Subnormal numbers are all smaller than 1.
+            (fraction, -1074)
+        } else {
+            (fraction | (1 << 52), biased_exponent - 1075)
+        };
+        Ok(if exponent >= 0 {
+            BigUint::new(mantissa) << (exponent as usize)
+        } else if exponent > -64 {
+            BigUint::new(mantissa >> -exponent)
+        } else {
+            BigUint::zero()
+        })
+    }
+}
+
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad_integral(true, "", &self.to_decimal_string())
+    }
+}
+
+impl fmt::LowerHex for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad_integral(true, "0x", &self.to_hex_string(false))
+    }
+}
+
+impl fmt::UpperHex for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad_integral(true, "0x", &self.to_hex_string(true))
+    }
+}
+
+impl fmt::LowerExp for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_exp(f, false)
+    }
+}
+
+impl fmt::UpperExp for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_exp(f, true)
+    }
+}
+
+impl<'a, 'b> ops::Add<&'a BigUint> for &'b BigUint {
+    type Output = BigUint;
+    fn add(self, rhs: &'a BigUint) -> Self::Output {
+        let (long, short) = if self.data.len() >= rhs.data.len() { (self, rhs) } else { (rhs, self) };
+        let mut result_vec = Digits::zeroed(long.data.len());
+        if add_digits(&mut result_vec, &long.data, &short.data) {
+            result_vec.push(1);
+        }
+        // We know that the invariant holds: The top digit of `long` is not 0, so the sum at that
+        // position can only be 0 if there is a carry out of it.
+        BigUint { data: result_vec }
+    }
+}
+
+// How many digits `add_digits` handles per step of its main loop.
+const ADD_CHUNK: usize = 4;
+
+// Compute `a + b` and store it in `out`, which must have the length of `a`; `b` must not be longer.
+// Returns the carry out of the top digit.
+//
+// The straightforward loop spends most of its time on checking which number still has digits left,
+// and on growing the result. Here, we walk over both numbers in lockstep chunks of the same size, so
+// that the compiler knows where all the digits are, and the carry chain becomes a sequence of
+// add-with-carry instructions. Once `b` is exhausted, we only have to propagate the carry, and can
+// copy the rest of `a` as soon as that stops.
+fn add_digits(out: &mut [Limb], a: &[Limb], b: &[Limb]) -> bool {
+    debug_assert!(out.len() == a.len() && a.len() >= b.len());
+    let (out_low, out_high) = out.split_at_mut(b.len());
+    let (a_low, a_high) = a.split_at(b.len());
+    let mut carry = false;
+    let mut out_chunks = out_low.chunks_exact_mut(ADD_CHUNK);
+    let mut a_chunks = a_low.chunks_exact(ADD_CHUNK);
+    let mut b_chunks = b.chunks_exact(ADD_CHUNK);
+    for ((out, a), b) in (&mut out_chunks).zip(&mut a_chunks).zip(&mut b_chunks) {
+        for i in 0..ADD_CHUNK {
+            let (sum, new_carry) = add_with_carry(a[i], b[i], carry);
+            out[i] = sum;
+            carry = new_carry;
+        }
+    }
+    let rest = out_chunks.into_remainder().iter_mut().zip(a_chunks.remainder()).zip(b_chunks.remainder());
+    for ((out, &a), &b) in rest {
+        let (sum, new_carry) = add_with_carry(a, b, carry);
+        *out = sum;
+        carry = new_carry;
+    }
+    for (i, &a) in a_high.iter().enumerate() {
+        if !carry {
+            out_high[i..].copy_from_slice(&a_high[i..]);
+            break;
+        }
+        let (sum, new_carry) = a.overflowing_add(1);
+        out_high[i] = sum;
+        carry = new_carry;
+    }
+    carry
+}
+
+impl<'a> ops::Add<BigUint> for &'a BigUint {
+    type Output = BigUint;
+    #[inline]
+    fn add(self, rhs: BigUint) -> Self::Output {
+        self + &rhs
+    }
+}
+
+impl<'a> ops::Add<&'a BigUint> for BigUint {
+    type Output = BigUint;
+    #[inline]
+    fn add(self, rhs: &'a BigUint) -> Self::Output {
+        &self + rhs
+    }
+}
+
+impl ops::Add<BigUint> for BigUint {
+    type Output = BigUint;
+    #[inline]
+    fn add(self, rhs: BigUint) -> Self::Output {
+        &self + &rhs
+    }
+}
+
+impl<'a, 'b> ops::Sub<&'a BigUint> for &'b BigUint {
+    type Output = BigUint;
+    fn sub(self, rhs: &'a BigUint) -> Self::Output {
+        let max_len = cmp::max(self.data.len(), rhs.data.len());
+        let mut result_vec = Digits::with_capacity(max_len);
+        let mut carry:bool = false; // the carry bit
+        for i in 0..max_len {
+            // compute next digit and carry
+            let lhs_val = if i < self.data.len() { self.data[i] } else { 0 };
+            let rhs_val = if i < rhs.data.len() { rhs.data[i] } else { 0 };
+            let (sum, new_carry) = sub_with_borrow(lhs_val, rhs_val, carry);
+            // store them
+            result_vec.push(sum);
+            carry = new_carry;
+        }
+        if carry {
+            panic!("Wrapping subtraction of BigUint");
+        }
+        // We may have trailing zeroes, so get rid of them
+        BigUint::from_digits(result_vec)
+    }
+}
+
+impl<'a> ops::Sub<BigUint> for &'a BigUint {
+    type Output = BigUint;
+    #[inline]
+    fn sub(self, rhs: BigUint) -> Self::Output {
+        self - &rhs
+    }
+}
+
+impl<'a> ops::Sub<&'a BigUint> for BigUint {
+    type Output = BigUint;
+    #[inline]
+    fn sub(self, rhs: &'a BigUint) -> Self::Output {
+        &self - rhs
+    }
+}
+
+impl ops::Sub<BigUint> for BigUint {
+    type Output = BigUint;
+    #[inline]
+    fn sub(self, rhs: BigUint) -> Self::Output {
+        &self - &rhs
+    }
+}
+
+// The remaining operators are all implemented on references. `forward_binop_by_value!` adds the three
+// variants that take (some of) their arguments by value, just like we did by hand for `Add` and `Sub`.
+
+impl iter::Sum for BigUint {
+    fn sum<I: Iterator<Item = BigUint>>(iter: I) -> BigUint {
+        iter.fold(BigUint::zero(), |acc, x| acc + x)
+    }
+}
+
+impl<'a> iter::Sum<&'a BigUint> for BigUint {
+    fn sum<I: Iterator<Item = &'a BigUint>>(iter: I) -> BigUint {
+        iter.fold(BigUint::zero(), |acc, x| acc + x)
+    }
+}
+
+impl iter::Product for BigUint {
+    fn product<I: Iterator<Item = BigUint>>(iter: I) -> BigUint {
+        iter.fold(BigUint::one(), |acc, x| &acc * &x)
+    }
+}
+
+impl<'a> iter::Product<&'a BigUint> for BigUint {
+    fn product<I: Iterator<Item = &'a BigUint>>(iter: I) -> BigUint {
+        iter.fold(BigUint::one(), |acc, x| &acc * x)
+    }
+}
+
+impl Eq for BigUint {}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &BigUint) -> cmp::Ordering {
+        debug_assert!(self.test_invariant() && other.test_invariant());
+        // Thanks to our invariant, the longer number is the larger one. If the lengths are equal, we
+        // compare most significant digit first, which is exactly what comparing the reversed
+        // digits lexicographically does.
+        self.data.len().cmp(&other.data.len())
+            .then_with(|| self.data.iter().rev().cmp(other.data.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    #[inline]
+    fn partial_cmp(&self, other: &BigUint) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Comparing with a `u64` directly saves constructing a `BigUint` for it.
+impl PartialEq<u64> for BigUint {
+    fn eq(&self, other: &u64) -> bool {
+        *self.data == *Digits::from_u64(*other)
+    }
+}
+
+impl PartialEq<BigUint> for u64 {
+    fn eq(&self, other: &BigUint) -> bool {
+        other == self
+    }
+}
+
+impl PartialOrd<u64> for BigUint {
+    fn partial_cmp(&self, other: &u64) -> Option<cmp::Ordering> {
+        if self.bit_length() > 64 {
+            Some(cmp::Ordering::Greater)
+        } else {
+            self.low_u64().partial_cmp(other)
+        }
+    }
+}
+
+impl PartialOrd<BigUint> for u64 {
+    fn partial_cmp(&self, other: &BigUint) -> Option<cmp::Ordering> {
+        other.partial_cmp(self).map(cmp::Ordering::reverse)
+    }
+}
+
+// Compute `a * b` and store it in `out`, which has to be zero and exactly large enough to hold
+// `a.len() + b.len()` digits. This is the schoolbook method: Multiply `b` by every digit of `a`, and
+// add the result at the right position.
+fn mul_digits(out: &mut [Limb], a: &[Limb], b: &[Limb]) {
+    debug_assert!(out.len() == a.len() + b.len());
+    for (i, &lhs_val) in a.iter().enumerate() {
+        let mut carry: Limb = 0;
+        for (j, &rhs_val) in b.iter().enumerate() {
+            let (low, high) = mul_add_carry(lhs_val, rhs_val, out[i + j], carry);
+            out[i + j] = low;
+            carry = high;
+        }
+        out[i + b.len()] = carry;
+    }
+}
+
+// Drop the leading zeros (at the end) of a slice of digits.
+fn trim_digits(digits: &[Limb]) -> &[Limb] {
+    let len = digits.iter().rposition(|&d| d != 0).map_or(0, |i| i + 1);
+    &digits[..len]
+}
+
+impl ops::Mul<&BigUint> for &BigUint {
+    type Output = BigUint;
+    fn mul(self, rhs: &BigUint) -> Self::Output {
+        if self.data.is_empty() || rhs.data.is_empty() {
+            return BigUint::new(0);
+        }
+        let mut result_vec = Digits::zeroed(self.data.len() + rhs.data.len());
+        mul_digits(&mut result_vec, &self.data, &rhs.data);
+        BigUint::from_digits(result_vec)
+    }
+}
+forward_binop_by_value!(BigUint, impl Mul, mul);
+
+#[cfg(feature = "std")]
+thread_local! {
+    // The scratch space for `*=`, so that multiplying in a loop does not allocate every time.
+    static MUL_SCRATCH: RefCell<Vec<Limb>> = const { RefCell::new(Vec::new()) };
+}
+
+impl ops::MulAssign<&BigUint> for BigUint {
+    fn mul_assign(&mut self, rhs: &BigUint) {
+        #[cfg(feature = "std")]
+        MUL_SCRATCH.with(|scratch| self.mul_assign_with_scratch(rhs, &mut scratch.borrow_mut()));
+        #[cfg(not(feature = "std"))]
+        self.mul_assign_with_scratch(rhs, &mut Vec::new());
+    }
+}
+
+impl ops::MulAssign<BigUint> for BigUint {
+    #[inline]
+    fn mul_assign(&mut self, rhs: BigUint) {
+        *self *= &rhs;
+    }
+}
+
+impl ops::Div<&BigUint> for &BigUint {
+    type Output = BigUint;
+    #[inline]
+    fn div(self, rhs: &BigUint) -> Self::Output {
+        self.div_rem(rhs).0
+    }
+}
+forward_binop_by_value!(BigUint, impl Div, div);
+
+impl ops::Rem<&BigUint> for &BigUint {
+    type Output = BigUint;
+    #[inline]
+    fn rem(self, rhs: &BigUint) -> Self::Output {
+        self.div_rem(rhs).1
+    }
+}
+forward_binop_by_value!(BigUint, impl Rem, rem);
+
+// The bitwise operations work digit by digit. For `&`, the result is as long as the shorter number;
+// for `|` and `^`, the longer number contributes its top digits unchanged.
+impl ops::BitAnd<&BigUint> for &BigUint {
+    type Output = BigUint;
+    fn bitand(self, rhs: &BigUint) -> Self::Output {
+        self.data.iter().zip(rhs.data.iter()).map(|(&a, &b)| a & b).collect()
+    }
+}
+forward_binop_by_value!(BigUint, impl BitAnd, bitand);
+
+impl ops::BitOr<&BigUint> for &BigUint {
+    type Output = BigUint;
+    fn bitor(self, rhs: &BigUint) -> Self::Output {
+        let (long, short) = if self.data.len() >= rhs.data.len() { (self, rhs) } else { (rhs, self) };
+        let mut result = long.data.clone();
+        for (digit, &other) in result.iter_mut().zip(short.data.iter()) {
+            *digit |= other;
+        }
+        BigUint { data: result }
+    }
+}
+forward_binop_by_value!(BigUint, impl BitOr, bitor);
+
+impl ops::BitXor<&BigUint> for &BigUint {
+    type Output = BigUint;
+    fn bitxor(self, rhs: &BigUint) -> Self::Output {
+        let (long, short) = if self.data.len() >= rhs.data.len() { (self, rhs) } else { (rhs, self) };
+        let mut result = long.data.clone();
+        for (digit, &other) in result.iter_mut().zip(short.data.iter()) {
+            *digit ^= other;
+        }
+        BigUint::from_digits(result)
+    }
+}
+forward_binop_by_value!(BigUint, impl BitXor, bitxor);
+
+impl ops::Shl<usize> for &BigUint {
+    type Output = BigUint;
+    fn shl(self, bits: usize) -> Self::Output {
+        if self.data.is_empty() {
+            return BigUint::new(0);
+        }
+        let (digits, bits) = (bits / LIMB_BITS as usize, bits as u32 % LIMB_BITS);
+        let mut result_vec = Digits::with_capacity(digits + self.data.len() + 1);
+        result_vec.resize(digits, 0);
+        if bits == 0 {
+            result_vec.extend_from_slice(&self.data);
+        } else {
+            // Every digit gets its low part shifted up, and the high part carried into the next digit.
+            let mut carry = 0;
+            for &digit in self.data.iter() {
+                result_vec.push((digit << bits) | carry);
+                carry = digit >> (LIMB_BITS - bits);
+            }
+            result_vec.push(carry);
+        }
+        BigUint::from_digits(result_vec)
+    }
+}
+
+impl ops::Shl<usize> for BigUint {
+    type Output = BigUint;
+    #[inline]
+    fn shl(mut self, bits: usize) -> Self::Output {
+        self <<= bits;
+        self
+    }
+}
+
+impl ops::ShlAssign<usize> for BigUint {
+    fn shl_assign(&mut self, bits: usize) {
+        if self.data.is_empty() {
+            return;
+        }
+        let (digits, bits) = (bits / LIMB_BITS as usize, bits as u32 % LIMB_BITS);
+        let len = self.data.len();
+        // Make room at the top, and move the digits up from the most significant one down, so that
+        // we never overwrite a digit before it was moved.
+        if bits == 0 {
+            self.data.resize(len + digits, 0);
+            self.data.copy_within(0..len, digits);
+        } else {
+            self.data.resize(len + digits + 1, 0);
+            let data = &mut self.data;
+            data[len + digits] = data[len - 1] >> (LIMB_BITS - bits);
+            for i in (1..len).rev() {
+                data[i + digits] = (data[i] << bits) | (data[i - 1] >> (LIMB_BITS - bits));
+            }
+            data[digits] = data[0] << bits;
+            if data[len + digits] == 0 {
+                data.pop();
+            }
+        }
+        for digit in self.data[..digits].iter_mut() {
+            *digit = 0;
+        }
+    }
+}
+
+impl ops::Shr<usize> for &BigUint {
+    type Output = BigUint;
+    fn shr(self, bits: usize) -> Self::Output {
+        let (digits, bits) = (bits / LIMB_BITS as usize, bits as u32 % LIMB_BITS);
+        if digits >= self.data.len() {
+            return BigUint::new(0);
+        }
+        let rest = &self.data[digits..];
+        if bits == 0 {
+            return BigUint::from_digits(Digits::from_slice(rest));
+        }
+        // Every digit gets shifted down, and receives the low bits of the next digit at the top.
+        let mut result_vec = Digits::with_capacity(rest.len());
+        for i in 0..rest.len() {
+            let high = if i + 1 < rest.len() { rest[i + 1] << (LIMB_BITS - bits) } else { 0 };
+            result_vec.push((rest[i] >> bits) | high);
+        }
+        BigUint::from_digits(result_vec)
+    }
+}
+
+impl ops::Shr<usize> for BigUint {
+    type Output = BigUint;
+    #[inline]
+    fn shr(mut self, bits: usize) -> Self::Output {
+        self >>= bits;
+        self
+    }
+}
+
+impl ops::ShrAssign<usize> for BigUint {
+    fn shr_assign(&mut self, bits: usize) {
+        let (digits, bits) = (bits / LIMB_BITS as usize, bits as u32 % LIMB_BITS);
+        let len = self.data.len();
+        if digits >= len {
+            self.data.truncate(0);
+            return;
+        }
+        // Move the digits down, least significant first. The digits we read are never below the one
+        // we write.
+        let data = &mut self.data;
+        if bits == 0 {
+            data.copy_within(digits..len, 0);
+        } else {
+            for i in 0..len - digits {
+                let high = if i + digits + 1 < len { data[i + digits + 1] << (LIMB_BITS - bits) } else { 0 };
+                data[i] = (data[i + digits] >> bits) | high;
+            }
+        }
+        data.truncate(len - digits);
+        if data.last() == Some(&0) {
+            data.pop();
+        }
+    }
+}
+
+/// An iterator over the digits (limbs) of a `BigUint`, created by `BigUint::iter`.
+pub struct Iter<'a> {
+    digits: &'a [Limb],
+    // The digits in `front..back` have not been returned yet.
+    front: usize,
+    back: usize,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = Limb;
+
+    fn next(&mut self) -> Option<Limb> {
+        if self.front == self.back {
+            None
+        } else {
+            self.front += 1;
+            Some(self.digits[self.front - 1])
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+}
+
+impl<'a> DoubleEndedIterator for Iter<'a> {
+    fn next_back(&mut self) -> Option<Limb> {
+        if self.front == self.back {
+            None
+        } else {
+            self.back -= 1;
+            Some(self.digits[self.back])
+        }
+    }
+}
+
+impl<'a> ExactSizeIterator for Iter<'a> {}
+
+// Once `front` and `back` meet, they stay together.
+impl<'a> iter::FusedIterator for Iter<'a> {}
+
+impl<'a> IntoIterator for &'a BigUint {
+    type Item = Limb;
+    type IntoIter = Iter<'a>;
+    fn into_iter(self) -> Iter<'a> {
+        self.iter()
+    }
+}
+
+/// Collect limbs, least significant first, into a number. Like `from_vec`, this drops
+/// trailing zeros.
+impl FromIterator<Limb> for BigUint {
+    fn from_iter<I: IntoIterator<Item = Limb>>(iter: I) -> Self {
+        let mut result = BigUint::zero();
+        result.extend(iter);
+        result
+    }
+}
+
+/// Append more significant digits after the current top digit.
+impl Extend<Limb> for BigUint {
+    fn extend<I: IntoIterator<Item = Limb>>(&mut self, iter: I) {
+        for digit in iter {
+            self.data.push(digit);
+        }
+        while self.data.last() == Some(&0) {
+            self.data.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use std::prelude::v1::*;
+    use std::u64;
+    use std::iter;
+    use std::cmp;
+    #[cfg(feature = "std")]
+    use std::fmt::Write;
+    #[cfg(feature = "std")]
+    use snapshot::assert_snapshot;
+    use locale;
+    use super::limbs::add_with_carry;
+    use super::{BigUint,BigInt,Minimum,vec_min,min_ref,min_cloned,ParseErrorKind,sort_bigints,Limb,LIMB_BITS};
+
+    #[test]
+    fn test_min() {
+        let b1 = BigUint::new(1);
+        let b2 = BigUint::new(42);
+        let b3 = BigUint::from_vec(vec![0, 1]);
+
+        // `BigUint` is also `Ord`, whose `min` takes precedence in method-call syntax, so we say which
+        // one we mean.
+        assert_eq!(Minimum::min(&b1, &b2), &b1);
+        assert_eq!(Minimum::min(&b2, &b1), &b1);
+        assert_eq!(Minimum::min(&b3, &b2), &b2);
+        assert_eq!(Minimum::min(&b2, &b3), &b2);
+    }
+
+    #[test]
+    fn test_vec_min() {
+    let b1 = BigUint::new(1);
+        let b2 = BigUint::new(42);
+        let b3 = BigUint::from_vec(vec![0, 1]);
+
+        assert_eq!(vec_min(&vec![b2.clone(), b1.clone(), b3.clone()]), Some(&b1));
+        assert_eq!(vec_min(&vec![b2.clone(), b3.clone()]), Some(&b2));
+        assert_eq!(vec_min(&vec![b3.clone()]), Some(&b3));
+        assert_eq!(vec_min::<BigUint>(&vec![]), None);
+    }
+
+    #[test]
+    fn test_min_ref() {
+        let v: Vec<BigInt> = ["12", "-99999999999999999999999", "-5", "-99999999999999999999999"].iter()
+            .map(|s| s.parse().unwrap()).collect();
+        // The first of several minima, and nothing cloned on the way.
+        assert!(::std::ptr::eq(min_ref(&v).unwrap(), &v[1]));
+        assert_eq!(min_ref(&v[2..]), Some(&v[3]));
+        assert_eq!(min_cloned(&v[..1]), Some(v[0].clone()));
+        assert_eq!(min_ref::<BigInt>(&[]), None);
+        assert_eq!(min_cloned(&[BigUint::new(4), BigUint::new(3)]), Some(BigUint::new(3)));
+    }
+
+    #[test]
+    fn test_add() {
+        let b1 = BigUint::new(1 << 32);
+        let b2 = BigUint::from_vec(vec![0, 1]);
+        let b3 = BigUint::from_vec(vec![0, 0, 1]);
+        let b4 = BigUint::new(1 << 63);
+
+        assert_eq!(&b1 + &b2, BigUint::from_vec(vec![1 << 32, 1]));
+        assert_eq!(&b2 + &b1, BigUint::from_vec(vec![1 << 32, 1]));
+        assert_eq!(&b2 + &b3, BigUint::from_vec(vec![0, 1, 1]));
+        assert_eq!(&b2 + &b3 + &b4 + &b4, BigUint::from_vec(vec![0, 2, 1]));
+        assert_eq!(&b2 + &b4 + &b3 + &b4, BigUint::from_vec(vec![0, 2, 1]));
+        assert_eq!(&b4 + &b2 + &b3 + &b4, BigUint::from_vec(vec![0, 2, 1]));
+    }
+
+    #[test]
+    fn test_sub() {
+        let b1 = BigUint::new(1 << 32);
+        let b2 = BigUint::from_vec(vec![0, 1]);
+        let b3 = BigUint::from_vec(vec![0, 0, 1]);
+        let b4 = BigUint::new(1 << 63);
+
+        assert_eq!(&b2 - &b1, BigUint::from_vec(vec![u64::max_value() - (1 << 32) + 1]));
+        assert_eq!(&b3 - &b2, BigUint::from_vec(vec![0, u64::max_value(), 0]));
+        assert_eq!(&b2 - &b4 - &b4, BigUint::new(0));
+        assert_eq!(&b3 - &b2 - &b4 - &b4, BigUint::from_vec(vec![0, u64::max_value() - 1]));
+        assert_eq!(&b3 - &b4 - &b2 - &b4, BigUint::from_vec(vec![0, u64::max_value() - 1]));
+        assert_eq!(&b3 - &b4 - &b4 - &b2, BigUint::from_vec(vec![0, u64::max_value() - 1]));
+    }
+
+    #[test]
+    #[should_panic(expected = "Wrapping subtraction of BigUint")]
+    fn test_sub_panic1() {
+        let _ = BigUint::new(1) - BigUint::new(5);
+    }
+
+    #[test]
+    #[should_panic(expected = "Wrapping subtraction of BigUint")]
+    fn test_sub_panic2() {
+        let _ = BigUint::from_vec(vec![5,8,3,33,1<<13,46,1<<49, 1, 583,1<<60,2533]) - BigUint::from_vec(vec![5,8,3,33,1<<13,46,1<<49, 5, 583,1<<60,2533]);
+    }
+
+    #[test]
+    fn test_inc1() {
+        let mut b = BigUint::new(0);
+        b.inc1();
+        assert_eq!(b, BigUint::new(1));
+        b.inc1();
+        assert_eq!(b, BigUint::new(2));
+
+        b = BigUint::new(u64::MAX);
+        b.inc1();
+        assert_eq!(b, BigUint::from_vec(vec![0, 1]));
+        b.inc1();
+        assert_eq!(b, BigUint::from_vec(vec![1, 1]));
+    }
+
+    #[test]
+    fn test_power_of_2() {
+        assert_eq!(BigUint::power_of_2(0), BigUint::new(1));
+        assert_eq!(BigUint::power_of_2(13), BigUint::new(1 << 13));
+        assert_eq!(BigUint::power_of_2(64), BigUint::from_vec(vec![0, 1]));
+        assert_eq!(BigUint::power_of_2(96), BigUint::from_vec(vec![0, 1 << 32]));
+        assert_eq!(BigUint::power_of_2(128), BigUint::from_vec(vec![0, 0, 1]));
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(BigUint::new(0).to_string(), "0");
+        assert_eq!(BigUint::new(1337).to_string(), "1337");
+        assert_eq!(BigUint::new(u64::MAX).to_string(), u64::MAX.to_string());
+        assert_eq!(BigUint::from_vec(vec![0, 1]).to_string(), "18446744073709551616");
+        assert_eq!(format!("{:>6}|{:<6}|{:06}", BigUint::new(42), BigUint::new(42), BigUint::new(42)),
+                   "    42|42    |000042");
+    }
+
+    #[test]
+    fn test_display_large() {
+        // Powers of 10 and their neighbors exercise the zero padding of the lower halves.
+        let ten = BigUint::new(10);
+        for &exp in [19, 38, 100, 607, 608, 1215, 1216, 2000, 5000].iter() {
+            let p = ten.pow(exp);
+            assert_eq!(p.to_string(), format!("1{}", "0".repeat(exp as usize)));
+            assert_eq!((&p - &BigUint::new(1)).to_string(), "9".repeat(exp as usize));
+            assert_eq!((&p + &BigUint::new(1)).to_string(), format!("1{}1", "0".repeat(exp as usize - 1)));
+        }
+        let mut seed = 1;
+        for &len in [33, 64, 100, 257].iter() {
+            let b = pseudo_random(&mut seed, len);
+            let mut naive = String::new();
+            b.push_decimal_chunks(0, &mut naive);
+            assert_eq!(b.to_string(), naive);
+            assert_eq!(BigUint::from_str_radix(&naive, 10).unwrap(), b);
+        }
+    }
+
+    #[test]
+    fn test_exp_matches_u64() {
+        // For numbers that fit into a `u64`, we should behave exactly like the standard library.
+        for &n in [0, 1, 25, 35, 125, 135, 99999, 1234567, 10_000_000_000, u64::MAX].iter() {
+            let b = BigUint::new(n);
+            assert_eq!(format!("{:e}", b), format!("{:e}", n));
+            assert_eq!(format!("{:E}", b), format!("{:E}", n));
+            for prec in 0..6 {
+                assert_eq!(format!("{:.*e}", prec, b), format!("{:.*e}", prec, n));
+            }
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_format_snapshot() {
+        let values = [
+            BigUint::new(0),
+            BigUint::new(7),
+            BigUint::new(1 << 63),
+            BigUint::new(u64::MAX),
+            BigUint::from_vec(vec![0, 1]),
+            BigUint::from_vec(vec![1, 0, 1]),
+            BigUint::from_vec(vec![u64::MAX, u64::MAX, u64::MAX]),
+            BigUint::power_of_2(200),
+        ];
+        let mut out = String::new();
+        for b in values.iter() {
+            writeln!(out, "{:?}", b).unwrap();
+            writeln!(out, "  dec  {}", b).unwrap();
+            writeln!(out, "  hex  {:#x}", b).unwrap();
+            writeln!(out, "  HEX  {:X}", b).unwrap();
+            writeln!(out, "  exp  {:e}", b).unwrap();
+            writeln!(out, "  exp3 {:.3E}", b).unwrap();
+        }
+        assert_snapshot("bigint_format", &out);
+    }
+
+    // A simple xorshift generator, so that we can test with "random" numbers without depending on
+    // a crate for that.
+    fn pseudo_random(seed: &mut u64, len: usize) -> BigUint {
+        let mut v = Vec::with_capacity(len);
+        for _ in 0..len {
+            *seed ^= *seed << 13;
+            *seed ^= *seed >> 7;
+            *seed ^= *seed << 17;
+            v.push(*seed);
+        }
+        BigUint::from_vec(v)
+    }
+
+    #[test]
+    fn test_cmp() {
+        let b1 = BigUint::new(1);
+        let b2 = BigUint::from_vec(vec![0, 1]);
+        let b3 = BigUint::from_vec(vec![1, 1]);
+        assert!(b1 < b2 && b2 < b3 && b1 < b3);
+        assert!(b3 > b2 && b2 >= b2 && BigUint::new(0) < b1);
+    }
+
+    #[test]
+    fn test_mul() {
+        assert_eq!(BigUint::new(6) * BigUint::new(7), BigUint::new(42));
+        assert_eq!(BigUint::new(0) * BigUint::from_vec(vec![3, 4]), BigUint::new(0));
+        assert_eq!(BigUint::new(u64::MAX) * BigUint::new(u64::MAX), BigUint::from_vec(vec![1, u64::MAX - 1]));
+        assert_eq!(BigUint::power_of_2(100) * BigUint::power_of_2(70), BigUint::power_of_2(170));
+    }
+
+    #[test]
+    fn test_shifts() {
+        let b = BigUint::from_vec(vec![0x8000_0000_0000_0001, 3]);
+        assert_eq!(&b << 0, b);
+        assert_eq!(&b << 1, BigUint::from_vec(vec![2, 7]));
+        assert_eq!(&b << 128, BigUint::from_vec(vec![0, 0, 0x8000_0000_0000_0001, 3]));
+        assert_eq!(&b >> 1, BigUint::from_vec(vec![0xC000_0000_0000_0000, 1]));
+        assert_eq!(&b >> 65, BigUint::new(1));
+        assert_eq!(&b >> 200, BigUint::new(0));
+        assert_eq!(BigUint::new(0) << 10, BigUint::new(0));
+    }
+
+    #[test]
+    fn test_div_rem() {
+        assert_eq!(BigUint::new(42).div_rem(&BigUint::new(5)), (BigUint::new(8), BigUint::new(2)));
+        assert_eq!(BigUint::new(4).div_rem(&BigUint::power_of_2(80)), (BigUint::new(0), BigUint::new(4)));
+        assert_eq!(BigUint::power_of_2(200) / BigUint::power_of_2(130), BigUint::power_of_2(70));
+        let mut seed = 0x1234_5678_9abc_def0;
+        for len_a in 1..8 {
+            for len_b in 1..len_a + 1 {
+                let a = pseudo_random(&mut seed, len_a);
+                let b = pseudo_random(&mut seed, len_b) >> (len_b * 7);
+                if b == BigUint::new(0) {
+                    continue;
+                }
+                let (q, r) = a.div_rem(&b);
+                assert!(r < b);
+                assert_eq!(&q * &b + &r, a);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Division of BigUint by zero")]
+    fn test_div_by_zero() {
+        let _ = BigUint::new(1) / BigUint::new(0);
+    }
+
+    #[test]
+    fn test_isqrt() {
+        assert_eq!(BigUint::new(0).isqrt(), BigUint::new(0));
+        assert_eq!(BigUint::new(1).isqrt(), BigUint::new(1));
+        assert_eq!(BigUint::new(15).isqrt(), BigUint::new(3));
+        assert_eq!(BigUint::new(16).isqrt(), BigUint::new(4));
+        assert_eq!(BigUint::new(u64::MAX).isqrt(), BigUint::new((1 << 32) - 1));
+        assert_eq!(BigUint::power_of_2(200).isqrt(), BigUint::power_of_2(100));
+        let mut seed = 42;
+        for len in 1..6 {
+            let root = pseudo_random(&mut seed, len);
+            let square = &root * &root;
+            assert_eq!(square.isqrt(), root);
+            assert_eq!((&square - BigUint::new(1)).isqrt(), &root - BigUint::new(1));
+            assert_eq!((&square + &root + &root).isqrt(), root);
+        }
+    }
+
+    #[test]
+    fn test_is_perfect_square() {
+        assert!(BigUint::new(0).is_perfect_square());
+        assert!(BigUint::new(1).is_perfect_square());
+        assert!(BigUint::new(144).is_perfect_square());
+        assert!(!BigUint::new(145).is_perfect_square());
+        assert!(!BigUint::new(2).is_perfect_square());
+        let b = BigUint::from_vec(vec![5, 17, 4]);
+        assert!((&b * &b).is_perfect_square());
+        assert!(!(&b * &b + BigUint::new(1)).is_perfect_square());
+    }
+
+    #[test]
+    fn test_jacobi() {
+        assert_eq!(BigUint::new(2).jacobi(&BigUint::new(7)), 1);
+        assert_eq!(BigUint::new(3).jacobi(&BigUint::new(7)), -1);
+        assert_eq!(BigUint::new(14).jacobi(&BigUint::new(7)), 0);
+        assert_eq!(BigUint::new(1001).jacobi(&BigUint::new(9907)), -1);
+        assert_eq!(BigUint::new(19).jacobi(&BigUint::new(45)), 1);
+        assert_eq!(BigUint::new(8).jacobi(&BigUint::new(21)), -1);
+        assert_eq!(BigUint::new(5).jacobi(&BigUint::new(21)), 1);
+        assert_eq!(BigUint::new(6).jacobi(&BigUint::new(15)), 0);
+        assert_eq!(BigUint::zero().jacobi(&BigUint::new(1)), 1);
+        // For a prime, this agrees with Euler's criterion `a^((p-1)/2) = (a / p) (mod p)`.
+        let p = BigUint::power_of_2(127) - BigUint::new(1);
+        let exp = (&p - BigUint::new(1)) >> 1;
+        let mut seed = 7;
+        for len in 1..4 {
+            let a = pseudo_random(&mut seed, len);
+            let expected = if a.mod_pow(&exp, &p).is_one() { 1 } else { -1 };
+            assert_eq!(a.jacobi(&p), expected);
+        }
+        // It is multiplicative in `n`.
+        let m = BigUint::factorial(25).add_u64(1);
+        let a = BigUint::power_of_2(100).add_u64(3);
+        assert_eq!(a.jacobi(&(&m * &p)), a.jacobi(&m) * a.jacobi(&p));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_jacobi_even() {
+        BigUint::new(3).jacobi(&BigUint::new(8));
+    }
+
+    #[test]
+    fn test_mod_inverse() {
+        assert_eq!(BigUint::new(3).mod_inverse(&BigUint::new(7)), Some(BigUint::new(5)));
+        assert_eq!(BigUint::new(10).mod_inverse(&BigUint::new(7)), Some(BigUint::new(5)));
+        assert_eq!(BigUint::new(6).mod_inverse(&BigUint::new(9)), None);
+        assert_eq!(BigUint::zero().mod_inverse(&BigUint::new(7)), None);
+        assert_eq!(BigUint::new(5).mod_inverse(&BigUint::new(1)), Some(BigUint::zero()));
+        let m = BigUint::power_of_2(127) - BigUint::new(1);
+        let mut seed = 3;
+        for len in 1..4 {
+            let a = pseudo_random(&mut seed, len);
+            let inverse = a.mod_inverse(&m).unwrap();
+            assert!(inverse < m);
+            assert!((&a * &inverse % &m).is_one());
+        }
+    }
+
+    #[test]
+    fn test_crt() {
+        let pairs = |v: &[(u64, u64)]| v.iter().map(|&(r, m)| (BigUint::new(r), BigUint::new(m))).collect::<Vec<_>>();
+        assert_eq!(BigUint::crt(&pairs(&[(2, 3), (3, 5), (2, 7)])), Some(BigUint::new(23)));
+        assert_eq!(BigUint::crt(&pairs(&[(10, 3), (0, 4)])), Some(BigUint::new(4)));
+        assert_eq!(BigUint::crt(&[]), Some(BigUint::zero()));
+        assert_eq!(BigUint::crt(&pairs(&[(1, 4), (3, 6)])), None);
+        assert_eq!(BigUint::crt(&pairs(&[(1, 0)])), None);
+        // Recombining the residues of a large number gives back the number.
+        let moduli = [BigUint::power_of_2(89) - BigUint::new(1), BigUint::power_of_2(107) - BigUint::new(1), BigUint::factorial(20)];
+        let n = BigUint::factorial(40) + BigUint::new(12345);
+        let residues: Vec<_> = moduli.iter().map(|m| (&n % m, m.clone())).collect();
+        assert_eq!(BigUint::crt(&residues), Some(n));
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!("0".parse::<BigUint>(), Ok(BigUint::new(0)));
+        assert_eq!("000042".parse::<BigUint>(), Ok(BigUint::new(42)));
+        assert_eq!("18446744073709551616".parse::<BigUint>(), Ok(BigUint::from_vec(vec![0, 1])));
+        assert_eq!(BigUint::from_str_radix("ffffffffffffffffFFFFFFFFFFFFFFFF", 16),
+                   Ok(BigUint::from_vec(vec![u64::MAX, u64::MAX])));
+        assert_eq!(BigUint::from_str_radix("1000000000000000000000000000000000000000000000000000000000000000000000", 2),
+                   Ok(BigUint::power_of_2(69)));
+        assert_eq!(BigUint::from_str_radix("zz", 36), Ok(BigUint::new(36 * 36 - 1)));
+        let mut seed = 7;
+        for len in 0..5 {
+            let b = pseudo_random(&mut seed, len);
+            assert_eq!(b.to_string().parse::<BigUint>(), Ok(b.clone()));
+            assert_eq!(BigUint::from_str_radix(&format!("{:x}", b), 16), Ok(b));
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let e = "".parse::<BigUint>().unwrap_err();
+        assert_eq!(*e.kind(), ParseErrorKind::Empty);
+        assert_eq!((e.offset(), e.snippet(), e.suggestion()), (0, "", None));
+
+        let e = "12f4".parse::<BigUint>().unwrap_err();
+        assert_eq!(*e.kind(), ParseErrorKind::InvalidDigit { found: 'f', radix: 10 });
+        assert_eq!((e.offset(), e.snippet()), (2, "f"));
+        assert_eq!(e.suggestion(), Some("did you mean base 16? found 'f'"));
+
+        let e = BigUint::from_str_radix("1012", 2).unwrap_err();
+        assert_eq!(e.suggestion(), Some("did you mean base 8? found '2'"));
+        let e = "0x1f".parse::<BigUint>().unwrap_err();
+        assert_eq!(e.suggestion(), Some("drop the '0x' prefix and parse in base 16"));
+        for input in [" 0x1f", "+0x1f"] {
+            let e = input.parse::<BigUint>().unwrap_err();
+            assert_eq!(e.suggestion(), Some("drop the '0x' prefix and parse in base 16"), "{:?}", input);
+        }
+        let e = "-0b101".parse::<BigInt>().unwrap_err();
+        assert_eq!(e.suggestion(), Some("drop the '0b' prefix and parse in base 2"));
+        let e = "10x1f".parse::<BigUint>().unwrap_err();
+        assert_eq!(e.suggestion(), Some("did you mean base 36? found 'x'"));
+        let e = "-5".parse::<BigUint>().unwrap_err();
+        assert_eq!(e.suggestion(), Some("BigUint is unsigned, so negative numbers cannot be represented; use BigInt for them"));
+        let e = BigUint::from_str_radix_strict("1_000", 10).unwrap_err();
+        assert_eq!(e.suggestion(), Some("remove the digit separators"));
+        let e = BigUint::from_str_radix_strict(" 1", 10).unwrap_err();
+        assert_eq!(e.suggestion(), Some("remove the whitespace"));
+        let e = "12€3".parse::<BigUint>().unwrap_err();
+        assert_eq!((e.offset(), e.snippet(), e.suggestion()), (2, "€", None));
+    }
+
+    #[test]
+    fn test_const() {
+        const TEN: BigUint = BigUint::new(10);
+        const NOTHING: BigUint = BigUint::new(0);
+        assert_eq!(TEN, BigUint::from_vec(vec![10]));
+        assert!(NOTHING.test_invariant() && NOTHING.is_zero());
+        assert!(BigUint::one().is_one());
+    }
+
+    #[test]
+    fn test_decimal_long() {
+        // Long enough for both printing and parsing to split the number, in all sorts of lengths.
+        let mut seed = 17;
+        for &len in [33, 64, 65, 100, 300].iter() {
+            let b = pseudo_random(&mut seed, len);
+            let s = b.to_string();
+            assert_eq!(s.parse::<BigUint>(), Ok(b.clone()));
+            assert_eq!(format!("{}7", s).parse::<BigUint>(), Ok(b.mul_u64(10).add_u64(7)));
+            assert_eq!(format!("000{}", s).parse::<BigUint>(), Ok(b));
+        }
+        let s = format!("1{}", "0".repeat(4000));
+        assert_eq!(s.parse::<BigUint>().unwrap(), BigUint::new(10).pow(4000));
+        assert_eq!(BigUint::new(10).pow(4000).to_string(), s);
+        // The powers are computed once, and then shared.
+        let powers = super::decimal_powers(3);
+        assert!(powers.len() >= 3);
+        assert_eq!(powers[2], BigUint::new(10).pow(super::DECIMAL_CHUNK_LEN as u32 * 4));
+        // Only with the standard library, the table is cached.
+        #[cfg(feature = "std")]
+        assert!(::std::sync::Arc::ptr_eq(&powers, &super::decimal_powers(2)));
+    }
+
+    #[test]
+    fn test_parse_lenient() {
+        assert_eq!("1_000_000".parse::<BigUint>(), Ok(BigUint::new(1_000_000)));
+        assert_eq!("  +42\n".parse::<BigUint>(), Ok(BigUint::new(42)));
+        assert_eq!(BigUint::from_str_radix("+ff_ff_", 16), Ok(BigUint::new(0xffff)));
+        assert_eq!(BigUint::from_str_radix_strict("+1", 10).unwrap_err().suggestion(), Some("remove the leading '+'"));
+
+        // The offsets of errors refer to the complete input.
+        let e = " +_1".parse::<BigUint>().unwrap_err();
+        assert_eq!((e.offset(), e.snippet()), (2, "_"));
+        let e = "1 000".parse::<BigUint>().unwrap_err();
+        assert_eq!((e.offset(), e.suggestion()), (1, Some("remove the whitespace")));
+        let e = "++1".parse::<BigUint>().unwrap_err();
+        assert_eq!((e.offset(), e.snippet()), (1, "+"));
+        for &s in ["", "   ", " + "].iter() {
+            assert_eq!(*s.parse::<BigUint>().unwrap_err().kind(), ParseErrorKind::Empty);
+        }
+        assert_eq!(" + ".parse::<BigUint>().unwrap_err().offset(), 2);
+    }
+
+    #[test]
+    fn test_parse_error_display() {
+        let e = "12f4".parse::<BigUint>().unwrap_err();
+        assert_eq!(e.to_string(), "invalid digit 'f' for base 10 at byte 2\n  |\n  | 12f4\n  |   ^\n  = help: did you mean base 16? found 'f'");
+        // The caret counts characters, not bytes.
+        let e = "€€x".parse::<BigUint>().unwrap_err();
+        assert_eq!(e.to_string(), "invalid digit '€' for base 10 at byte 0\n  |\n  | €€x\n  | ^");
+        let e = "".parse::<BigUint>().unwrap_err();
+        assert_eq!(e.to_string(), "cannot parse number from empty string\n  |\n  | \n  | ^");
+        // Long inputs are shortened around the problem.
+        let long = format!("{}x{}", "1".repeat(50), "2".repeat(50));
+        let e = long.parse::<BigUint>().unwrap_err();
+        assert_eq!(e.to_string(), format!("invalid digit 'x' for base 10 at byte 50\n  |\n  | ...{}x{}...\n  | {}^\n  = help: did you mean base 36? found 'x'",
+                                          "1".repeat(30), "2".repeat(30), " ".repeat(33)));
+    }
+
+    #[test]
+    fn test_nth_root() {
+        assert_eq!(BigUint::new(0).nth_root(3), BigUint::new(0));
+        assert_eq!(BigUint::new(1).nth_root(7), BigUint::new(1));
+        assert_eq!(BigUint::new(12345).nth_root(1), BigUint::new(12345));
+        assert_eq!(BigUint::new(26).nth_root(3), BigUint::new(2));
+        assert_eq!(BigUint::new(27).nth_root(3), BigUint::new(3));
+        assert_eq!(BigUint::new(28).nth_root(3), BigUint::new(3));
+        assert_eq!(BigUint::new(1023).nth_root(10), BigUint::new(1));
+        assert_eq!(BigUint::new(1024).nth_root(10), BigUint::new(2));
+        assert_eq!(BigUint::new(u64::MAX).nth_root(64), BigUint::new(1));
+        assert_eq!(BigUint::power_of_2(300).nth_root(5), BigUint::power_of_2(60));
+        assert_eq!(BigUint::power_of_2(1000).nth_root(u32::MAX), BigUint::new(1));
+        // Exact powers and their neighbours of some "random" numbers.
+        let mut seed = 99;
+        for n in 2..7 {
+            for len in 1..4 {
+                let root = pseudo_random(&mut seed, len);
+                let power = root.pow(n);
+                assert_eq!(power.nth_root(n), root);
+                assert_eq!((&power - BigUint::new(1)).nth_root(n), &root - BigUint::new(1));
+                assert_eq!((&power + BigUint::new(1)).nth_root(n), root);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "The 0th root is not defined")]
+    fn test_nth_root_zero() {
+        BigUint::new(5).nth_root(0);
+    }
+
+    #[test]
+    fn test_pow() {
+        assert_eq!(BigUint::new(0).pow(0), BigUint::new(1));
+        assert_eq!(BigUint::new(0).pow(5), BigUint::new(0));
+        assert_eq!(BigUint::new(7).pow(1), BigUint::new(7));
+        assert_eq!(BigUint::new(3).pow(40), BigUint::new(3u64.pow(40)));
+        assert_eq!(BigUint::new(2).pow(1000), BigUint::power_of_2(1000));
+        assert_eq!(BigUint::power_of_2(3).pow(37), BigUint::power_of_2(111));
+        let b = BigUint::from_vec(vec![12345, 678]);
+        let mut expected = BigUint::new(1);
+        for exp in 0..20 {
+            assert_eq!(b.pow(exp), expected);
+            expected = &expected * &b;
+        }
+    }
+
+    #[test]
+    fn test_format_localized() {
+        let b = BigUint::new(1234567890);
+        assert_eq!(b.format_localized(&locale::EN_US), "1,234,567,890");
+        assert_eq!(b.format_localized(&locale::DE_DE), "1.234.567.890");
+        assert_eq!(b.format_localized(&locale::EN_IN), "1,23,45,67,890");
+        assert_eq!(b.format_localized(&locale::POSIX), "1234567890");
+        assert_eq!(BigUint::new(0).format_localized(&locale::EN_US), "0");
+        assert_eq!(BigUint::power_of_2(64).format_localized(&locale::DE_CH), "18'446'744'073'709'551'616");
+    }
+
+    #[test]
+    fn test_factorial() {
+        assert_eq!(BigUint::factorial(0), BigUint::new(1));
+        assert_eq!(BigUint::factorial(1), BigUint::new(1));
+        assert_eq!(BigUint::factorial(5), BigUint::new(120));
+        assert_eq!(BigUint::factorial(20), BigUint::new(2432902008176640000));
+        assert_eq!(BigUint::factorial(30).to_string(), "265252859812191058636308480000000");
+        // Compare against the naive product, across several levels of the product tree.
+        let mut expected = BigUint::new(1);
+        for n in 1..200 {
+            expected = &expected * &BigUint::new(n);
+            assert_eq!(BigUint::factorial(n), expected);
+        }
+        let digits = BigUint::factorial(1000).to_string();
+        assert_eq!(digits.len(), 2568);
+        assert!(digits.starts_with("402387260077") && digits.ends_with(&"0".repeat(249)));
+    }
+
+    #[test]
+    fn test_binomial() {
+        assert_eq!(BigUint::binomial(0, 0), BigUint::new(1));
+        assert_eq!(BigUint::binomial(5, 0), BigUint::new(1));
+        assert_eq!(BigUint::binomial(5, 5), BigUint::new(1));
+        assert_eq!(BigUint::binomial(5, 2), BigUint::new(10));
+        assert_eq!(BigUint::binomial(5, 6), BigUint::new(0));
+        assert_eq!(BigUint::binomial(52, 5), BigUint::new(2598960));
+        assert_eq!(BigUint::binomial(100, 50).to_string(), "100891344545564193334812497256");
+        // Pascal's rule.
+        for n in 1..60 {
+            for k in 1..n {
+                assert_eq!(BigUint::binomial(n, k), BigUint::binomial(n - 1, k - 1) + BigUint::binomial(n - 1, k));
+            }
+        }
+    }
+
+    #[test]
+    fn test_factorial_many_leaves() {
+        // Lots of levels in the product tree, including levels with an odd number of nodes.
+        let mut expected = BigUint::new(1);
+        for n in 2..5001 {
+            expected.mul_add_digit(n, 0);
+        }
+        assert_eq!(BigUint::factorial(5000), expected);
+        assert_eq!(BigUint::binomial(5000, 2500), BigUint::factorial(5000) / BigUint::factorial(2500).pow(2));
+    }
+
+    #[test]
+    fn test_fibonacci() {
+        assert_eq!(BigUint::fibonacci(0), BigUint::new(0));
+        assert_eq!(BigUint::fibonacci(1), BigUint::new(1));
+        assert_eq!(BigUint::fibonacci(2), BigUint::new(1));
+        assert_eq!(BigUint::fibonacci(10), BigUint::new(55));
+        assert_eq!(BigUint::fibonacci(93), BigUint::new(12200160415121876738));
+        assert_eq!(BigUint::fibonacci(100).to_string(), "354224848179261915075");
+        // Compare with the linear recurrence.
+        let (mut a, mut b) = (BigUint::new(0), BigUint::new(1));
+        for n in 0..300 {
+            assert_eq!(BigUint::fibonacci(n), a);
+            let next = &a + &b;
+            a = b;
+            b = next;
+        }
+        // Cassini's identity: F(n-1) * F(n+1) - F(n)^2 = (-1)^n, here for an even n.
+        let n = 10_000;
+        let f = BigUint::fibonacci(n);
+        assert_eq!(BigUint::fibonacci(n - 1) * BigUint::fibonacci(n + 1), &f * &f + BigUint::new(1));
+        assert_eq!(f.to_string().len(), 2090);
+    }
+
+    #[test]
+    fn test_bit_introspection() {
+        let zero = BigUint::new(0);
+        assert_eq!((zero.bit_length(), zero.count_ones(), zero.trailing_zeros()), (0, 0, None));
+        let one = BigUint::new(1);
+        assert_eq!((one.bit_length(), one.count_ones(), one.trailing_zeros()), (1, 1, Some(0)));
+        let b = BigUint::new(0b1011000);
+        assert_eq!((b.bit_length(), b.count_ones(), b.trailing_zeros()), (7, 3, Some(3)));
+        let b = BigUint::power_of_2(200);
+        assert_eq!((b.bit_length(), b.count_ones(), b.trailing_zeros()), (201, 1, Some(200)));
+        let b = BigUint::from_vec(vec![0, 1 << 5, u64::MAX]);
+        assert_eq!((b.bit_length(), b.count_ones(), b.trailing_zeros()), (192, 65, Some(69)));
+        let b = &BigUint::power_of_2(130) - BigUint::new(1);
+        assert_eq!((b.bit_length(), b.count_ones(), b.trailing_zeros()), (130, 130, Some(0)));
+    }
+
+    #[test]
+    fn test_logarithms() {
+        assert!(BigUint::one().is_power_of_two() && !BigUint::zero().is_power_of_two());
+        assert!(BigUint::power_of_2(200).is_power_of_two());
+        assert!(!(BigUint::power_of_2(200) + BigUint::one()).is_power_of_two());
+        assert!(!BigUint::from_vec(vec![0, 3]).is_power_of_two());
+        assert_eq!((BigUint::one().ilog2(), BigUint::one().ilog10()), (0, 0));
+        assert_eq!(BigUint::power_of_2(200).ilog2(), 200);
+        assert_eq!((&BigUint::power_of_2(200) - BigUint::one()).ilog2(), 199);
+        // Around the powers of 10, and where the bit length changes, against the primitive types.
+        for exp in 1..39 {
+            let power = 10u128.pow(exp);
+            for &n in [power - 1, power, power + 1, power.next_power_of_two(), power.next_power_of_two() - 1].iter() {
+                let b = BigUint::from_vec(vec![n as u64, (n >> 64) as u64]);
+                assert_eq!(b.ilog10(), n.ilog10() as u64, "{}", n);
+                assert_eq!(b.ilog2(), n.ilog2() as u64, "{}", n);
+            }
+        }
+        for &exp in [39, 100, 1000, 4321].iter() {
+            let power = BigUint::new(10).pow(exp);
+            assert_eq!(power.ilog10(), exp as u64);
+            assert_eq!((&power - BigUint::one()).ilog10(), exp as u64 - 1);
+            assert_eq!((&power * &BigUint::new(9)).ilog10(), exp as u64);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "must be positive")]
+    fn test_ilog10_zero() {
+        BigUint::zero().ilog10();
+    }
+
+    #[test]
+    fn test_bit_accessors() {
+        let mut b = BigUint::new(0);
+        assert!(!b.get_bit(0) && !b.get_bit(1000));
+        b.set_bit(0, true);
+        assert_eq!(b, BigUint::new(1));
+        b.set_bit(130, true);
+        assert_eq!(b, BigUint::from_vec(vec![1, 0, 1 << 2]));
+        assert!(b.get_bit(130) && !b.get_bit(129) && !b.get_bit(131));
+        b.set_bit(5, false);
+        assert_eq!(b, BigUint::from_vec(vec![1, 0, 1 << 2]));
+        b.set_bit(130, false);
+        assert_eq!(b, BigUint::new(1));
+        assert!(b.test_invariant());
+        b.set_bit(1000, false);
+        assert_eq!(b, BigUint::new(1));
+        b.flip_bit(64);
+        assert_eq!(b, BigUint::from_vec(vec![1, 1]));
+        b.flip_bit(64);
+        b.flip_bit(0);
+        assert_eq!(b, BigUint::new(0));
+        assert!(b.test_invariant());
+    }
+
+    #[test]
+    fn test_parity() {
+        assert!(BigUint::new(0).is_even() && !BigUint::new(0).is_odd());
+        assert!(BigUint::new(7).is_odd() && !BigUint::new(7).is_even());
+        assert!(BigUint::power_of_2(64).is_even());
+        assert!((BigUint::power_of_2(64) + BigUint::new(1)).is_odd());
+    }
+
+    #[test]
+    fn test_zero_one() {
+        assert_eq!(BigUint::zero(), BigUint::new(0));
+        assert_eq!(BigUint::one(), BigUint::new(1));
+        assert_eq!(BigUint::default(), BigUint::zero());
+        assert!(BigUint::zero().test_invariant() && BigUint::one().test_invariant());
+        assert!(BigUint::zero().is_zero() && !BigUint::zero().is_one());
+        assert!(BigUint::one().is_one() && !BigUint::one().is_zero());
+        let b = BigUint::power_of_2(64);
+        assert!(!b.is_zero() && !b.is_one());
+        assert!((&b - &b).is_zero());
+    }
+
+    #[test]
+    fn test_compare_u64() {
+        let big = BigUint::power_of_2(64);
+        for &(ref b, n) in [(BigUint::new(0), 0), (BigUint::new(0), 1), (BigUint::new(5), 5), (BigUint::new(5), 3),
+                        (BigUint::new(5), u64::MAX), (big.clone(), u64::MAX)].iter() {
+            assert_eq!(*b == n, *b == BigUint::new(n));
+            assert_eq!(n == *b, *b == BigUint::new(n));
+            assert_eq!(b.partial_cmp(&n), Some(b.cmp(&BigUint::new(n))));
+            assert_eq!(n.partial_cmp(b), Some(BigUint::new(n).cmp(b)));
+        }
+        assert!(BigUint::one() >= 1u64);
+        assert!(BigUint::one() <= 1u64);
+        assert!(big > 1u64);
+        assert!(u64::MAX < big);
+        assert!(big != 0);
+    }
+
+    // A corpus of numbers of mixed sizes, with some duplicates and numbers sharing their top digit.
+    fn sort_corpus(count: usize) -> Vec<BigUint> {
+        let mut seed = 0x2545f4914f6cdd1d;
+        (0..count).map(|i| match i % 4 {
+            0 => pseudo_random(&mut seed, 1 + i % 7),
+            1 => BigUint::new(i as u64 % 100),
+            2 => BigUint::power_of_2(64 * (i as u64 % 5) + 3) + BigUint::new(i as u64),
+            _ => pseudo_random(&mut seed, 1 + i % 50),
+        }).collect()
+    }
+
+    #[test]
+    fn test_sort_bigints() {
+        let mut v = sort_corpus(1000);
+        let mut expected = v.clone();
+        expected.sort();
+        sort_bigints(&mut v);
+        assert_eq!(v, expected);
+        let mut empty: Vec<BigUint> = vec![];
+        sort_bigints(&mut empty);
+        assert!(empty.is_empty());
+    }
+
+    // A benchmark comparing `sort_bigints` with the generic sort. Run with
+    // `cargo test --release -- --ignored --nocapture bench_sort_bigints`.
+    #[cfg(feature = "std")]
+    #[test]
+    #[ignore]
+    fn bench_sort_bigints() {
+        use std::time::Instant;
+        let corpus = sort_corpus(1_000_000);
+        let mut v = corpus.clone();
+        let start = Instant::now();
+        v.sort_unstable();
+        println!("sort_unstable: {:?}", start.elapsed());
+        let mut w = corpus;
+        let start = Instant::now();
+        sort_bigints(&mut w);
+        println!("sort_bigints:  {:?}", start.elapsed());
+        assert_eq!(v, w);
+    }
+
+    #[test]
+    fn test_add_digits() {
+        // Cover all the positions of the chunks and of the carry chain, against the simple loop.
+        let mut seed = 7;
+        for long_len in 0..13 {
+            for short_len in 0..long_len + 1 {
+                for &fill in [None, Some(u64::MAX)].iter() {
+                    let mut a = pseudo_random(&mut seed, long_len);
+                    let mut b = pseudo_random(&mut seed, short_len);
+                    if let Some(fill) = fill {
+                        // Long carry chains.
+                        a = BigUint::from_vec(a.data.iter().map(|_| fill).collect());
+                        b = BigUint::from_vec(b.data.iter().enumerate().map(|(i, _)| if i == 0 { 1 } else { 0 }).collect());
+                    }
+                    let sum = &a + &b;
+                    assert_eq!(sum, serial_add(&a, &b));
+                    assert_eq!(sum, &b + &a);
+                    assert!(sum.test_invariant());
+                }
+            }
+        }
+    }
+
+    // The plain digit-by-digit carry chain that `add_digits` replaces.
+    fn serial_add(a: &BigUint, b: &BigUint) -> BigUint {
+        let max_len = cmp::max(a.data.len(), b.data.len());
+        let mut result = Vec::with_capacity(max_len + 1);
+        let mut carry = false;
+        for i in 0..max_len {
+            let lhs_val = if i < a.data.len() { a.data[i] } else { 0 };
+            let rhs_val = if i < b.data.len() { b.data[i] } else { 0 };
+            let (sum, new_carry) = add_with_carry(lhs_val, rhs_val, carry);
+            result.push(sum);
+            carry = new_carry;
+        }
+        if carry {
+            result.push(1);
+        }
+        result.into_iter().collect()
+    }
+
+    // A benchmark of adding two numbers of 4 KiB and of 4 MiB each. Run with
+    // `cargo test --release -- --ignored --nocapture bench_add`.
+    #[cfg(feature = "std")]
+    #[test]
+    #[ignore]
+    fn bench_add() {
+        use std::time::Instant;
+        let mut seed = 3;
+        for &(len, rounds) in [(1 << 9, 100_000), (1 << 19, 100)].iter() {
+            let (a, b) = (pseudo_random(&mut seed, len), pseudo_random(&mut seed, len));
+            let start = Instant::now();
+            let mut serial = BigUint::zero();
+            for _ in 0..rounds {
+                serial = serial_add(&a, &b);
+            }
+            println!("{} digits, serial:  {:?}", len, start.elapsed() / rounds);
+            let start = Instant::now();
+            let mut chunked = BigUint::zero();
+            for _ in 0..rounds {
+                chunked = &a + &b;
+            }
+            println!("{} digits, chunked: {:?}", len, start.elapsed() / rounds);
+            assert_eq!(serial, chunked);
+        }
+    }
+
+    // A benchmark of printing a number with a million digits. Run with
+    // `cargo test --release -- --ignored --nocapture bench_to_string`.
+    #[cfg(feature = "std")]
+    #[test]
+    #[ignore]
+    fn bench_to_string() {
+        use std::time::Instant;
+        let b = BigUint::new(7).pow(1_183_000);
+        let start = Instant::now();
+        let s = b.to_string();
+        println!("to_string ({} digits): {:?}", s.len(), start.elapsed());
+        assert_eq!(s.len(), 999_751);
+        assert!(s.ends_with('1'));
+        let mut naive = String::new();
+        let start = Instant::now();
+        b.push_decimal_chunks(0, &mut naive);
+        println!("chunk by chunk: {:?}", start.elapsed());
+        assert_eq!(s, naive);
+    }
+
+    #[test]
+    fn test_limbs() {
+        for digits in [vec![], vec![5], vec![1, 2], vec![0, 0, 7]] {
+            let b = unsafe { BigUint::from_vec_unchecked(digits.clone()) };
+            assert_eq!(b, digits.iter().cloned().collect::<BigUint>());
+            assert_eq!(b.as_limbs(), &digits[..]);
+            assert_eq!(b.into_limbs(), digits);
+        }
+        assert_eq!(BigUint::from_vec(vec![3, 0, 0]).as_limbs(), &[3]);
+    }
+
+    #[test]
+    fn test_checked_arithmetic() {
+        let (small, large) = (BigUint::new(5), BigUint::power_of_2(64));
+        assert_eq!(large.checked_sub(&small), Some(BigUint::new(u64::MAX - 4)));
+        assert_eq!(small.checked_sub(&small), Some(BigUint::zero()));
+        assert_eq!(small.checked_sub(&large), None);
+        assert_eq!(large.saturating_sub(&small), BigUint::new(u64::MAX - 4));
+        assert_eq!(small.saturating_sub(&large), BigUint::zero());
+        assert_eq!(large.checked_div(&small), Some(&large / &small));
+        assert_eq!(large.checked_rem(&small), Some(BigUint::new(1)));
+        assert_eq!(large.checked_div(&BigUint::zero()), None);
+        assert_eq!(large.checked_rem(&BigUint::zero()), None);
+    }
+
+    #[test]
+    fn test_pow_in_arena() {
+        let mut arena = super::BigIntArena::new();
+        let b = BigUint::from_vec(vec![u64::MAX, 12345]);
+        for exp in 0..20 {
+            let mut expected = BigUint::new(1);
+            for _ in 0..exp {
+                expected = &expected * &b;
+            }
+            assert_eq!(b.pow_in(exp, &arena), expected);
+            assert_eq!(b.pow(exp), expected);
+            arena.reset();
+        }
+        assert_eq!(BigUint::new(0).pow_in(0, &arena), BigUint::new(1));
+        assert_eq!(BigUint::new(0).pow_in(3, &arena), BigUint::new(0));
+    }
+
+    #[test]
+    fn test_abs_diff() {
+        let (small, large) = (BigUint::new(5), BigUint::power_of_2(64));
+        assert_eq!(large.abs_diff(&small), BigUint::new(u64::MAX - 4));
+        assert_eq!(small.abs_diff(&large), BigUint::new(u64::MAX - 4));
+        assert_eq!(small.abs_diff(&small), BigUint::zero());
+    }
+
+    #[test]
+    fn test_sum_product() {
+        let v: Vec<BigUint> = (1..21).map(BigUint::new).collect();
+        assert_eq!(v.iter().sum::<BigUint>(), BigUint::new(210));
+        assert_eq!(v.iter().product::<BigUint>(), BigUint::factorial(20));
+        assert_eq!(v.clone().into_iter().sum::<BigUint>(), BigUint::new(210));
+        assert_eq!(v.into_iter().product::<BigUint>(), BigUint::factorial(20));
+        let empty: Vec<BigUint> = vec![];
+        assert_eq!(empty.iter().sum::<BigUint>(), BigUint::zero());
+        assert_eq!(empty.iter().product::<BigUint>(), BigUint::one());
+        let big = vec![BigUint::new(u64::MAX); 3];
+        assert_eq!(big.iter().sum::<BigUint>(), BigUint::new(u64::MAX) * BigUint::new(3));
+    }
+
+    #[test]
+    fn test_to_f64() {
+        for &n in [0, 1, 12345, 1 << 53, (1 << 53) + 1, u64::MAX].iter() {
+            assert_eq!(BigUint::new(n).to_f64(), n as f64);
+        }
+        assert_eq!(BigUint::power_of_2(100).to_f64(), 2f64.powi(100));
+        assert_eq!(BigUint::power_of_2(1023).to_f64(), 2f64.powi(1023));
+        assert_eq!(BigUint::power_of_2(1024).to_f64(), f64::INFINITY);
+        assert_eq!(BigUint::factorial(20).to_f64(), 2432902008176640000.0);
+        assert_eq!(BigUint::factorial(25).to_f64(), 1.5511210043330986e25);
+        assert_eq!(BigUint::factorial(170).to_f64(), 7.257415615307999e306);
+        assert_eq!(BigUint::factorial(171).to_f64(), f64::INFINITY);
+        // Rounding: 2^100 + 2^47 is exactly between two `f64`s, and rounds to the even one, but a
+        // little more tips it over.
+        let half = BigUint::power_of_2(100) + BigUint::power_of_2(47);
+        assert_eq!(half.to_f64(), 2f64.powi(100));
+        assert_eq!((half + BigUint::new(1)).to_f64(), 2f64.powi(100) + 2f64.powi(48));
+        // The largest `f64`, and what lies just below the point where rounding overflows.
+        let max = (BigUint::power_of_2(53) - BigUint::new(1)) << 971;
+        assert_eq!(max.to_f64(), f64::MAX);
+        assert_eq!((&max + (BigUint::power_of_2(969))).to_f64(), f64::MAX);
+        assert_eq!((&max + (BigUint::power_of_2(970))).to_f64(), f64::INFINITY);
+    }
+
+    #[test]
+    fn test_try_from_f64() {
+        use std::convert::TryFrom;
+        use std::f64;
+        for &(x, n) in [(0.0, 0), (-0.0, 0), (0.99, 0), (1.0, 1), (1.5, 1), (12345.678, 12345),
+                        (f64::MIN_POSITIVE, 0), (5e-324, 0), (9007199254740993.0, 9007199254740992)].iter() {
+            assert_eq!(BigUint::try_from(x), Ok(BigUint::new(n)), "{}", x);
+        }
+        assert_eq!(BigUint::try_from(2f64.powi(100)), Ok(BigUint::power_of_2(100)));
+        assert_eq!(BigUint::try_from(f64::MAX), Ok((BigUint::power_of_2(53) - BigUint::new(1)) << 971));
+        let b = BigUint::factorial(30);
+        assert_eq!(BigUint::try_from(b.to_f64()).unwrap().to_f64(), b.to_f64());
+        for &x in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY, -1.0, -0.5].iter() {
+            let err = BigUint::try_from(x).unwrap_err();
+            assert!(err.value().is_nan() && x.is_nan() || err.value() == x);
+        }
+        assert_eq!(BigUint::try_from(-1.5).unwrap_err().to_string(), "cannot convert -1.5 to BigUint");
+    }
+
+    // A benchmark of arithmetic on small numbers, which is dominated by allocation unless the digits
+    // are stored inline. Run with `cargo test --release -- --ignored --nocapture bench_small_numbers`.
+    #[cfg(feature = "std")]
+    #[test]
+    #[ignore]
+    fn bench_small_numbers() {
+        use std::time::Instant;
+        let start = Instant::now();
+        let mut checksum = BigUint::zero();
+        for i in 0..1_000_000u64 {
+            let a = BigUint::new(i);
+            let b = &a * &a + &a;
+            let c = b.clone() - a;
+            checksum = (checksum + c) >> 1;
+        }
+        println!("small numbers: {:?}", start.elapsed());
+        assert!(checksum.data.len() <= 2);
+    }
+
+    #[test]
+    fn test_u64_ops() {
+        let mut seed = 42;
+        for len in 0..5 {
+            let b = pseudo_random(&mut seed, len);
+            for &x in [0, 1, 7, 1 << 40, u64::MAX].iter() {
+                let big_x = BigUint::new(x);
+                let product = b.mul_u64(x);
+                assert_eq!(product, &b * &big_x);
+                assert!(product.test_invariant());
+                let sum = b.add_u64(x);
+                assert_eq!(sum, &b + &big_x);
+                assert!(sum.test_invariant());
+                if x != 0 {
+                    let (quotient, rem) = b.div_rem_u64(x);
+                    assert_eq!((quotient, BigUint::new(rem)), b.div_rem(&big_x));
+                }
+            }
+        }
+        assert_eq!(BigUint::new(u64::MAX).add_u64(1), BigUint::power_of_2(64));
+    }
+
+    #[test]
+    fn test_iter() {
+        let b: BigUint = vec![1, 2, 3, 4].into_iter().collect();
+        assert_eq!(b.iter().collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+        assert_eq!(b.iter_msf().collect::<Vec<_>>(), vec![4, 3, 2, 1]);
+        assert_eq!((&b).into_iter().sum::<Limb>(), 10);
+        assert_eq!(BigUint::zero().iter().next(), None);
+
+        // Taking from both ends, the two meet in the middle and stay there.
+        let mut it = b.iter();
+        assert_eq!(it.len(), 4);
+        assert_eq!((it.next(), it.next_back()), (Some(1), Some(4)));
+        assert_eq!(it.len(), 2);
+        assert_eq!((it.next_back(), it.next()), (Some(3), Some(2)));
+        assert_eq!(it.len(), 0);
+        assert_eq!((it.next(), it.next_back(), it.next()), (None, None, None));
+    }
+
+    #[test]
+    fn test_mul_assign() {
+        let mut seed = 11;
+        let mut scratch = Vec::new();
+        for len in 0..6 {
+            let a = pseudo_random(&mut seed, len);
+            let b = pseudo_random(&mut seed, 6 - len);
+            let mut c = a.clone();
+            c.mul_assign_with_scratch(&b, &mut scratch);
+            assert_eq!(c, &a * &b);
+            assert!(c.test_invariant());
+            let mut d = a.clone();
+            d *= &b;
+            d *= BigUint::new(3);
+            assert_eq!(d, &a * &b * BigUint::new(3));
+        }
+        // Computing a factorial in a loop.
+        let mut fact = BigUint::one();
+        for i in 1..=100 {
+            fact.mul_assign_with_scratch(&BigUint::new(i), &mut scratch);
+        }
+        assert_eq!(fact, BigUint::factorial(100));
+        fact *= BigUint::zero();
+        assert!(fact.is_zero());
+    }
+
+    #[test]
+    fn test_bit_ops() {
+        let a = BigUint::from_vec(vec![0b1100, 0b1010, 1]);
+        let b = BigUint::from_vec(vec![0b1010, 0b1010]);
+        assert_eq!(&a & &b, BigUint::from_vec(vec![0b1000, 0b1010]));
+        assert_eq!(&a | &b, BigUint::from_vec(vec![0b1110, 0b1010, 1]));
+        assert_eq!(&b | &a, BigUint::from_vec(vec![0b1110, 0b1010, 1]));
+        assert_eq!(&a ^ &b, BigUint::from_vec(vec![0b0110, 0, 1]));
+        // Digits that become 0 at the top are dropped.
+        assert_eq!(b.clone() ^ b.clone(), BigUint::zero());
+        assert_eq!(&a & &BigUint::power_of_2(128), BigUint::one() << 128);
+        assert!((BigUint::from_vec(vec![1, 1]) & BigUint::from_vec(vec![1, 2])).test_invariant());
+        let mut seed = 9;
+        for len in 0..4 {
+            let (x, y) = (pseudo_random(&mut seed, len), pseudo_random(&mut seed, 3));
+            assert_eq!(&(&x & &y) + &(&x | &y), &x + &y);
+            assert_eq!(&x ^ &y, &(&x | &y) - &(&x & &y));
+        }
+    }
+
+    #[test]
+    fn test_shift_assign() {
+        let mut seed = 5;
+        for len in 0..5 {
+            let b = pseudo_random(&mut seed, len);
+            for &bits in [0, 1, 63, 64, 65, 130, 400].iter() {
+                let mut shifted = b.clone();
+                shifted <<= bits;
+                assert_eq!(shifted, &b << bits);
+                assert!(shifted.test_invariant());
+                shifted >>= bits;
+                assert_eq!(shifted, b);
+                let mut shifted = b.clone();
+                shifted >>= bits;
+                assert_eq!(shifted, &b >> bits);
+                assert!(shifted.test_invariant());
+            }
+        }
+        // Once there is room, shifting back and forth does not allocate.
+        let mut n = BigUint::from_vec(vec![u64::MAX; 8]);
+        n <<= 100;
+        n >>= 100;
+        let digits = n.as_limbs().as_ptr();
+        for _ in 0..10 {
+            n <<= 100;
+            n >>= 100;
+        }
+        assert_eq!(n.as_limbs().as_ptr(), digits);
+        assert_eq!(n, BigUint::from_vec(vec![u64::MAX; 8]));
+    }
+
+    #[test]
+    fn test_collect_limbs() {
+        let b: BigUint = vec![1, 2, 0, 0].into_iter().collect();
+        assert_eq!(b.as_limbs(), &[1, 2]);
+        assert!(b.test_invariant());
+        assert!(iter::repeat_n(0, 5).collect::<BigUint>().is_zero());
+        // A round trip through an iterator pipeline.
+        let doubled: BigUint = b.iter().map(|d| d * 2).collect();
+        assert_eq!(doubled.as_limbs(), &[2, 4]);
+
+        let mut c = BigUint::new(7);
+        c.extend(vec![0, 3]);
+        assert_eq!(c.as_limbs(), &[7, 0, 3]);
+        c.extend(vec![0, 0]);
+        assert_eq!(c.as_limbs(), &[7, 0, 3]);
+        let mut zero = BigUint::zero();
+        zero.extend(vec![0, 0, 1]);
+        assert_eq!(zero, BigUint::power_of_2(2 * LIMB_BITS as u64));
+    }
+
+    #[test]
+    fn test_digits() {
+        assert_eq!(BigUint::new(1234).digits(10).collect::<Vec<_>>(), vec![4, 3, 2, 1]);
+        assert_eq!(BigUint::new(6).digits(2).collect::<Vec<_>>(), vec![0, 1, 1]);
+        assert_eq!(BigUint::zero().digits(10).next(), None);
+        assert_eq!(BigUint::new(u64::MAX).digits(u64::MAX).collect::<Vec<_>>(), vec![0, 1]);
+        // The chunks in the middle keep their zeros.
+        let n = BigUint::power_of_2(200) + BigUint::one();
+        let digits: Vec<u64> = n.digits(10).collect();
+        let decimal: String = digits.iter().rev().map(|&d| (b'0' + d as u8) as char).collect();
+        assert_eq!(decimal, n.to_string());
+        let mut seed = 3;
+        let b = pseudo_random(&mut seed, 4);
+        for &base in [3, 7, 10, 1000, 1 << 40].iter() {
+            let back = b.digits(base).collect::<Vec<_>>().into_iter().rev()
+                .fold(BigUint::zero(), |acc, d| acc.mul_u64(base).add_u64(d));
+            assert_eq!(back, b);
+        }
+        assert_eq!(BigUint::power_of_2(10000).digits(10).take(3).collect::<Vec<_>>(), vec![6, 7, 3]);
+    }
+}
@@ -0,0 +1,134 @@
+// Random numbers for property tests, with quickcheck or proptest. The numbers have up to
+// `MAX_ARBITRARY_LIMBS` digits, which is enough to reach all the special cases of the algorithms
+// (like the two-digit divisors of long division), while keeping the tests fast. When a test fails,
+// both frameworks shrink the number towards fewer digits first, and then towards smaller digits.
+
+use super::BigUint;
+
+// The maximal number of 64-bit digits of a generated number.
+const MAX_ARBITRARY_LIMBS: usize = 8;
+
+#[cfg(feature = "quickcheck")]
+mod quickcheck_impl {
+    use quickcheck::{Arbitrary, Gen};
+    use super::{BigUint, MAX_ARBITRARY_LIMBS};
+
+    impl Arbitrary for BigUint {
+        fn arbitrary(g: &mut Gen) -> Self {
+            let len = usize::arbitrary(g) % (MAX_ARBITRARY_LIMBS + 1);
+            BigUint::from_vec((0..len).map(|_| u64::arbitrary(g)).collect())
+        }
+
+        fn shrink(&self) -> Box<dyn Iterator<Item = BigUint>> {
+            let digits: Vec<u64> = self.u64_digits().collect();
+            let mut candidates = Vec::new();
+            if let Some((&top, rest)) = digits.split_last() {
+                // Fewer digits: Drop the most significant one, or the least significant one.
+                candidates.push(BigUint::from_vec(rest.to_vec()));
+                if digits.len() > 1 {
+                    candidates.push(BigUint::from_vec(digits[1..].to_vec()));
+                }
+                // Then, a smaller most significant digit.
+                let rest = rest.to_vec();
+                candidates.extend(top.shrink().map(move |top| {
+                    let mut digits = rest.clone();
+                    digits.push(top);
+                    BigUint::from_vec(digits)
+                }));
+            }
+            Box::new(candidates.into_iter())
+        }
+    }
+}
+
+#[cfg(feature = "proptest")]
+mod proptest_impl {
+    use proptest::arbitrary::{any, Arbitrary};
+    use proptest::collection::vec;
+    use proptest::strategy::{BoxedStrategy, Strategy};
+    use super::{BigUint, MAX_ARBITRARY_LIMBS};
+
+    // Shrinking the vector of digits removes digits, and shrinks the ones that are left.
+    impl Arbitrary for BigUint {
+        type Parameters = ();
+        type Strategy = BoxedStrategy<BigUint>;
+
+        fn arbitrary_with(_: ()) -> Self::Strategy {
+            vec(any::<u64>(), 0..MAX_ARBITRARY_LIMBS + 1).prop_map(BigUint::from_vec).boxed()
+        }
+    }
+}
+
+#[cfg(all(test, feature = "quickcheck"))]
+mod quickcheck_tests {
+    use quickcheck::{quickcheck, Arbitrary, Gen};
+    use super::super::BigUint;
+
+    quickcheck! {
+        fn prop_add_sub(a: BigUint, b: BigUint) -> bool {
+            &(&a + &b) - &b == a
+        }
+
+        fn prop_mul_commutes(a: BigUint, b: BigUint) -> bool {
+            &a * &b == &b * &a
+        }
+
+        fn prop_div_rem(a: BigUint, b: BigUint) -> bool {
+            if b.is_zero() {
+                return true;
+            }
+            let (q, r) = a.div_rem(&b);
+            r < b && &q * &b + r == a
+        }
+
+        fn prop_display_roundtrip(a: BigUint) -> bool {
+            a.to_string().parse::<BigUint>() == Ok(a)
+        }
+    }
+
+    #[test]
+    fn test_arbitrary() {
+        let mut g = Gen::new(100);
+        for _ in 0..100 {
+            let b = BigUint::arbitrary(&mut g);
+            assert!(b.test_invariant() && b.u64_digits().len() <= super::MAX_ARBITRARY_LIMBS);
+        }
+    }
+
+    #[test]
+    fn test_shrink() {
+        let b = BigUint::from_vec(vec![1, 2, 3]);
+        let shrunk: Vec<BigUint> = b.shrink().collect();
+        assert_eq!(shrunk[0], BigUint::from_vec(vec![1, 2]));
+        assert_eq!(shrunk[1], BigUint::from_vec(vec![2, 3]));
+        assert!(shrunk[2..].iter().all(|s| *s < b && s.u64_digits().len() <= 3));
+        assert!(shrunk.iter().all(|s| s.test_invariant()));
+        assert_eq!(BigUint::zero().shrink().count(), 0);
+    }
+}
+
+#[cfg(all(test, feature = "proptest"))]
+mod proptest_tests {
+    use proptest::prelude::*;
+    use super::super::BigUint;
+
+    proptest! {
+        #[test]
+        fn prop_add_sub(a: BigUint, b: BigUint) {
+            prop_assert_eq!(&(&a + &b) - &b, a);
+        }
+
+        #[test]
+        fn prop_mul_distributes(a: BigUint, b: BigUint, c: BigUint) {
+            prop_assert_eq!(&a * &(&b + &c), &a * &b + &a * &c);
+        }
+
+        #[test]
+        fn prop_gcd_divides(a: BigUint, b: BigUint) {
+            let g = a.gcd(&b);
+            if !g.is_zero() {
+                prop_assert!((&a % &g).is_zero() && (&b % &g).is_zero());
+            }
+        }
+    }
+}
@@ -0,0 +1,123 @@
+// A bump arena for the temporary numbers of an algorithm. Instead of allocating every intermediate
+// result on its own, we hand out pieces of a few large chunks of memory, and free everything at once
+// when the arena is dropped or reset.
+
+#[cfg(not(feature = "std"))]
+use std::prelude::v1::*;
+use std::cell::RefCell;
+use std::cmp;
+use std::slice;
+use super::Limb;
+
+// The size of the first chunk, if nothing else was requested.
+const MIN_CHUNK_SIZE: usize = 64;
+
+/// An arena handing out zero-initialized slices of digits. The slices live as long as the shared
+/// borrow of the arena they were allocated from, so `reset` can only be called once they are all
+/// gone.
+pub struct BigIntArena {
+    // All chunks are allocated with a fixed capacity that they never grow beyond, so the memory of a
+    // chunk never moves. We only ever append to the last chunk.
+    chunks: RefCell<Vec<Vec<Limb>>>,
+}
+
+impl BigIntArena {
+    /// Create an empty arena.
+    pub fn new() -> Self {
+        BigIntArena { chunks: RefCell::new(Vec::new()) }
+    }
+
+    /// Create an arena that can hand out `capacity` digits before it has to allocate again.
+    pub fn with_capacity(capacity: usize) -> Self {
+        BigIntArena { chunks: RefCell::new(vec![Vec::with_capacity(capacity)]) }
+    }
+
+    /// Allocate `len` digits, all set to 0.
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc(&self, len: usize) -> &mut [Limb] {
+        let mut chunks = self.chunks.borrow_mut();
+        let last_capacity = match chunks.last() {
+            Some(chunk) if chunk.capacity() - chunk.len() >= len => None,
+            Some(chunk) => Some(chunk.capacity()),
+            None => Some(0),
+        };
+        if let Some(last_capacity) = last_capacity {
+            // Start a new chunk, at least twice as large as the previous one, so that the number of
+            // chunks stays logarithmic.
+            let capacity = cmp::max(len, cmp::max(2 * last_capacity, MIN_CHUNK_SIZE));
+            chunks.push(Vec::with_capacity(capacity));
+        }
+        let chunk = chunks.last_mut().unwrap();
+        let start = chunk.len();
+        // This stays within the capacity, so the chunk is not reallocated.
+        chunk.resize(start + len, 0);
+        // The slice does not overlap with any slice handed out before, and the memory stays where it
+        // is until the arena is reset or dropped, both of which require that no borrows are left.
+        unsafe { slice::from_raw_parts_mut(chunk.as_mut_ptr().add(start), len) }
+    }
+
+    /// Allocate a copy of `digits`.
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_copy(&self, digits: &[Limb]) -> &mut [Limb] {
+        let slice = self.alloc(digits.len());
+        slice.copy_from_slice(digits);
+        slice
+    }
+
+    /// The number of digits that can be allocated in total before the arena has to grow.
+    pub fn capacity(&self) -> usize {
+        self.chunks.borrow().iter().map(|chunk| chunk.capacity()).sum()
+    }
+
+    /// Free everything allocated so far. The largest chunk is kept around to serve future
+    /// allocations.
+    pub fn reset(&mut self) {
+        let chunks = self.chunks.get_mut();
+        if let Some(mut largest) = chunks.pop() {
+            largest.clear();
+            chunks.clear();
+            chunks.push(largest);
+        }
+    }
+}
+
+impl Default for BigIntArena {
+    fn default() -> Self {
+        BigIntArena::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BigIntArena;
+
+    #[test]
+    fn test_alloc() {
+        let arena = BigIntArena::new();
+        let a = arena.alloc(3);
+        let b = arena.alloc(1000);
+        let c = arena.alloc_copy(&[7, 8]);
+        assert_eq!(a, &[0, 0, 0]);
+        assert!(b.iter().all(|&d| d == 0));
+        a[0] = 1;
+        b[999] = 2;
+        assert_eq!((a[0], b[999], &c[..]), (1, 2, &[7, 8][..]));
+        assert!(arena.alloc(0).is_empty());
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut arena = BigIntArena::with_capacity(10);
+        assert_eq!(arena.capacity(), 10);
+        for _ in 0..10 {
+            arena.alloc(100)[99] = 1;
+        }
+        let capacity = arena.capacity();
+        assert!(capacity >= 1000);
+        arena.reset();
+        let largest = arena.capacity();
+        assert!(largest < capacity);
+        assert!(arena.alloc(100).iter().all(|&d| d == 0));
+        assert_eq!(arena.capacity(), largest);
+    }
+}
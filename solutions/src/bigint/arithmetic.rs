@@ -0,0 +1,218 @@
+// Arithmetic functions in the sense of number theory: functions of a positive integer that are
+// computed from its prime factorization. None of them is defined for 0.
+
+#[cfg(not(feature = "std"))]
+use std::prelude::v1::*;
+use super::BigUint;
+
+impl BigUint {
+    // Factor the number, with the primes in increasing order. Panics for 0.
+    fn sorted_prime_factors(&self, function: &str) -> Vec<(BigUint, u32)> {
+        if self.data.is_empty() {
+            panic!("{} is not defined for 0", function);
+        }
+        let mut factors: Vec<_> = self.prime_factors().collect();
+        factors.sort();
+        factors
+    }
+
+    /// Compute Euler's totient function: how many numbers from 1 to `self` are coprime to `self`.
+    pub fn euler_phi(&self) -> BigUint {
+        let one = BigUint::one();
+        let mut phi = BigUint::one();
+        for (p, exp) in self.sorted_prime_factors("euler_phi") {
+            // phi(p^e) = p^(e-1) * (p-1)
+            phi = phi * p.pow(exp - 1) * (&p - &one);
+        }
+        phi
+    }
+
+    /// Compute the Möbius function: 0 if the number is divisible by a square other than 1, and
+    /// otherwise 1 or -1 depending on whether the number of prime factors is even or odd.
+    pub fn moebius(&self) -> i32 {
+        let mut mu = 1;
+        for (_, exp) in self.sorted_prime_factors("moebius") {
+            if exp > 1 {
+                return 0;
+            }
+            mu = -mu;
+        }
+        mu
+    }
+
+    /// Count the positive divisors of the number.
+    pub fn divisor_count(&self) -> BigUint {
+        let mut count = BigUint::one();
+        for (_, exp) in self.sorted_prime_factors("divisor_count") {
+            count.mul_add_u64(exp as u64 + 1, 0);
+        }
+        count
+    }
+
+    /// Compute the sum of the positive divisors of the number.
+    pub fn divisor_sum(&self) -> BigUint {
+        let one = BigUint::one();
+        let mut sum = BigUint::one();
+        for (p, exp) in self.sorted_prime_factors("divisor_sum") {
+            // 1 + p + ... + p^e = (p^(e+1) - 1) / (p - 1)
+            sum *= (p.pow(exp + 1) - &one) / (&p - &one);
+        }
+        sum
+    }
+
+    /// Compute the Carmichael function: the smallest `m` such that `a^m = 1 (mod self)` for all `a`
+    /// coprime to `self`. This divides `euler_phi`.
+    pub fn carmichael_lambda(&self) -> BigUint {
+        let one = BigUint::one();
+        let mut lambda = BigUint::one();
+        for (p, exp) in self.sorted_prime_factors("carmichael_lambda") {
+            // Modulo a prime power, the group of units is cyclic - except for powers of 2 starting at
+            // 8, where it is only half as large.
+            let lambda_p = if p == 2 && exp >= 3 {
+                BigUint::power_of_2(exp as u64 - 2)
+            } else {
+                p.pow(exp - 1) * (&p - &one)
+            };
+            let gcd = lambda.gcd(&lambda_p);
+            lambda *= lambda_p / gcd;
+        }
+        lambda
+    }
+
+    /// Compute the multiplicative order of `self` modulo `n`: the smallest `m > 0` such that
+    /// `self^m = 1 (mod n)`. Returns `None` if `self` and `n` are not coprime, in which case there is
+    /// no such `m`.
+    pub fn multiplicative_order(&self, n: &BigUint) -> Option<BigUint> {
+        let one = BigUint::new(1) % n;
+        if self.gcd(n) != 1 {
+            return None;
+        }
+        // The order divides `lambda(n)`. We find it by taking out prime factors of `lambda(n)` for as
+        // long as the power still gives 1.
+        let mut order = n.carmichael_lambda();
+        for (p, _) in order.clone().prime_factors() {
+            loop {
+                let (quotient, rem) = order.div_rem(&p);
+                if !rem.data.is_empty() || self.mod_pow(&quotient, n) != one {
+                    break;
+                }
+                order = quotient;
+            }
+        }
+        Some(order)
+    }
+
+    /// Iterate over the positive divisors of the number. They start with 1, but are otherwise not
+    /// produced in increasing order.
+    pub fn divisors(&self) -> Divisors {
+        let factors = self.sorted_prime_factors("divisors");
+        Divisors { exponents: vec![0; factors.len()], factors, next: Some(BigUint::new(1)) }
+    }
+}
+
+/// The iterator returned by `BigUint::divisors`.
+pub struct Divisors {
+    factors: Vec<(BigUint, u32)>,
+    // The exponents of the primes in the divisor we produce next. We count through all combinations
+    // like an odometer, with the first prime in the last digit.
+    exponents: Vec<u32>,
+    next: Option<BigUint>,
+}
+
+impl Iterator for Divisors {
+    type Item = BigUint;
+
+    fn next(&mut self) -> Option<BigUint> {
+        let divisor = self.next.take()?;
+        let mut next = divisor.clone();
+        for (i, &(ref p, max_exp)) in self.factors.iter().enumerate() {
+            if self.exponents[i] < max_exp {
+                self.exponents[i] += 1;
+                next *= p;
+                self.next = Some(next);
+                break;
+            }
+            // This digit rolls over, so take out that prime entirely and carry on to the next one.
+            next = next / p.pow(max_exp);
+            self.exponents[i] = 0;
+        }
+        Some(divisor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use std::prelude::v1::*;
+    use super::super::BigUint;
+
+    fn big(v: u64) -> BigUint {
+        BigUint::new(v)
+    }
+
+    #[test]
+    fn test_euler_phi() {
+        let phi: Vec<_> = (1..13).map(|n| big(n).euler_phi()).collect();
+        let expected: Vec<_> = [1, 1, 2, 2, 4, 2, 6, 4, 6, 4, 10, 4].iter().map(|&n| big(n)).collect();
+        assert_eq!(phi, expected);
+        // 2^64 - 1 = 3 * 5 * 17 * 257 * 641 * 65537 * 6700417
+        let phi = [2u64, 4, 16, 256, 640, 65536, 6700416].iter().fold(big(1), |acc, &n| acc * big(n));
+        assert_eq!(big(u64::MAX).euler_phi(), phi);
+    }
+
+    #[test]
+    fn test_moebius() {
+        let mu: Vec<_> = (1..16).map(|n| big(n).moebius()).collect();
+        assert_eq!(mu, vec![1, -1, -1, 0, -1, 1, -1, 0, 0, 1, -1, 0, -1, 1, 1]);
+        assert_eq!(big(u64::MAX).moebius(), -1);
+    }
+
+    #[test]
+    fn test_divisor_functions() {
+        for n in 1..200 {
+            let divisors: Vec<_> = (1..n + 1).filter(|d| n % d == 0).collect();
+            assert_eq!(big(n).divisor_count(), big(divisors.len() as u64));
+            assert_eq!(big(n).divisor_sum(), big(divisors.iter().sum()));
+            let mut found: Vec<_> = big(n).divisors().collect();
+            found.sort();
+            assert_eq!(found, divisors.iter().map(|&d| big(d)).collect::<Vec<_>>());
+        }
+        let b = BigUint::power_of_2(100) * big(3).pow(5);
+        assert_eq!(b.divisor_count(), big(101 * 6));
+        assert_eq!(b.divisor_sum(), (BigUint::power_of_2(101) - big(1)) * big(364));
+        assert_eq!(b.divisors().next(), Some(big(1)));
+        assert_eq!(b.divisors().count(), 606);
+    }
+
+    #[test]
+    #[should_panic(expected = "divisors is not defined for 0")]
+    fn test_divisors_of_zero() {
+        big(0).divisors();
+    }
+
+    #[test]
+    fn test_carmichael_lambda() {
+        let lambda: Vec<_> = (1..17).map(|n| big(n).carmichael_lambda()).collect();
+        let expected: Vec<_> = [1, 1, 2, 2, 4, 2, 6, 2, 6, 4, 10, 2, 12, 6, 4, 4].iter().map(|&n| big(n)).collect();
+        assert_eq!(lambda, expected);
+        assert_eq!(big(561).carmichael_lambda(), big(80));
+        assert_eq!(BigUint::power_of_2(100).carmichael_lambda(), BigUint::power_of_2(98));
+    }
+
+    #[test]
+    fn test_multiplicative_order() {
+        for n in 1..60 {
+            for a in 0..n {
+                let expected = if big(a).gcd(&big(n)) != big(1) {
+                    None
+                } else {
+                    (1..n + 1).find(|&m| big(a).mod_pow(&big(m), &big(n)) == big(1) % big(n)).map(big)
+                };
+                assert_eq!(big(a).multiplicative_order(&big(n)), expected, "{} mod {}", a, n);
+            }
+        }
+        // 2 has order 64 modulo the Fermat number 2^32 + 1.
+        assert_eq!(big(2).multiplicative_order(&big((1 << 32) + 1)), Some(big(64)));
+        assert_eq!(big(10).multiplicative_order(&big(15)), None);
+    }
+}
@@ -0,0 +1,131 @@
+// Building a `BigUint` piece by piece. Reading a huge number from a file should not need the whole
+// file in memory first: the builder takes the input chunk by chunk, and only keeps the digits of the
+// number itself.
+
+use super::{BigUint, Limb, ParseBigIntError, DECIMAL_CHUNK, DECIMAL_CHUNK_LEN};
+
+// What the builder was fed so far. Limbs come least significant first, decimal digits most
+// significant first, so the two cannot be mixed.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Input {
+    Nothing,
+    Limbs,
+    Decimal,
+}
+
+/// Build a `BigUint` from its limbs or from its decimal digits, which are pushed in chunks.
+/// For example, a number in a file can be read into a small buffer over and over again, with every
+/// buffer full passed to `push_decimal_str_chunk`.
+#[derive(Clone, Debug)]
+pub struct BigIntBuilder {
+    // The limbs so far, which may end in zeros until we are done. For decimal input, this is the
+    // value of all the digits that were already moved out of `chunk`.
+    value: BigUint,
+    // Decimal digits that have not been added to `value` yet.
+    chunk: Limb,
+    chunk_digits: usize,
+    input: Input,
+}
+
+impl BigIntBuilder {
+    pub fn new() -> Self {
+        BigIntBuilder { value: BigUint::zero(), chunk: 0, chunk_digits: 0, input: Input::Nothing }
+    }
+
+    fn start(&mut self, input: Input) {
+        if self.input == Input::Nothing {
+            self.input = input;
+        }
+        assert!(self.input == input, "BigIntBuilder: cannot mix limbs and decimal digits");
+    }
+
+    /// Append the next limb (see `BigUint::as_limbs`), which is more significant than all the ones
+    /// before. Panics if decimal digits have been pushed.
+    pub fn push_limb(&mut self, limb: Limb) {
+        self.start(Input::Limbs);
+        self.value.data.push(limb);
+    }
+
+    /// Append more decimal digits, which are less significant than all the ones before. A number
+    /// can be split into chunks anywhere. If the chunk contains something else than a digit, the
+    /// error refers to its position in the chunk, and the builder is left unchanged. Panics if limbs
+    /// have been pushed.
+    pub fn push_decimal_str_chunk(&mut self, chunk: &str) -> Result<(), ParseBigIntError> {
+        if let Some((offset, c)) = chunk.char_indices().find(|&(_, c)| !c.is_ascii_digit()) {
+            return Err(ParseBigIntError::invalid_digit(chunk, offset, c, 10));
+        }
+        self.start(Input::Decimal);
+        for digit in chunk.bytes() {
+            self.chunk = self.chunk * 10 + (digit - b'0') as Limb;
+            self.chunk_digits += 1;
+            if self.chunk_digits == DECIMAL_CHUNK_LEN {
+                self.value.mul_add_digit(DECIMAL_CHUNK as Limb, self.chunk);
+                self.chunk = 0;
+                self.chunk_digits = 0;
+            }
+        }
+        Ok(())
+    }
+
+    /// Return the number. Nothing pushed at all, or a bunch of empty chunks, make 0.
+    pub fn finish(self) -> BigUint {
+        let mut value = BigUint::from_digits(self.value.data);
+        if self.chunk_digits > 0 {
+            value.mul_add_digit((10 as Limb).pow(self.chunk_digits as u32), self.chunk);
+        }
+        value
+    }
+}
+
+impl Default for BigIntBuilder {
+    fn default() -> Self {
+        BigIntBuilder::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bigint::BigUint;
+    use super::BigIntBuilder;
+
+    #[test]
+    fn test_limbs() {
+        let mut builder = BigIntBuilder::new();
+        for &limb in [5, 0, 7, 0, 0].iter() {
+            builder.push_limb(limb);
+        }
+        let n = builder.finish();
+        assert_eq!(n.as_limbs(), &[5, 0, 7]);
+        assert!(BigIntBuilder::new().finish().is_zero());
+    }
+
+    #[test]
+    fn test_decimal() {
+        let s = "9876543210".repeat(13);
+        let expected: BigUint = s.parse().unwrap();
+        // Cut the string into pieces of all kinds of sizes, some of them empty.
+        for &size in [1, 7, 19, 20, 64].iter() {
+            let mut builder = BigIntBuilder::new();
+            builder.push_decimal_str_chunk("").unwrap();
+            for piece in s.as_bytes().chunks(size) {
+                builder.push_decimal_str_chunk(::std::str::from_utf8(piece).unwrap()).unwrap();
+            }
+            assert_eq!(builder.finish(), expected);
+        }
+
+        let mut builder = BigIntBuilder::new();
+        builder.push_decimal_str_chunk("000123").unwrap();
+        let err = builder.push_decimal_str_chunk("45x6").unwrap_err();
+        assert_eq!((err.offset(), err.snippet()), (2, "x"));
+        builder.push_decimal_str_chunk("456").unwrap();
+        assert_eq!(builder.finish(), BigUint::new(123456));
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot mix")]
+    fn test_mixed() {
+        let mut builder = BigIntBuilder::new();
+        builder.push_limb(1);
+        let _ = builder.push_decimal_str_chunk("1");
+    }
+}
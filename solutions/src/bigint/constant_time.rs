@@ -0,0 +1,151 @@
+// Operations whose running time does not depend on the values of the numbers, only on their lengths
+// (that is, the number of digits after dropping leading zeros). This matters when the numbers are
+// secret, as keys in cryptography are: A comparison that returns as soon as it sees the first
+// differing digit tells an attacker who can measure the time how many digits were equal.
+//
+// What is constant-time:
+// - `ct_eq`, `ct_lt` and `ct_cmp` look at all digits of both numbers, and combine them without
+//   branching on their values.
+// - `ct_overflowing_sub` and `ct_sub_if_ge` compute the full borrow chain, and `ct_sub_if_ge` selects
+//   its result with a mask instead of a branch.
+//
+// What is *not*:
+// - The lengths themselves. A `BigUint` never stores leading zeros, so the length of every result
+//   (including those of the functions here) reveals roughly how large it is, and allocating it may
+//   take different paths for short and long numbers.
+// - All the other operations, in particular `==` and `<` (they return early), `+` (it stops
+//   propagating the carry early), `-` (it panics depending on the values), multiplication (it
+//   returns early for 0), division and remainder (the quotient estimate is corrected a
+//   value-dependent number of times), `pow` (it branches on the bits of the exponent), formatting,
+//   parsing and factoring.
+//
+// The compiler is in principle free to turn our masks back into branches; `black_box` makes that
+// unlikely, but there is no guarantee short of checking the generated code.
+
+use std::cmp;
+use std::hint::black_box;
+use super::BigUint;
+use super::storage::{Digits, Limb};
+
+// The `i`-th digit of a number, or 0 beyond its end. This branches on `i` and the length only.
+#[inline(always)]
+fn digit(digits: &[Limb], i: usize) -> Limb {
+    if i < digits.len() { digits[i] } else { 0 }
+}
+
+// Subtract with borrow, without branches. The borrow is 0 or 1.
+#[inline(always)]
+fn sub_with_borrow(a: Limb, b: Limb, borrow: Limb) -> (Limb, Limb) {
+    let (diff, borrow1) = a.overflowing_sub(b);
+    let (diff, borrow2) = diff.overflowing_sub(borrow);
+    (diff, (borrow1 | borrow2) as Limb)
+}
+
+// Compute `a - b` modulo `2^(LIMB_BITS * len)`, where `len` is the length of the longer number,
+// without dropping leading zeros. Also returns the borrow out of the top digit, which is 1 if `a < b`.
+fn sub_digits(a: &[Limb], b: &[Limb]) -> (Digits, Limb) {
+    let len = cmp::max(a.len(), b.len());
+    let mut result = Digits::zeroed(len);
+    let mut borrow = 0;
+    for (i, out) in result.iter_mut().enumerate() {
+        let (diff, new_borrow) = sub_with_borrow(digit(a, i), digit(b, i), borrow);
+        *out = diff;
+        borrow = new_borrow;
+    }
+    (result, borrow)
+}
+
+impl BigUint {
+    /// Check whether the two numbers are equal, in time that depends only on their lengths.
+    pub fn ct_eq(&self, other: &BigUint) -> bool {
+        let len = cmp::max(self.data.len(), other.data.len());
+        let mut diff = 0;
+        for i in 0..len {
+            diff |= digit(&self.data, i) ^ digit(&other.data, i);
+        }
+        black_box(diff) == 0
+    }
+
+    /// Check whether `self < other`, in time that depends only on the lengths of the numbers.
+    pub fn ct_lt(&self, other: &BigUint) -> bool {
+        // `self < other` exactly if subtracting borrows out of the top digit.
+        let len = cmp::max(self.data.len(), other.data.len());
+        let mut borrow = 0;
+        for i in 0..len {
+            borrow = sub_with_borrow(digit(&self.data, i), digit(&other.data, i), borrow).1;
+        }
+        black_box(borrow) == 1
+    }
+
+    /// Compare the numbers, in time that depends only on their lengths.
+    pub fn ct_cmp(&self, other: &BigUint) -> cmp::Ordering {
+        let less = self.ct_lt(other) as i8;
+        let greater = other.ct_lt(self) as i8;
+        // Only now that both are known we branch, on the result.
+        (greater - less).cmp(&0)
+    }
+
+    /// Subtract `other`, wrapping around at `2^(w * len)` where `len` is the number of limbs of the
+    /// longer number and `w` their width in bits (see `as_limbs`). Also returns whether it did wrap,
+    /// that is, whether `self < other`. The time depends only on the lengths of the numbers.
+    pub fn ct_overflowing_sub(&self, other: &BigUint) -> (BigUint, bool) {
+        let (diff, borrow) = sub_digits(&self.data, &other.data);
+        (BigUint::from_digits(diff), black_box(borrow) == 1)
+    }
+
+    /// Subtract `m` if that does not make the number negative, which is the last step of reducing
+    /// modulo `m`. The time depends only on the lengths of the numbers.
+    pub fn ct_sub_if_ge(&self, m: &BigUint) -> BigUint {
+        let (mut diff, borrow) = sub_digits(&self.data, &m.data);
+        // All ones if we keep the difference, all zeros if we keep `self`.
+        let keep_diff = black_box(borrow.wrapping_sub(1));
+        for (i, out) in diff.iter_mut().enumerate() {
+            *out = (*out & keep_diff) | (digit(&self.data, i) & !keep_diff);
+        }
+        BigUint::from_digits(diff)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use std::prelude::v1::*;
+    use super::super::{BigUint, Limb, LIMB_BITS};
+
+    fn samples() -> Vec<BigUint> {
+        [&[][..], &[0], &[1], &[2], &[Limb::MAX], &[0, 1], &[1, 1], &[Limb::MAX, 1], &[0, 2], &[5, 0, 1], &[5, 1, 1]]
+            .iter().map(|digits| digits.iter().cloned().collect()).collect()
+    }
+
+    #[test]
+    fn test_comparisons() {
+        for a in samples().iter() {
+            for b in samples().iter() {
+                assert_eq!(a.ct_eq(b), a == b, "{:?} == {:?}", a, b);
+                assert_eq!(a.ct_lt(b), a < b, "{:?} < {:?}", a, b);
+                assert_eq!(a.ct_cmp(b), a.cmp(b), "{:?} <=> {:?}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_subtraction() {
+        for a in samples().iter() {
+            for b in samples().iter() {
+                let (diff, wrapped) = a.ct_overflowing_sub(b);
+                assert_eq!(wrapped, a < b);
+                if wrapped {
+                    let len = ::std::cmp::max(a.data.len(), b.data.len());
+                    assert_eq!(&diff + b, a + &BigUint::power_of_2(LIMB_BITS as u64 * len as u64));
+                    assert_eq!(a.ct_sub_if_ge(b), *a);
+                } else {
+                    assert_eq!(diff, a - b);
+                    assert_eq!(a.ct_sub_if_ge(b), a - b);
+                }
+            }
+        }
+        assert_eq!(BigUint::new(5).ct_overflowing_sub(&BigUint::new(7)), (BigUint::power_of_2(LIMB_BITS as u64) - BigUint::new(2), true));
+        assert_eq!(BigUint::new(12).ct_sub_if_ge(&BigUint::new(7)), BigUint::new(5));
+        assert_eq!(BigUint::new(6).ct_sub_if_ge(&BigUint::new(7)), BigUint::new(6));
+    }
+}
@@ -0,0 +1,56 @@
+// Storing numbers in the binary format of the `binary` module. The payload of a `BigUint` is the
+// number of digits, followed by the digits, least significant first. A `BigInt` is a byte for the
+// sign (1 if it is negative, else 0), followed by the magnitude as a nested value.
+
+#[cfg(not(feature = "std"))]
+use std::prelude::v1::*;
+use binary::{self, Binary, Reader, DecodeError};
+use super::{BigInt, BigUint, Sign};
+
+impl Binary for BigUint {
+    const TAG: u8 = binary::tag::BIGINT;
+
+    fn encode_payload(&self, out: &mut Vec<u8>) {
+        // The digits are 64 bits wide, whatever the limbs are, so that the format is the same on
+        // all targets.
+        let digits = self.u64_digits();
+        binary::write_u64(out, digits.len() as u64);
+        for digit in digits {
+            binary::write_u64(out, digit);
+        }
+    }
+
+    fn decode_payload(input: &mut Reader) -> Result<Self, DecodeError> {
+        let len = input.read_u64()?;
+        let mut digits = Vec::new();
+        for _ in 0..len {
+            digits.push(input.read_u64()?);
+        }
+        if digits.last() == Some(&0) {
+            return Err(DecodeError::Invalid("leading zero digit"));
+        }
+        Ok(BigUint::from_vec(digits))
+    }
+}
+
+impl Binary for BigInt {
+    const TAG: u8 = binary::tag::SIGNED_BIGINT;
+
+    fn encode_payload(&self, out: &mut Vec<u8>) {
+        out.push(self.is_negative() as u8);
+        binary::write_nested(out, self.magnitude());
+    }
+
+    fn decode_payload(input: &mut Reader) -> Result<Self, DecodeError> {
+        let sign = match input.read_u8()? {
+            0 => Sign::Plus,
+            1 => Sign::Minus,
+            _ => return Err(DecodeError::Invalid("invalid sign")),
+        };
+        let magnitude: BigUint = input.read_nested()?;
+        if sign == Sign::Minus && magnitude.is_zero() {
+            return Err(DecodeError::Invalid("negative zero"));
+        }
+        Ok(BigInt::from_biguint(sign, magnitude))
+    }
+}
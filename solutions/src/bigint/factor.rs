@@ -0,0 +1,479 @@
+// Primality testing and factorization. Factors are found by trial division first, which cheaply
+// takes care of all the small primes - usually, that's most of them. Whatever is left afterwards
+// gets split using Pollard's rho method, until all pieces are prime.
+
+#[cfg(not(feature = "std"))]
+use std::prelude::v1::*;
+use std::cmp;
+use std::vec;
+use super::BigUint;
+#[cfg(feature = "std")]
+use skiplist::ConcurrentSkipListMap;
+
+// Trial division gives up at this divisor, and hands over to Pollard's rho method.
+const TRIAL_DIVISION_BOUND: u64 = 1 << 12;
+
+// We use the first 12 primes as bases for the Miller-Rabin test. That is known to make the test
+// deterministic for numbers below 3.18 * 10^23; beyond that, composite numbers passing the test for
+// all these bases are extremely rare.
+const MILLER_RABIN_BASES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+impl BigUint {
+    /// Check whether the number is prime, using the Miller-Rabin test. The answer is always right for
+    /// numbers below 3.18 * 10^23; for larger numbers, a composite number could in principle be
+    /// reported as prime, but no such number is known.
+    pub fn is_probable_prime(&self) -> bool {
+        if *self < 2 {
+            return false;
+        }
+        for &p in MILLER_RABIN_BASES.iter() {
+            let (_, rem) = self.div_rem_u64(p);
+            if rem == 0 {
+                return *self == p;
+            }
+        }
+        // Write `self - 1` as `d * 2^s` with odd `d`.
+        let one = BigUint::one();
+        let minus_one = self - &one;
+        let s = minus_one.trailing_zeros().unwrap();
+        let d = &minus_one >> (s as usize);
+        'bases: for &a in MILLER_RABIN_BASES.iter() {
+            let mut x = BigUint::new(a).mod_pow(&d, self);
+            if x == one || x == minus_one {
+                continue;
+            }
+            for _ in 1..s {
+                x = &x * &x % self;
+                if x == minus_one {
+                    continue 'bases;
+                }
+            }
+            // `a` witnesses that we are not prime.
+            return false;
+        }
+        true
+    }
+
+    /// Iterate over the prime factorization, as pairs of a prime and its exponent. Small primes are
+    /// found right away by trial division, and come first and in increasing order. Larger factors are
+    /// computed lazily, so stopping early saves work; they are produced in the order they are found.
+    pub fn prime_factors(&self) -> PrimeFactors {
+        let (small, rest) = self.trial_divide_up_to(TRIAL_DIVISION_BOUND);
+        let pending = if rest > 1 { vec![rest] } else { vec![] };
+        PrimeFactors { small: small.into_iter(), pending, rho_seed: 1 }
+    }
+}
+
+// A simple primality test for small numbers, good enough to walk through the first few primes.
+fn is_small_prime(n: u64) -> bool {
+    n >= 2 && (2..).take_while(|d| d * d <= n).all(|d| !n.is_multiple_of(d))
+}
+
+impl BigUint {
+    /// Check whether the number is a square. This is the same as `is_perfect_square`.
+    pub fn is_square(&self) -> bool {
+        self.is_perfect_square()
+    }
+
+    /// Check whether the number is a perfect power `base^exp` with `exp >= 2`. If so, return the
+    /// representation with the largest exponent. 0 and 1 are reported as their own squares.
+    pub fn is_perfect_power(&self) -> Option<(BigUint, u32)> {
+        if *self <= 1 {
+            return Some((self.clone(), 2));
+        }
+        // If `self = base^(p*q)`, we find this by first taking the `p`-th root, then the `q`-th root.
+        // So it is enough to try prime exponents, and repeat after every success. An exponent larger
+        // than the number of bits would mean that the root is 1.
+        let mut base = self.clone();
+        let mut exp: u32 = 1;
+        'search: loop {
+            let bits = base.bit_length();
+            for p in (2..).take_while(|&p| (p as u64) < bits).filter(|&p| is_small_prime(p as u64)) {
+                let root = base.nth_root(p);
+                if root.pow(p) == base {
+                    base = root;
+                    exp *= p;
+                    continue 'search;
+                }
+            }
+            break;
+        }
+        if exp > 1 { Some((base, exp)) } else { None }
+    }
+
+    /// Return the `bound`-smooth part of the number: the largest divisor whose prime factors are all
+    /// at most `bound`. This is 0 for 0.
+    pub fn smooth_part(&self, bound: u64) -> BigUint {
+        if self.data.is_empty() {
+            return BigUint::new(0);
+        }
+        let (factors, _) = self.trial_divide_up_to(bound);
+        let mut smooth = BigUint::one();
+        for (p, exp) in factors {
+            for _ in 0..exp {
+                smooth.mul_add_u64(p, 0);
+            }
+        }
+        smooth
+    }
+
+    /// Divide out all prime factors up to `bound`. Returns these factors with their exponents, in
+    /// increasing order, and the remaining cofactor. All prime factors of the cofactor are larger than
+    /// `bound`. This is the first stage of factorization.
+    pub fn trial_divide_up_to(&self, bound: u64) -> (Vec<(u64, u32)>, BigUint) {
+        let mut factors = Vec::new();
+        let mut rest = self.clone();
+        if rest.data.is_empty() {
+            return (factors, rest);
+        }
+        for d in WheelDivisors::new().take_while(|&d| d <= bound) {
+            if BigUint::new(d) * BigUint::new(d) > rest {
+                // What is left is 1 or a prime.
+                if rest != 1 && rest <= bound {
+                    factors.push((rest.low_u64(), 1));
+                    rest = BigUint::one();
+                }
+                break;
+            }
+            let mut exp = 0;
+            loop {
+                let (quotient, rem) = rest.div_rem_u64(d);
+                if rem != 0 {
+                    break;
+                }
+                rest = quotient;
+                exp += 1;
+            }
+            if exp > 0 {
+                factors.push((d, exp));
+            }
+        }
+        (factors, rest)
+    }
+}
+
+// The gaps between the numbers coprime to 2*3*5*7 = 210, starting at 11.
+const WHEEL_GAPS: [u64; 48] = [
+    2, 4, 2, 4, 6, 2, 6, 4, 2, 4, 6, 6, 2, 6, 4, 2, 6, 4, 6, 8, 4, 2, 4, 2,
+    4, 8, 6, 4, 6, 2, 4, 6, 2, 6, 6, 4, 2, 4, 6, 2, 6, 4, 2, 4, 2, 10, 2, 10,
+];
+
+// Candidates for trial division: 2, 3, 5, 7, and then all numbers not divisible by any of these. That
+// skips 77% of all numbers, the majority of which could not be prime. The iteration stops when the
+// next candidate does not fit into a `u64`.
+struct WheelDivisors {
+    next: Option<u64>,
+    gap: usize,
+}
+
+impl WheelDivisors {
+    fn new() -> Self {
+        WheelDivisors { next: Some(2), gap: 0 }
+    }
+}
+
+impl Iterator for WheelDivisors {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        let d = self.next?;
+        self.next = match d {
+            2 => Some(3),
+            3 => Some(5),
+            5 => Some(7),
+            7 => Some(11),
+            _ => {
+                let next = d.checked_add(WHEEL_GAPS[self.gap]);
+                self.gap = (self.gap + 1) % WHEEL_GAPS.len();
+                next
+            }
+        };
+        Some(d)
+    }
+}
+
+/// The iterator returned by `BigUint::prime_factors`.
+pub struct PrimeFactors {
+    // The factors found by trial division, which we did not return yet.
+    small: vec::IntoIter<(u64, u32)>,
+    // The part of the number that we did not factor yet, split into pieces that multiply up to it.
+    pending: Vec<BigUint>,
+    // The next parameter for Pollard's rho method.
+    rho_seed: u64,
+}
+
+impl PrimeFactors {
+    // Remove all factors `p` from the pending pieces, and return how many there were.
+    fn remove_all(&mut self, p: &BigUint) -> u32 {
+        let mut count = 0;
+        for piece in self.pending.iter_mut() {
+            loop {
+                let (quotient, rem) = piece.div_rem(p);
+                if !rem.data.is_empty() {
+                    break;
+                }
+                *piece = quotient;
+                count += 1;
+            }
+        }
+        count
+    }
+}
+
+impl Iterator for PrimeFactors {
+    type Item = (BigUint, u32);
+
+    fn next(&mut self) -> Option<(BigUint, u32)> {
+        if let Some((p, exp)) = self.small.next() {
+            return Some((BigUint::new(p), exp));
+        }
+        // Split the pending pieces until one of them is prime.
+        while let Some(piece) = self.pending.pop() {
+            if piece == 1 {
+                continue;
+            }
+            if piece.is_probable_prime() {
+                let exp = 1 + self.remove_all(&piece);
+                return Some((piece, exp));
+            }
+            // Rho has a hard time with squares, but those are easy to split anyway.
+            let factor = if piece.is_perfect_square() {
+                piece.isqrt()
+            } else {
+                pollard_rho(&piece, &mut self.rho_seed)
+            };
+            let cofactor = &piece / &factor;
+            self.pending.push(factor);
+            self.pending.push(cofactor);
+        }
+        None
+    }
+}
+
+// Find a non-trivial factor of the composite number `n`, using Brent's variant of Pollard's rho
+// method: Iterating `x -> x^2 + c (mod n)` eventually cycles modulo every prime factor `p` of `n`,
+// usually after about `sqrt(p)` steps. When that happens, `p` divides the difference of two values
+// of the sequence, which we detect with a `gcd`. To save on `gcd`s, we multiply a bunch of
+// differences together before checking.
+fn pollard_rho(n: &BigUint, seed: &mut u64) -> BigUint {
+    const BATCH: u64 = 64;
+    let one = BigUint::one();
+    if n.is_even() {
+        return BigUint::new(2);
+    }
+    loop {
+        let c = BigUint::new(*seed);
+        *seed += 1;
+        let step = |x: &BigUint| (x * x + &c) % n;
+
+        let mut y = BigUint::new(2);
+        let mut x = y.clone();
+        let mut ys = y.clone();
+        let mut q = one.clone();
+        let mut g = one.clone();
+        let mut r: u64 = 1;
+        while g == one {
+            x = y.clone();
+            for _ in 0..r {
+                y = step(&y);
+            }
+            let mut k = 0;
+            while k < r && g == one {
+                ys = y.clone();
+                for _ in 0..cmp::min(BATCH, r - k) {
+                    y = step(&y);
+                    q = q * x.abs_diff(&y) % n;
+                }
+                g = q.gcd(n);
+                k += BATCH;
+            }
+            r *= 2;
+        }
+        if g == *n {
+            // The batch overshot; go through it again step by step.
+            loop {
+                ys = step(&ys);
+                g = x.abs_diff(&ys).gcd(n);
+                if g != one {
+                    break;
+                }
+            }
+        }
+        if g != *n {
+            return g;
+        }
+        // We found the trivial factor; try again with a different `c`.
+    }
+}
+
+/// Remembers the results of `is_probable_prime`, for sharing between threads. Testing a large number
+/// takes a dozen modular exponentiations, and factoring many numbers tends to run into the same
+/// primes again and again.
+#[cfg(feature = "std")]
+pub struct PrimeCache {
+    known: ConcurrentSkipListMap<BigUint, bool>,
+}
+
+#[cfg(feature = "std")]
+impl PrimeCache {
+    pub fn new() -> Self {
+        PrimeCache { known: ConcurrentSkipListMap::new() }
+    }
+
+    /// The number of numbers in the cache.
+    pub fn len(&self) -> usize {
+        self.known.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.known.is_empty()
+    }
+
+    /// Check whether `n` is prime, like `BigUint::is_probable_prime` does, or look up the answer.
+    pub fn is_probable_prime(&self, n: &BigUint) -> bool {
+        if let Some(known) = self.known.get(n) {
+            return known;
+        }
+        // We test without holding the lock, so two threads may end up testing the same number. They
+        // come to the same conclusion, though.
+        let result = n.is_probable_prime();
+        self.known.insert(n.clone(), result);
+        result
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for PrimeCache {
+    fn default() -> Self {
+        PrimeCache::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use std::prelude::v1::*;
+    use super::super::BigUint;
+
+    fn factorize(n: &BigUint) -> Vec<(BigUint, u32)> {
+        let mut factors: Vec<_> = n.prime_factors().collect();
+        factors.sort();
+        factors
+    }
+
+    fn big(v: u64) -> BigUint {
+        BigUint::new(v)
+    }
+
+    #[test]
+    fn test_is_probable_prime() {
+        let primes: Vec<u64> = (0..200).filter(|&n| n >= 2 && (2..n).all(|d| n % d != 0)).collect();
+        for n in 0..200 {
+            assert_eq!(big(n).is_probable_prime(), primes.contains(&n), "{}", n);
+        }
+        // Carmichael numbers and strong pseudoprimes to base 2.
+        for &n in [561, 41041, 825265, 2047, 3215031751, 3825123056546413051].iter() {
+            assert!(!big(n).is_probable_prime(), "{}", n);
+        }
+        assert!(big(18446744073709551557).is_probable_prime());
+        // 2^127 - 1 is a Mersenne prime, 2^128 + 1 is not.
+        assert!((BigUint::power_of_2(127) - big(1)).is_probable_prime());
+        assert!(!(BigUint::power_of_2(128) + big(1)).is_probable_prime());
+    }
+
+    #[test]
+    fn test_prime_factors() {
+        assert_eq!(factorize(&big(0)), vec![]);
+        assert_eq!(factorize(&big(1)), vec![]);
+        assert_eq!(factorize(&big(2)), vec![(big(2), 1)]);
+        assert_eq!(factorize(&big(360)), vec![(big(2), 3), (big(3), 2), (big(5), 1)]);
+        assert_eq!(factorize(&big(4093 * 4093 * 4099)), vec![(big(4093), 2), (big(4099), 1)]);
+        // Factors beyond the trial division bound.
+        let (p, q) = (big(1_000_003), big(4_294_967_311));
+        assert_eq!(factorize(&(&p * &q * &q * big(12))), vec![(big(2), 2), (big(3), 1), (p.clone(), 1), (q.clone(), 2)]);
+        assert_eq!(factorize(&(&p * &p * &p)), vec![(p, 3)]);
+        assert_eq!(factorize(&(BigUint::power_of_2(64) + big(1))), vec![(big(274177), 1), (big(67280421310721), 1)]);
+    }
+
+    #[test]
+    fn test_prime_factors_lazy() {
+        // The small factors come first and in order, so we can stop before the hard part.
+        let hard = BigUint::power_of_2(127) - big(1);
+        let n = &hard * &hard * big(2 * 2 * 7);
+        let mut factors = n.prime_factors();
+        assert_eq!(factors.next(), Some((big(2), 2)));
+        assert_eq!(factors.next(), Some((big(7), 1)));
+        let smooth: Vec<_> = (&hard * big(30)).prime_factors().take_while(|(p, _)| *p < big(1000)).collect();
+        assert_eq!(smooth, vec![(big(2), 1), (big(3), 1), (big(5), 1)]);
+    }
+
+    #[test]
+    fn test_is_perfect_power() {
+        assert_eq!(big(0).is_perfect_power(), Some((big(0), 2)));
+        assert_eq!(big(1).is_perfect_power(), Some((big(1), 2)));
+        assert_eq!(big(2).is_perfect_power(), None);
+        assert_eq!(big(8).is_perfect_power(), Some((big(2), 3)));
+        assert_eq!(big(36).is_perfect_power(), Some((big(6), 2)));
+        assert_eq!(big(72).is_perfect_power(), None);
+        assert_eq!(big(1 << 12).is_perfect_power(), Some((big(2), 12)));
+        assert_eq!(big(6u64.pow(15)).is_perfect_power(), Some((big(6), 15)));
+        assert_eq!(BigUint::power_of_2(200).is_perfect_power(), Some((big(2), 200)));
+        let b = BigUint::from_vec(vec![12345, 6789]);
+        assert_eq!(b.pow(6).is_perfect_power(), Some((b.clone(), 6)));
+        assert_eq!((b.pow(6) + big(1)).is_perfect_power(), None);
+        assert!(b.pow(6).is_square() && !b.pow(3).is_square());
+    }
+
+    #[test]
+    fn test_smooth_part() {
+        assert_eq!(big(0).smooth_part(10), big(0));
+        assert_eq!(big(1).smooth_part(10), big(1));
+        assert_eq!(big(360).smooth_part(1), big(1));
+        assert_eq!(big(360).smooth_part(2), big(8));
+        assert_eq!(big(360).smooth_part(4), big(72));
+        assert_eq!(big(360).smooth_part(5), big(360));
+        assert_eq!(big(2 * 3 * 101).smooth_part(100), big(6));
+        assert_eq!(big(2 * 3 * 101).smooth_part(101), big(606));
+        assert_eq!(big(2 * 3 * 101).smooth_part(u64::MAX), big(606));
+        let hard = BigUint::power_of_2(127) - big(1);
+        assert_eq!((&hard * big(4 * 9 * 49)).smooth_part(1000), big(4 * 9 * 49));
+    }
+
+    #[test]
+    fn test_wheel_divisors() {
+        let expected: Vec<u64> = (2..1000).filter(|&d| [2, 3, 5, 7].contains(&d) || [2, 3, 5, 7].iter().all(|&p| d % p != 0)).collect();
+        let wheel: Vec<u64> = super::WheelDivisors::new().take_while(|&d| d < 1000).collect();
+        assert_eq!(wheel, expected);
+    }
+
+    #[test]
+    fn test_trial_divide_up_to() {
+        assert_eq!(big(0).trial_divide_up_to(100), (vec![], big(0)));
+        assert_eq!(big(1).trial_divide_up_to(100), (vec![], big(1)));
+        assert_eq!(big(360).trial_divide_up_to(3), (vec![(2, 3), (3, 2)], big(5)));
+        assert_eq!(big(360).trial_divide_up_to(100), (vec![(2, 3), (3, 2), (5, 1)], big(1)));
+        assert_eq!(big(11 * 11 * 997).trial_divide_up_to(100), (vec![(11, 2)], big(997)));
+        assert_eq!(big(11 * 11 * 997).trial_divide_up_to(997), (vec![(11, 2), (997, 1)], big(1)));
+        let hard = BigUint::power_of_2(127) - big(1);
+        assert_eq!((&hard * big(143)).trial_divide_up_to(1 << 16), (vec![(11, 1), (13, 1)], hard));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_prime_cache() {
+        use std::sync::Arc;
+        use std::thread;
+        use super::PrimeCache;
+        let cache = Arc::new(PrimeCache::new());
+        let threads: Vec<_> = (0..4).map(|_| {
+            let cache = cache.clone();
+            thread::spawn(move || (0..100).filter(|&n| cache.is_probable_prime(&big(n))).count())
+        }).collect();
+        for thread in threads {
+            assert_eq!(thread.join().unwrap(), 25);
+        }
+        assert_eq!(cache.len(), 100);
+        assert!(cache.is_probable_prime(&(BigUint::power_of_2(127) - big(1))));
+        assert_eq!(cache.len(), 101);
+    }
+}
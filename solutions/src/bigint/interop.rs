@@ -0,0 +1,126 @@
+// Conversions between `BigUint` and the unsigned numbers of other big integer libraries:
+// `num_bigint::BigUint` with the `num-bigint` feature, and `ibig::UBig` with the `ibig` feature.
+// This helps with moving code over one function at a time, and with checking our results against an
+// established implementation. The conversions work on the digits directly, never on strings.
+
+#[cfg(feature = "num-bigint")]
+mod num_bigint_impl {
+    #[cfg(not(feature = "std"))]
+    use std::prelude::v1::*;
+    use num_bigint::BigUint as NumBigUint;
+    use super::super::BigUint;
+
+    impl From<NumBigUint> for BigUint {
+        fn from(n: NumBigUint) -> Self {
+            BigUint::from_vec(n.to_u64_digits())
+        }
+    }
+
+    // `num_bigint`'s `BigUint` can only be built from 32-bit digits, so we split ours in halves.
+    impl From<BigUint> for NumBigUint {
+        fn from(n: BigUint) -> Self {
+            NumBigUint::new(n.u64_digits().flat_map(|d| [d as u32, (d >> 32) as u32]).collect())
+        }
+    }
+
+    impl PartialEq<NumBigUint> for BigUint {
+        fn eq(&self, other: &NumBigUint) -> bool {
+            other.iter_u64_digits().eq(self.u64_digits())
+        }
+    }
+
+    impl PartialEq<BigUint> for NumBigUint {
+        fn eq(&self, other: &BigUint) -> bool {
+            other == self
+        }
+    }
+}
+
+#[cfg(feature = "ibig")]
+mod ibig_impl {
+    #[cfg(not(feature = "std"))]
+    use std::prelude::v1::*;
+    use ibig::UBig;
+    use super::super::BigUint;
+
+    // `UBig` only exposes its digits as little-endian bytes, without trailing zeros.
+    fn to_le_bytes(n: &BigUint) -> Vec<u8> {
+        let mut bytes: Vec<u8> = n.data.iter().flat_map(|d| d.to_le_bytes()).collect();
+        while bytes.last() == Some(&0) {
+            bytes.pop();
+        }
+        bytes
+    }
+
+    fn from_le_bytes(bytes: &[u8]) -> BigUint {
+        BigUint::from_vec(bytes.chunks(8).map(|chunk| {
+            let mut digit = [0; 8];
+            digit[..chunk.len()].copy_from_slice(chunk);
+            u64::from_le_bytes(digit)
+        }).collect())
+    }
+
+    impl From<UBig> for BigUint {
+        fn from(n: UBig) -> Self {
+            from_le_bytes(&n.to_le_bytes())
+        }
+    }
+
+    impl From<BigUint> for UBig {
+        fn from(n: BigUint) -> Self {
+            UBig::from_le_bytes(&to_le_bytes(&n))
+        }
+    }
+
+    impl PartialEq<UBig> for BigUint {
+        fn eq(&self, other: &UBig) -> bool {
+            to_le_bytes(self) == other.to_le_bytes()
+        }
+    }
+
+    impl PartialEq<BigUint> for UBig {
+        fn eq(&self, other: &BigUint) -> bool {
+            other == self
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::BigUint;
+
+    fn samples() -> Vec<BigUint> {
+        vec![BigUint::zero(), BigUint::new(1), BigUint::new(u64::MAX), BigUint::power_of_2(64), BigUint::factorial(50)]
+    }
+
+    #[cfg(feature = "num-bigint")]
+    #[test]
+    fn test_num_bigint() {
+        use num_bigint::BigUint as NumBigUint;
+        for n in samples() {
+            let other = NumBigUint::from(n.clone());
+            assert_eq!(other.to_string(), n.to_string());
+            assert_eq!(n, other);
+            assert_eq!(other, n);
+            assert_eq!(BigUint::from(other), n);
+        }
+        assert!(BigUint::new(2) != NumBigUint::from(3u32));
+        // Cross-check a computation.
+        let product = NumBigUint::from(BigUint::factorial(30)) * NumBigUint::from(BigUint::power_of_2(100));
+        assert_eq!(BigUint::from(product), BigUint::factorial(30) * BigUint::power_of_2(100));
+    }
+
+    #[cfg(feature = "ibig")]
+    #[test]
+    fn test_ibig() {
+        use ibig::UBig;
+        for n in samples() {
+            let other = UBig::from(n.clone());
+            assert_eq!(other.to_string(), n.to_string());
+            assert_eq!(n, other);
+            assert_eq!(other, n);
+            assert_eq!(BigUint::from(other), n);
+        }
+        assert!(BigUint::new(2) != UBig::from(3u32));
+    }
+}
@@ -0,0 +1,132 @@
+// The building blocks of the arithmetic on `BigUint`: operations on single digits ("limbs") that keep
+// track of the carry, and on slices of them. They are public, so that algorithms that are not part of
+// this crate can work on raw limbs, too. Numbers are always least significant limb first. The limbs
+// are `Limb`, or `u32` with the `u32-limbs` feature.
+
+use super::storage::{Limb, DoubleLimb, LIMB_BITS};
+
+/// Compute `a + b + carry`, returning the sum and whether there is a carry out of it.
+#[inline(always)]
+pub fn add_with_carry(a: Limb, b: Limb, carry: bool) -> (Limb, bool) {
+    // Without branches, the compiler turns this into a single add-with-carry instruction.
+    let (sum, carry1) = a.overflowing_add(b);
+    let (sum, carry2) = sum.overflowing_add(carry as Limb);
+    (sum, carry1 | carry2)
+}
+
+/// Compute `a - b - borrow`, returning the difference and whether it wrapped around.
+#[inline(always)]
+pub fn sub_with_borrow(a: Limb, b: Limb, borrow: bool) -> (Limb, bool) {
+    let (diff, borrow1) = a.overflowing_sub(b);
+    let (diff, borrow2) = diff.overflowing_sub(borrow as Limb);
+    (diff, borrow1 | borrow2)
+}
+
+/// Compute `a * b + addend + carry`, returning the low and the high limb. This cannot overflow: for
+/// `b` bits, `(2^b-1)^2 + 2*(2^b-1) = 2^(2b)-1`.
+#[inline(always)]
+pub fn mul_add_carry(a: Limb, b: Limb, addend: Limb, carry: Limb) -> (Limb, Limb) {
+    let cur = (a as DoubleLimb) * (b as DoubleLimb) + (addend as DoubleLimb) + (carry as DoubleLimb);
+    (cur as Limb, (cur >> LIMB_BITS) as Limb)
+}
+
+/// Add `b` to `a` in place, and return the carry out of the top limb of `a`. Panics if `b` is longer
+/// than `a`.
+pub fn add_slices_in_place(a: &mut [Limb], b: &[Limb]) -> bool {
+    assert!(a.len() >= b.len(), "add_slices_in_place: the second slice is longer than the first");
+    let mut carry = false;
+    for (a, &b) in a.iter_mut().zip(b) {
+        let (sum, new_carry) = add_with_carry(*a, b, carry);
+        *a = sum;
+        carry = new_carry;
+    }
+    for a in a[b.len()..].iter_mut() {
+        if !carry {
+            break;
+        }
+        let (sum, new_carry) = a.overflowing_add(1);
+        *a = sum;
+        carry = new_carry;
+    }
+    carry
+}
+
+/// Subtract `b` from `a` in place, and return whether the result wrapped around, i.e., whether `b`
+/// was larger. Panics if `b` is longer than `a`.
+pub fn sub_slices_in_place(a: &mut [Limb], b: &[Limb]) -> bool {
+    assert!(a.len() >= b.len(), "sub_slices_in_place: the second slice is longer than the first");
+    let mut borrow = false;
+    for (a, &b) in a.iter_mut().zip(b) {
+        let (diff, new_borrow) = sub_with_borrow(*a, b, borrow);
+        *a = diff;
+        borrow = new_borrow;
+    }
+    for a in a[b.len()..].iter_mut() {
+        if !borrow {
+            break;
+        }
+        let (diff, new_borrow) = a.overflowing_sub(1);
+        *a = diff;
+        borrow = new_borrow;
+    }
+    borrow
+}
+
+/// Compute `a * factor + addend` in place, and return the limb that is carried out of the top.
+pub fn mul_limb_in_place(a: &mut [Limb], factor: Limb, addend: Limb) -> Limb {
+    let mut carry = addend;
+    for a in a.iter_mut() {
+        let (low, high) = mul_add_carry(*a, factor, 0, carry);
+        *a = low;
+        carry = high;
+    }
+    carry
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    // The highest bit of a limb.
+    const TOP: Limb = 1 << (LIMB_BITS - 1);
+
+    #[test]
+    fn test_add_with_carry() {
+        assert_eq!(add_with_carry(10, 100, false), (110, false));
+        assert_eq!(add_with_carry(10, 100, true), (111, false));
+        assert_eq!(add_with_carry(TOP, TOP, false), (0, true));
+        assert_eq!(add_with_carry(TOP, TOP, true), (1, true));
+        assert_eq!(add_with_carry(TOP, TOP - 1, true), (0, true));
+    }
+
+    #[test]
+    fn test_sub_with_borrow() {
+        assert_eq!(sub_with_borrow(100, 10, false), (90, false));
+        assert_eq!(sub_with_borrow(100, 10, true), (89, false));
+        assert_eq!(sub_with_borrow(10, TOP, false), (TOP + 10, true));
+        assert_eq!(sub_with_borrow(10, TOP, true), (TOP + 9, true));
+        assert_eq!(sub_with_borrow(42, 42 , true), (Limb::MAX, true));
+    }
+
+    #[test]
+    fn test_mul_add_carry() {
+        assert_eq!(mul_add_carry(3, 4, 5, 6), (23, 0));
+        assert_eq!(mul_add_carry(Limb::MAX, Limb::MAX, Limb::MAX, Limb::MAX), (Limb::MAX, Limb::MAX));
+        assert_eq!(mul_add_carry(1 << (LIMB_BITS / 2), 1 << (LIMB_BITS / 2), 0, 1), (1, 1));
+    }
+
+    #[test]
+    fn test_slices() {
+        let mut a = [Limb::MAX, Limb::MAX, 5];
+        assert!(!add_slices_in_place(&mut a, &[1]));
+        assert_eq!(a, [0, 0, 6]);
+        assert!(sub_slices_in_place(&mut a, &[1, 0, 6]));
+        assert_eq!(a, [Limb::MAX, Limb::MAX, Limb::MAX]);
+        assert!(add_slices_in_place(&mut a, &[1]));
+        assert_eq!(a, [0, 0, 0]);
+        assert!(!sub_slices_in_place(&mut a, &[]));
+
+        let mut a = [Limb::MAX, 2];
+        assert_eq!(mul_limb_in_place(&mut a, TOP, 1), 1);
+        assert_eq!(a, [TOP + 1, TOP - 1]);
+    }
+}
@@ -0,0 +1,370 @@
+// The traits of the num-traits and num-integer crates, so that generic numeric code written against
+// those can use `BigUint` and `BigInt` directly. They mostly forward to the inherent methods of the
+// same name.
+
+#[cfg(not(feature = "std"))]
+use std::prelude::v1::*;
+use std::convert::TryFrom;
+use num_traits::{Zero, One, Num, Signed, ToPrimitive, FromPrimitive};
+use num_integer::Integer;
+use super::{BigInt, BigUint, ParseBigIntError, Sign};
+
+impl Zero for BigUint {
+    fn zero() -> Self {
+        BigUint::zero()
+    }
+
+    fn is_zero(&self) -> bool {
+        BigUint::is_zero(self)
+    }
+}
+
+impl One for BigUint {
+    fn one() -> Self {
+        BigUint::one()
+    }
+
+    fn is_one(&self) -> bool {
+        BigUint::is_one(self)
+    }
+}
+
+impl Num for BigUint {
+    type FromStrRadixErr = ParseBigIntError;
+
+    fn from_str_radix(src: &str, radix: u32) -> Result<Self, ParseBigIntError> {
+        BigUint::from_str_radix(src, radix)
+    }
+}
+
+// We only have non-negative numbers, so rounding towards negative infinity (`div_floor`) is the
+// same as truncating division.
+impl Integer for BigUint {
+    fn div_floor(&self, other: &Self) -> Self {
+        self / other
+    }
+
+    fn mod_floor(&self, other: &Self) -> Self {
+        self % other
+    }
+
+    fn gcd(&self, other: &Self) -> Self {
+        BigUint::gcd(self, other)
+    }
+
+    fn lcm(&self, other: &Self) -> Self {
+        if self.is_zero() || other.is_zero() {
+            return BigUint::zero();
+        }
+        self / &BigUint::gcd(self, other) * other
+    }
+
+    // Like the primitive types, we consider 0 a multiple only of 0.
+    fn is_multiple_of(&self, other: &Self) -> bool {
+        if other.is_zero() {
+            self.is_zero()
+        } else {
+            (self % other).is_zero()
+        }
+    }
+
+    fn is_even(&self) -> bool {
+        BigUint::is_even(self)
+    }
+
+    fn is_odd(&self) -> bool {
+        BigUint::is_odd(self)
+    }
+
+    fn div_rem(&self, other: &Self) -> (Self, Self) {
+        BigUint::div_rem(self, other)
+    }
+}
+
+impl ToPrimitive for BigUint {
+    fn to_i64(&self) -> Option<i64> {
+        self.to_u64().and_then(|x| i64::try_from(x).ok())
+    }
+
+    fn to_u64(&self) -> Option<u64> {
+        if self.bit_length() <= 64 { Some(self.low_u64()) } else { None }
+    }
+
+    fn to_u128(&self) -> Option<u128> {
+        if self.bit_length() <= 128 {
+            Some(self.u64_digits().enumerate().fold(0, |acc, (i, digit)| acc | (digit as u128) << (64 * i)))
+        } else {
+            None
+        }
+    }
+
+    // The default would go through `to_u64`, and give up on larger numbers.
+    fn to_f64(&self) -> Option<f64> {
+        Some(BigUint::to_f64(self))
+    }
+}
+
+impl FromPrimitive for BigUint {
+    fn from_i64(n: i64) -> Option<Self> {
+        u64::try_from(n).ok().map(BigUint::new)
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        Some(BigUint::new(n))
+    }
+
+    fn from_u128(n: u128) -> Option<Self> {
+        Some(BigUint::from_vec(vec![n as u64, (n >> 64) as u64]))
+    }
+
+    // Like for the primitive types, the fractional part is dropped, so anything above -1 works.
+    fn from_f64(n: f64) -> Option<Self> {
+        if n > -1.0 && n <= 0.0 {
+            Some(BigUint::zero())
+        } else {
+            BigUint::try_from(n).ok()
+        }
+    }
+}
+
+impl Zero for BigInt {
+    fn zero() -> Self {
+        BigInt::zero()
+    }
+
+    fn is_zero(&self) -> bool {
+        BigInt::is_zero(self)
+    }
+}
+
+impl One for BigInt {
+    fn one() -> Self {
+        BigInt::one()
+    }
+
+    fn is_one(&self) -> bool {
+        self.is_positive() && self.magnitude().is_one()
+    }
+}
+
+impl Num for BigInt {
+    type FromStrRadixErr = ParseBigIntError;
+
+    fn from_str_radix(src: &str, radix: u32) -> Result<Self, ParseBigIntError> {
+        BigInt::from_str_radix(src, radix)
+    }
+}
+
+impl Signed for BigInt {
+    fn abs(&self) -> Self {
+        BigInt::abs(self)
+    }
+
+    fn abs_sub(&self, other: &Self) -> Self {
+        if self <= other { BigInt::zero() } else { self - other }
+    }
+
+    fn signum(&self) -> Self {
+        BigInt::from_biguint(self.sign(), BigUint::one())
+    }
+
+    fn is_positive(&self) -> bool {
+        BigInt::is_positive(self)
+    }
+
+    fn is_negative(&self) -> bool {
+        BigInt::is_negative(self)
+    }
+}
+
+// The division of `BigInt` truncates, like the one of the primitive types. For `div_floor`, we have to
+// correct the quotient when the remainder and the divisor have different signs.
+impl Integer for BigInt {
+    fn div_floor(&self, other: &Self) -> Self {
+        self.div_mod_floor(other).0
+    }
+
+    fn mod_floor(&self, other: &Self) -> Self {
+        self.div_mod_floor(other).1
+    }
+
+    fn div_mod_floor(&self, other: &Self) -> (Self, Self) {
+        let (q, r) = BigInt::div_rem(self, other);
+        if !r.is_zero() && r.is_negative() != other.is_negative() {
+            (q - BigInt::one(), r + other)
+        } else {
+            (q, r)
+        }
+    }
+
+    fn gcd(&self, other: &Self) -> Self {
+        BigInt::gcd(self, other)
+    }
+
+    // Like `gcd`, this is never negative.
+    fn lcm(&self, other: &Self) -> Self {
+        BigInt::from(Integer::lcm(self.magnitude(), other.magnitude()))
+    }
+
+    fn is_multiple_of(&self, other: &Self) -> bool {
+        self.magnitude().is_multiple_of(other.magnitude())
+    }
+
+    fn is_even(&self) -> bool {
+        BigInt::is_even(self)
+    }
+
+    fn is_odd(&self) -> bool {
+        BigInt::is_odd(self)
+    }
+
+    fn div_rem(&self, other: &Self) -> (Self, Self) {
+        BigInt::div_rem(self, other)
+    }
+}
+
+impl ToPrimitive for BigInt {
+    fn to_i64(&self) -> Option<i64> {
+        let magnitude = self.magnitude().to_u64()?;
+        if self.is_negative() { 0i64.checked_sub_unsigned(magnitude) } else { i64::try_from(magnitude).ok() }
+    }
+
+    fn to_u64(&self) -> Option<u64> {
+        if self.is_negative() { None } else { self.magnitude().to_u64() }
+    }
+
+    fn to_i128(&self) -> Option<i128> {
+        let magnitude = self.magnitude().to_u128()?;
+        if self.is_negative() { 0i128.checked_sub_unsigned(magnitude) } else { i128::try_from(magnitude).ok() }
+    }
+
+    fn to_u128(&self) -> Option<u128> {
+        if self.is_negative() { None } else { self.magnitude().to_u128() }
+    }
+
+    fn to_f64(&self) -> Option<f64> {
+        Some(BigInt::to_f64(self))
+    }
+}
+
+impl FromPrimitive for BigInt {
+    fn from_i64(n: i64) -> Option<Self> {
+        Some(BigInt::new(n))
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        Some(BigInt::from(BigUint::new(n)))
+    }
+
+    fn from_i128(n: i128) -> Option<Self> {
+        let sign = if n < 0 { Sign::Minus } else { Sign::Plus };
+        BigUint::from_u128(n.unsigned_abs()).map(|magnitude| BigInt::from_biguint(sign, magnitude))
+    }
+
+    fn from_u128(n: u128) -> Option<Self> {
+        BigUint::from_u128(n).map(BigInt::from)
+    }
+
+    // The fractional part is dropped, as for the primitive types.
+    fn from_f64(n: f64) -> Option<Self> {
+        BigInt::try_from(n).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_traits::{Num, One, Signed, ToPrimitive, FromPrimitive};
+    use num_integer::Integer;
+    use super::super::{BigInt, BigUint};
+
+    // Some generic code that knows nothing about `BigUint`.
+    fn sum_of_squares<T: Num + Clone>(xs: &[T]) -> T {
+        xs.iter().fold(T::zero(), |sum, x| sum + x.clone() * x.clone())
+    }
+
+    fn lcm_of<T: Integer + One>(xs: &[T]) -> T {
+        xs.iter().fold(T::one(), |l, x| l.lcm(x))
+    }
+
+    #[test]
+    fn test_generic_code() {
+        let xs: Vec<BigUint> = (1..11).map(BigUint::new).collect();
+        assert_eq!(sum_of_squares(&xs), BigUint::new(385));
+        assert_eq!(lcm_of(&xs), BigUint::new(2520));
+        assert_eq!(<BigUint as Num>::from_str_radix("ff", 16), Ok(BigUint::new(255)));
+    }
+
+    #[test]
+    fn test_integer() {
+        let (a, b) = (BigUint::new(12), BigUint::new(18));
+        assert_eq!(a.div_floor(&BigUint::new(5)), BigUint::new(2));
+        assert_eq!(a.mod_floor(&BigUint::new(5)), BigUint::new(2));
+        assert_eq!(Integer::gcd(&a, &b), BigUint::new(6));
+        assert_eq!(a.lcm(&b), BigUint::new(36));
+        assert_eq!(a.lcm(&BigUint::zero()), BigUint::zero());
+        assert!(b.is_multiple_of(&BigUint::new(6)));
+        assert!(!b.is_multiple_of(&a));
+        assert!(BigUint::zero().is_multiple_of(&BigUint::zero()));
+        assert!(!a.is_multiple_of(&BigUint::zero()));
+        assert_eq!(Integer::div_rem(&b, &a), (BigUint::new(1), BigUint::new(6)));
+    }
+
+    #[test]
+    fn test_primitive_conversions() {
+        let big = BigUint::power_of_2(100);
+        assert_eq!(BigUint::new(42).to_i64(), Some(42));
+        assert_eq!(BigUint::new(u64::MAX).to_i64(), None);
+        assert_eq!(BigUint::new(u64::MAX).to_u64(), Some(u64::MAX));
+        assert_eq!(big.to_u64(), None);
+        assert_eq!(big.to_u128(), Some(1 << 100));
+        assert_eq!((&big * &big).to_u128(), None);
+        assert_eq!(ToPrimitive::to_f64(&big), Some(2f64.powi(100)));
+        assert_eq!(BigUint::new(300).to_u8(), None);
+        assert_eq!(BigUint::from_i64(-1), None);
+        assert_eq!(BigUint::from_i32(7), Some(BigUint::new(7)));
+        assert_eq!(BigUint::from_u128(1 << 100), Some(big));
+        assert_eq!(BigUint::from_f64(-0.5), Some(BigUint::zero()));
+        assert_eq!(BigUint::from_f64(-1.0), None);
+        assert_eq!(BigUint::from_f64(1e20), Some(BigUint::new(10).pow(20)));
+        assert_eq!(BigUint::from_f64(f64::NAN), None);
+    }
+
+    #[test]
+    fn test_signed() {
+        let xs: Vec<BigInt> = (-5..6).map(BigInt::new).collect();
+        assert_eq!(sum_of_squares(&xs), BigInt::new(110));
+        assert_eq!(lcm_of(&[BigInt::new(-4), BigInt::new(6)]), BigInt::new(12));
+        assert_eq!(<BigInt as Num>::from_str_radix("-ff", 16), Ok(BigInt::new(-255)));
+        assert!(BigInt::one().is_one() && !BigInt::new(-1).is_one());
+        assert_eq!(Signed::abs(&BigInt::new(-3)), BigInt::new(3));
+        assert_eq!(BigInt::new(-3).signum(), BigInt::new(-1));
+        assert_eq!(BigInt::zero().signum(), BigInt::zero());
+        assert_eq!(BigInt::new(2).abs_sub(&BigInt::new(5)), BigInt::zero());
+        assert_eq!(BigInt::new(5).abs_sub(&BigInt::new(-2)), BigInt::new(7));
+        // Rounding towards negative infinity, compared with what num-integer does for `i64`.
+        for &(a, b) in [(7i64, 2i64), (-7, 2), (7, -2), (-7, -2), (-6, 3), (6, -4)].iter() {
+            let (q, r) = BigInt::new(a).div_mod_floor(&BigInt::new(b));
+            assert_eq!((q.clone(), r.clone()), (BigInt::new(Integer::div_floor(&a, &b)), BigInt::new(Integer::mod_floor(&a, &b))), "{} {}", a, b);
+            assert_eq!((BigInt::new(a).div_floor(&BigInt::new(b)), BigInt::new(a).mod_floor(&BigInt::new(b))), (q, r));
+        }
+        assert!(BigInt::new(-12).is_multiple_of(&BigInt::new(4)));
+        assert!(BigInt::new(-7).is_odd());
+    }
+
+    #[test]
+    fn test_signed_primitive_conversions() {
+        assert_eq!(BigInt::new(i64::MIN).to_i64(), Some(i64::MIN));
+        assert_eq!((BigInt::new(i64::MIN) - BigInt::one()).to_i64(), None);
+        assert_eq!(BigInt::from(BigUint::new(1 << 63)).to_i64(), None);
+        assert_eq!(BigInt::new(-1).to_u64(), None);
+        assert_eq!(BigInt::from_i128(i128::MIN).and_then(|n| n.to_i128()), Some(i128::MIN));
+        assert_eq!(BigInt::from_u128(u128::MAX).and_then(|n| n.to_u128()), Some(u128::MAX));
+        assert_eq!(BigInt::from_u128(u128::MAX).and_then(|n| n.to_i128()), None);
+        assert_eq!(BigInt::from_i32(-7), Some(BigInt::new(-7)));
+        assert_eq!(BigInt::from_f64(-2.5), Some(BigInt::new(-2)));
+        assert_eq!(BigInt::from_f64(f64::INFINITY), None);
+        assert_eq!(ToPrimitive::to_f64(&BigInt::new(-3)), Some(-3.0));
+        assert_eq!(BigInt::new(-300).to_i8(), None);
+        assert_eq!(BigInt::new(-100).to_i8(), Some(-100));
+    }
+}
@@ -0,0 +1,255 @@
+// Conversions between `BigUint` and two rather more human ways of writing numbers: Roman numerals and
+// English words. Both boil down to repeatedly splitting off groups of three decimal digits.
+
+#[cfg(not(feature = "std"))]
+use std::prelude::v1::*;
+use super::BigUint;
+
+// The Roman symbols with their values, including the subtractive pairs, largest first.
+const ROMAN: &[(u64, &str)] = &[
+    (1000, "M"), (900, "CM"), (500, "D"), (400, "CD"), (100, "C"), (90, "XC"),
+    (50, "L"), (40, "XL"), (10, "X"), (9, "IX"), (5, "V"), (4, "IV"), (1, "I"),
+];
+
+// A combining overline. In the vinculum notation, an overline multiplies a symbol by 1000.
+const OVERLINE: char = '\u{305}';
+
+// Write `n` (which must be less than 4000) with the standard symbols, each followed by `level`
+// overlines.
+fn push_roman(out: &mut String, mut n: u64, level: usize) {
+    debug_assert!(n < 4000);
+    for &(value, symbols) in ROMAN.iter() {
+        while n >= value {
+            for symbol in symbols.chars() {
+                out.push(symbol);
+                out.extend((0..level).map(|_| OVERLINE));
+            }
+            n -= value;
+        }
+    }
+}
+
+const SMALL_WORDS: &[&str] = &[
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten",
+    "eleven", "twelve", "thirteen", "fourteen", "fifteen", "sixteen", "seventeen", "eighteen", "nineteen",
+];
+const TENS_WORDS: &[&str] = &["", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety"];
+// The (short scale) names of the powers of 1000. Beyond the last one, we keep using it, i.e., 10^66 is
+// "one thousand vigintillion".
+const SCALE_WORDS: &[&str] = &[
+    "", "thousand", "million", "billion", "trillion", "quadrillion", "quintillion", "sextillion",
+    "septillion", "octillion", "nonillion", "decillion", "undecillion", "duodecillion", "tredecillion",
+    "quattuordecillion", "quindecillion", "sexdecillion", "septendecillion", "octodecillion",
+    "novemdecillion", "vigintillion",
+];
+
+// Spell out a number between 1 and 999.
+fn push_words_below_1000(words: &mut Vec<&'static str>, n: u64) {
+    debug_assert!(n > 0 && n < 1000);
+    let (hundreds, rest) = (n / 100, n % 100);
+    if hundreds > 0 {
+        words.push(SMALL_WORDS[hundreds as usize]);
+        words.push("hundred");
+    }
+    if rest >= 20 {
+        words.push(TENS_WORDS[(rest / 10) as usize]);
+        if rest % 10 != 0 {
+            // We push the two parts separately, and put in the hyphen when assembling the string.
+            words.push("-");
+            words.push(SMALL_WORDS[(rest % 10) as usize]);
+        }
+    } else if rest > 0 {
+        words.push(SMALL_WORDS[rest as usize]);
+    }
+}
+
+// Split a number into groups of three decimal digits, least significant group first.
+fn digit_groups(n: &BigUint) -> Vec<u64> {
+    // We divide off six groups at once, which saves a lot of passes over a big number.
+    let mut groups = Vec::new();
+    let mut rest = n.clone();
+    while !rest.data.is_empty() {
+        let (quotient, mut six_groups) = rest.div_rem_u64(1_000_000_000_000_000_000);
+        for _ in 0..6 {
+            groups.push(six_groups % 1000);
+            six_groups /= 1000;
+        }
+        rest = quotient;
+    }
+    while groups.last() == Some(&0) {
+        groups.pop();
+    }
+    groups
+}
+
+impl BigUint {
+    /// Write the number as a Roman numeral. From 4000 upwards, this uses overlines (as combining
+    /// characters) to multiply by 1000, possibly several times. There is no Roman numeral for 0.
+    pub fn to_roman(&self) -> Option<String> {
+        if self.data.is_empty() {
+            return None;
+        }
+        // Everything from 4000 upwards is split into thousands, which go one overline level up, and
+        // the rest. We do the splitting iteratively; a recursion would get as deep as the number has
+        // thousands groups.
+        let mut groups = Vec::new();
+        let mut top = self.clone();
+        while top >= 4000 {
+            let (thousands, rest) = top.div_rem_u64(1000);
+            groups.push(rest);
+            top = thousands;
+        }
+        let mut out = String::new();
+        push_roman(&mut out, top.low_u64(), groups.len());
+        for (level, &group) in groups.iter().enumerate().rev() {
+            push_roman(&mut out, group, level);
+        }
+        Some(out)
+    }
+
+    /// Parse a Roman numeral as written by `to_roman`. Non-canonical numerals like "IIII" or "IM" are
+    /// rejected.
+    pub fn from_roman(s: &str) -> Option<BigUint> {
+        // First, determine the value of every symbol, including the overlines following it.
+        let mut values: Vec<BigUint> = Vec::new();
+        for c in s.chars() {
+            if c == OVERLINE {
+                let last = values.pop()?;
+                values.push(last * BigUint::new(1000));
+                continue;
+            }
+            let value = match c {
+                'I' => 1, 'V' => 5, 'X' => 10, 'L' => 50, 'C' => 100, 'D' => 500, 'M' => 1000,
+                _ => return None,
+            };
+            values.push(BigUint::new(value));
+        }
+        // A symbol followed by a larger one is subtracted, all others are added.
+        let mut sum = BigUint::new(0);
+        let mut subtract = BigUint::new(0);
+        for (idx, value) in values.iter().enumerate() {
+            if values.get(idx + 1).is_some_and(|next| next > value) {
+                subtract = subtract + value;
+            } else {
+                sum = sum + value;
+            }
+        }
+        // The symbols' values grow fast enough that the subtracted ones never outweigh the others.
+        let result = sum - subtract;
+        // Rather than spelling out all the rules for well-formed numerals, we just check that we
+        // would have written the number the same way.
+        if result.to_roman().as_ref().map(|r| &r[..]) == Some(s) {
+            Some(result)
+        } else {
+            None
+        }
+    }
+
+    /// Spell out the number in (American) English words, e.g. "two quadrillion three".
+    pub fn to_english_words(&self) -> String {
+        if self.data.is_empty() {
+            return SMALL_WORDS[0].to_string();
+        }
+        // Beyond the largest scale word, we count in units of it: We read the number as
+        // `(... (S_k vigintillion + S_k-1) vigintillion ...) + S_0`, where each `S_i` is made up of
+        // as many groups as we have scale words for.
+        let groups = digit_groups(self);
+        let per_scale = SCALE_WORDS.len() - 1;
+        let mut words = Vec::new();
+        for (repeat, chunk) in groups.chunks(per_scale).enumerate().rev() {
+            for (scale, &group) in chunk.iter().enumerate().rev() {
+                if group != 0 {
+                    push_words_below_1000(&mut words, group);
+                    if scale > 0 {
+                        words.push(SCALE_WORDS[scale]);
+                    }
+                }
+            }
+            if repeat > 0 {
+                words.push(SCALE_WORDS[per_scale]);
+            }
+        }
+        let mut out = String::new();
+        for (idx, word) in words.iter().enumerate() {
+            if idx > 0 && *word != "-" && words[idx - 1] != "-" {
+                out.push(' ');
+            }
+            out.push_str(word);
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::BigUint;
+
+    #[test]
+    fn test_to_roman() {
+        assert_eq!(BigUint::new(0).to_roman(), None);
+        assert_eq!(BigUint::new(1).to_roman().unwrap(), "I");
+        assert_eq!(BigUint::new(4).to_roman().unwrap(), "IV");
+        assert_eq!(BigUint::new(1994).to_roman().unwrap(), "MCMXCIV");
+        assert_eq!(BigUint::new(3999).to_roman().unwrap(), "MMMCMXCIX");
+        assert_eq!(BigUint::new(4000).to_roman().unwrap(), "I\u{305}V\u{305}");
+        assert_eq!(BigUint::new(4001).to_roman().unwrap(), "I\u{305}V\u{305}I");
+        assert_eq!(BigUint::new(12_345).to_roman().unwrap(), "X\u{305}I\u{305}I\u{305}CCCXLV");
+        assert_eq!(BigUint::new(5_000_000).to_roman().unwrap(), "V\u{305}\u{305}");
+    }
+
+    #[test]
+    fn test_from_roman() {
+        assert_eq!(BigUint::from_roman("MCMXCIV"), Some(BigUint::new(1994)));
+        assert_eq!(BigUint::from_roman("I\u{305}V\u{305}I"), Some(BigUint::new(4001)));
+        assert_eq!(BigUint::from_roman(""), None);
+        assert_eq!(BigUint::from_roman("IIII"), None);
+        assert_eq!(BigUint::from_roman("IM"), None);
+        assert_eq!(BigUint::from_roman("MMMM"), None);
+        assert_eq!(BigUint::from_roman("\u{305}I"), None);
+        assert_eq!(BigUint::from_roman("XLA"), None);
+        for n in (1..5000).chain(3_999_990..4_000_010) {
+            let b = BigUint::new(n);
+            assert_eq!(BigUint::from_roman(&b.to_roman().unwrap()), Some(b));
+        }
+        let big = BigUint::power_of_2(100);
+        assert_eq!(BigUint::from_roman(&big.to_roman().unwrap()), Some(big));
+    }
+
+    #[test]
+    fn test_to_english_words() {
+        assert_eq!(BigUint::new(0).to_english_words(), "zero");
+        assert_eq!(BigUint::new(7).to_english_words(), "seven");
+        assert_eq!(BigUint::new(13).to_english_words(), "thirteen");
+        assert_eq!(BigUint::new(40).to_english_words(), "forty");
+        assert_eq!(BigUint::new(42).to_english_words(), "forty-two");
+        assert_eq!(BigUint::new(100).to_english_words(), "one hundred");
+        assert_eq!(BigUint::new(999).to_english_words(), "nine hundred ninety-nine");
+        assert_eq!(BigUint::new(1_000_001).to_english_words(), "one million one");
+        assert_eq!(BigUint::new(2_000_000_000_000_003).to_english_words(), "two quadrillion three");
+        assert_eq!(BigUint::new(u64::MAX).to_english_words(),
+                   "eighteen quintillion four hundred forty-six quadrillion seven hundred forty-four trillion \
+                    seventy-three billion seven hundred nine million five hundred fifty-one thousand \
+                    six hundred fifteen");
+        assert_eq!(BigUint::new(10).pow(63).to_english_words(), "one vigintillion");
+        assert_eq!(BigUint::new(10).pow(66).to_english_words(), "one thousand vigintillion");
+        assert_eq!((BigUint::new(10).pow(129) + BigUint::new(21)).to_english_words(),
+                   "one thousand vigintillion vigintillion twenty-one");
+    }
+
+    #[test]
+    fn test_deep_numbers() {
+        // These used to be handled recursively, one level per group of digits. Make sure numbers
+        // with lots of groups work.
+        let b = BigUint::new(10).pow(3 * 1000) * BigUint::new(4);
+        let roman = b.to_roman().unwrap();
+        assert!(roman.starts_with(&format!("I{}V{}", "\u{305}".repeat(1000), "\u{305}".repeat(1000))));
+        assert_eq!(roman.chars().count(), 2 * 1001);
+        assert_eq!(BigUint::from_roman(&roman), Some(b));
+
+        let b = BigUint::new(10).pow(63 * 300) * BigUint::new(12) + BigUint::new(1);
+        let words = b.to_english_words();
+        assert!(words.starts_with("twelve vigintillion vigintillion"));
+        assert!(words.ends_with("vigintillion vigintillion one"));
+        assert_eq!(words.split(' ').count(), 1 + 300 + 1);
+    }
+}
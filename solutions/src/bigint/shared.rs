@@ -0,0 +1,138 @@
+// A `BigUint` that can be cloned cheaply. Cloning a `BigUint` copies all its digits; a `SharedBigInt`
+// instead shares them between all clones, and only copies them when one of the clones is modified
+// while others still exist ("copy on write").
+
+use std::cmp;
+use std::fmt;
+use std::ops;
+use std::sync::Arc;
+use super::BigUint;
+
+/// A reference-counted `BigUint` with copy-on-write. Through `Deref`, all the methods taking
+/// `&BigUint` are available.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct SharedBigInt {
+    inner: Arc<BigUint>,
+}
+
+impl SharedBigInt {
+    pub fn new(n: BigUint) -> Self {
+        SharedBigInt { inner: Arc::new(n) }
+    }
+
+    /// Get mutable access to the number. If it is shared with other clones, it is copied first.
+    pub fn make_mut(&mut self) -> &mut BigUint {
+        Arc::make_mut(&mut self.inner)
+    }
+
+    /// Return the number, copying it only if it is shared.
+    pub fn into_inner(self) -> BigUint {
+        Arc::try_unwrap(self.inner).unwrap_or_else(|shared| (*shared).clone())
+    }
+
+    /// Check whether two values share their digits.
+    pub fn ptr_eq(&self, other: &SharedBigInt) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl From<BigUint> for SharedBigInt {
+    fn from(n: BigUint) -> Self {
+        SharedBigInt::new(n)
+    }
+}
+
+impl From<SharedBigInt> for BigUint {
+    fn from(n: SharedBigInt) -> Self {
+        n.into_inner()
+    }
+}
+
+impl ops::Deref for SharedBigInt {
+    type Target = BigUint;
+    fn deref(&self) -> &BigUint {
+        &self.inner
+    }
+}
+
+impl PartialEq<BigUint> for SharedBigInt {
+    fn eq(&self, other: &BigUint) -> bool {
+        *self.inner == *other
+    }
+}
+
+impl PartialOrd<BigUint> for SharedBigInt {
+    fn partial_cmp(&self, other: &BigUint) -> Option<cmp::Ordering> {
+        (*self.inner).partial_cmp(other)
+    }
+}
+
+impl fmt::Debug for SharedBigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+impl fmt::Display for SharedBigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&*self.inner, f)
+    }
+}
+
+macro_rules! forward_shared_binop {
+    (impl $imp:ident, $method:ident) => {
+        impl ops::$imp<&SharedBigInt> for &SharedBigInt {
+            type Output = SharedBigInt;
+            #[inline]
+            fn $method(self, rhs: &SharedBigInt) -> SharedBigInt {
+                SharedBigInt::new(ops::$imp::$method(&*self.inner, &*rhs.inner))
+            }
+        }
+    }
+}
+
+forward_shared_binop!(impl Add, add);
+forward_shared_binop!(impl Sub, sub);
+forward_shared_binop!(impl Mul, mul);
+forward_shared_binop!(impl Div, div);
+forward_shared_binop!(impl Rem, rem);
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use std::prelude::v1::*;
+    use super::SharedBigInt;
+    use super::super::BigUint;
+
+    #[test]
+    fn test_copy_on_write() {
+        let a = SharedBigInt::new(BigUint::power_of_2(1000));
+        let mut b = a.clone();
+        assert!(a.ptr_eq(&b));
+        b.make_mut().inc1();
+        assert!(!a.ptr_eq(&b));
+        assert_eq!(a, BigUint::power_of_2(1000));
+        assert_eq!(b, BigUint::power_of_2(1000) + BigUint::one());
+        // Now `b` is not shared, so modifying it does not copy.
+        let before = &*b as *const BigUint;
+        b.make_mut().inc1();
+        assert_eq!(&*b as *const BigUint, before);
+    }
+
+    #[test]
+    fn test_ops_and_conversions() {
+        let a = SharedBigInt::from(BigUint::new(6));
+        let b = SharedBigInt::from(BigUint::new(4));
+        assert_eq!(&a + &b, BigUint::new(10));
+        assert_eq!(&a - &b, BigUint::new(2));
+        assert_eq!(&a * &b, BigUint::new(24));
+        assert_eq!(&a / &b, BigUint::new(1));
+        assert_eq!(&a % &b, BigUint::new(2));
+        assert!(a > b && a > BigUint::new(5));
+        assert_eq!(a.to_string(), "6");
+        assert!(a.is_even());
+        let c = a.clone();
+        assert_eq!(BigUint::from(a), BigUint::new(6));
+        assert_eq!(c.into_inner(), BigUint::new(6));
+    }
+}
@@ -0,0 +1,670 @@
+// Signed big integers. Just like `num_bigint`, we store a sign next to a `BigUint` for the magnitude,
+// and do all the actual work on the magnitudes. Everything that only makes sense for non-negative
+// numbers, like modular arithmetic or reading and setting single bits, stays on `BigUint`. The
+// bitwise operators are here, too, and act on the numbers in two's complement.
+
+#[cfg(not(feature = "std"))]
+use std::prelude::v1::*;
+use std::cmp;
+use std::convert::TryFrom;
+use std::fmt;
+use std::iter;
+use std::ops;
+use std::str::FromStr;
+use locale::Locale;
+use super::{trimmed_bounds, BigUint, Limb, Minimum, ParseBigIntError, TryFromF64Error};
+use super::limbs::add_slices_in_place;
+
+/// The sign of a `BigInt`. 0 is the only number with `NoSign`.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Sign {
+    Minus,
+    NoSign,
+    Plus,
+}
+
+impl ops::Neg for Sign {
+    type Output = Sign;
+    fn neg(self) -> Sign {
+        match self {
+            Sign::Minus => Sign::Plus,
+            Sign::NoSign => Sign::NoSign,
+            Sign::Plus => Sign::Minus,
+        }
+    }
+}
+
+impl ops::Mul for Sign {
+    type Output = Sign;
+    fn mul(self, other: Sign) -> Sign {
+        match (self, other) {
+            (Sign::NoSign, _) | (_, Sign::NoSign) => Sign::NoSign,
+            _ if self == other => Sign::Plus,
+            _ => Sign::Minus,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct BigInt {
+    sign: Sign, // `NoSign` exactly if the magnitude is 0.
+    magnitude: BigUint,
+}
+
+impl BigInt {
+    /// Construct a BigInt from a sign and a magnitude. A magnitude of 0 always makes 0, and so does
+    /// `NoSign`, whatever the magnitude.
+    pub fn from_biguint(sign: Sign, magnitude: BigUint) -> Self {
+        if sign == Sign::NoSign || magnitude.is_zero() {
+            BigInt::zero()
+        } else {
+            BigInt { sign, magnitude }
+        }
+    }
+
+    /// Construct a BigInt from a "small" one. This works in constants, too.
+    pub const fn new(x: i64) -> Self {
+        let sign = if x < 0 { Sign::Minus } else if x == 0 { Sign::NoSign } else { Sign::Plus };
+        BigInt { sign, magnitude: BigUint::new(x.unsigned_abs()) }
+    }
+
+    /// The number 0.
+    pub const fn zero() -> Self {
+        BigInt { sign: Sign::NoSign, magnitude: BigUint::zero() }
+    }
+
+    /// The number 1.
+    pub const fn one() -> Self {
+        BigInt { sign: Sign::Plus, magnitude: BigUint::one() }
+    }
+
+    pub fn sign(&self) -> Sign {
+        self.sign
+    }
+
+    /// The absolute value, as an unsigned number.
+    pub fn magnitude(&self) -> &BigUint {
+        &self.magnitude
+    }
+
+    pub fn into_parts(self) -> (Sign, BigUint) {
+        (self.sign, self.magnitude)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.sign == Sign::NoSign
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.sign == Sign::Minus
+    }
+
+    pub fn is_positive(&self) -> bool {
+        self.sign == Sign::Plus
+    }
+
+    /// The absolute value.
+    pub fn abs(&self) -> BigInt {
+        BigInt::from_biguint(Sign::Plus, self.magnitude.clone())
+    }
+
+    /// Convert to an unsigned number, if the number is not negative.
+    pub fn to_biguint(&self) -> Option<BigUint> {
+        if self.is_negative() { None } else { Some(self.magnitude.clone()) }
+    }
+
+    /// Raise the number to the given power.
+    pub fn pow(&self, exp: u32) -> BigInt {
+        let sign = if exp & 1 == 0 { Sign::Plus } else { self.sign };
+        BigInt::from_biguint(sign, self.magnitude.pow(exp))
+    }
+
+    /// Divide, returning the quotient and the remainder. Like for the primitive integers, the
+    /// quotient is rounded towards 0, so the remainder has the sign of `self`. Panics if `divisor`
+    /// is 0.
+    pub fn div_rem(&self, divisor: &BigInt) -> (BigInt, BigInt) {
+        let (quotient, rem) = self.magnitude.div_rem(&divisor.magnitude);
+        (BigInt::from_biguint(self.sign * divisor.sign, quotient), BigInt::from_biguint(self.sign, rem))
+    }
+
+    /// Compute `n!`. This and the other functions that only produce non-negative numbers are computed
+    /// as a `BigUint`.
+    pub fn factorial(n: u64) -> BigInt {
+        BigInt::from(BigUint::factorial(n))
+    }
+
+    /// Compute the binomial coefficient "`n` choose `k`".
+    pub fn binomial(n: u64, k: u64) -> BigInt {
+        BigInt::from(BigUint::binomial(n, k))
+    }
+
+    /// Compute the `n`-th Fibonacci number, with `fibonacci(0) == 0` and `fibonacci(1) == 1`.
+    pub fn fibonacci(n: u64) -> BigInt {
+        BigInt::from(BigUint::fibonacci(n))
+    }
+
+    pub fn is_even(&self) -> bool {
+        self.magnitude.is_even()
+    }
+
+    pub fn is_odd(&self) -> bool {
+        self.magnitude.is_odd()
+    }
+
+    /// Compute the greatest common divisor, which is never negative. By convention, `gcd(0, 0)` is 0.
+    pub fn gcd(&self, other: &BigInt) -> BigInt {
+        BigInt::from(self.magnitude.gcd(&other.magnitude))
+    }
+
+    /// Return the floor of the square root. Panics if the number is negative.
+    pub fn isqrt(&self) -> BigInt {
+        assert!(!self.is_negative(), "Square root of a negative number");
+        BigInt::from(self.magnitude.isqrt())
+    }
+
+    /// Return the `n`-th root, rounded towards 0, so that odd roots of negative numbers are negative.
+    /// Panics if `n` is 0, or if it is even and the number is negative.
+    pub fn nth_root(&self, n: u32) -> BigInt {
+        let root = self.magnitude.nth_root(n);
+        assert!(!self.is_negative() || n & 1 == 1, "Even root of a negative number");
+        BigInt::from_biguint(self.sign, root)
+    }
+
+    /// Compute the distance `|self - other|`.
+    pub fn abs_diff(&self, other: &BigInt) -> BigUint {
+        (self - other).magnitude
+    }
+
+    /// Divide by `divisor`, or return `None` if `divisor` is 0.
+    pub fn checked_div(&self, divisor: &BigInt) -> Option<BigInt> {
+        if divisor.is_zero() { None } else { Some(self / divisor) }
+    }
+
+    /// Compute the remainder of the division by `divisor`, or return `None` if `divisor` is 0.
+    pub fn checked_rem(&self, divisor: &BigInt) -> Option<BigInt> {
+        if divisor.is_zero() { None } else { Some(self % divisor) }
+    }
+
+    /// Convert to the nearest `f64`, like `BigUint::to_f64`. The rounding is symmetric, so negating
+    /// the number negates the result.
+    pub fn to_f64(&self) -> f64 {
+        let x = self.magnitude.to_f64();
+        if self.is_negative() { -x } else { x }
+    }
+
+    /// Render the number in base 10, with digits grouped according to the given locale.
+    pub fn format_localized(&self, locale: &Locale) -> String {
+        let digits = self.magnitude.format_localized(locale);
+        if self.is_negative() { format!("-{}", digits) } else { digits }
+    }
+
+    /// Parse a number from its digits in the given base, which must lie between 2 and 36, with an
+    /// optional sign in front. Like `BigUint::from_str_radix`, this skips surrounding whitespace and
+    /// underscores between the digits.
+    pub fn from_str_radix(src: &str, radix: u32) -> Result<BigInt, ParseBigIntError> {
+        let (mut start, end) = trimmed_bounds(src);
+        let sign = match src[start..end].chars().next() {
+            Some('-') => Sign::Minus,
+            _ => Sign::Plus,
+        };
+        if src[start..end].starts_with(['-', '+']) {
+            start += 1;
+        }
+        let magnitude = BigUint::parse_digits(src, start, end, radix, true)?;
+        Ok(BigInt::from_biguint(sign, magnitude))
+    }
+}
+
+impl Default for BigInt {
+    fn default() -> Self {
+        BigInt::zero()
+    }
+}
+
+impl From<BigUint> for BigInt {
+    fn from(magnitude: BigUint) -> Self {
+        BigInt::from_biguint(Sign::Plus, magnitude)
+    }
+}
+
+impl From<i64> for BigInt {
+    fn from(x: i64) -> Self {
+        BigInt::new(x)
+    }
+}
+
+/// Convert a finite `f64`, dropping the fractional part (that is, rounding towards 0).
+impl TryFrom<f64> for BigInt {
+    type Error = TryFromF64Error;
+
+    fn try_from(value: f64) -> Result<Self, TryFromF64Error> {
+        if !value.is_finite() {
+            return Err(TryFromF64Error { value, target: "BigInt" });
+        }
+        let sign = if value < 0.0 { Sign::Minus } else { Sign::Plus };
+        let magnitude = BigUint::try_from(if value < 0.0 { -value } else { value })?;
+        Ok(BigInt::from_biguint(sign, magnitude))
+    }
+}
+
+impl FromStr for BigInt {
+    type Err = ParseBigIntError;
+    /// Parse a number in base 10.
+    fn from_str(s: &str) -> Result<BigInt, ParseBigIntError> {
+        BigInt::from_str_radix(s, 10)
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad_integral(!self.is_negative(), "", &self.magnitude.to_decimal_string())
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &BigInt) -> cmp::Ordering {
+        // For negative numbers, the larger magnitude is the smaller number.
+        match self.sign.cmp(&other.sign) {
+            cmp::Ordering::Equal if self.sign == Sign::Minus => other.magnitude.cmp(&self.magnitude),
+            cmp::Ordering::Equal => self.magnitude.cmp(&other.magnitude),
+            unequal => unequal,
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &BigInt) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Comparing with primitive integers directly, like for `BigUint`. A `u64` is compared with the
+// magnitude, after the sign; an `i64` becomes a `BigInt`, which does not allocate.
+impl PartialEq<u64> for BigInt {
+    fn eq(&self, other: &u64) -> bool {
+        !self.is_negative() && self.magnitude == *other
+    }
+}
+
+impl PartialEq<BigInt> for u64 {
+    fn eq(&self, other: &BigInt) -> bool {
+        other == self
+    }
+}
+
+impl PartialOrd<u64> for BigInt {
+    fn partial_cmp(&self, other: &u64) -> Option<cmp::Ordering> {
+        if self.is_negative() { Some(cmp::Ordering::Less) } else { self.magnitude.partial_cmp(other) }
+    }
+}
+
+impl PartialOrd<BigInt> for u64 {
+    fn partial_cmp(&self, other: &BigInt) -> Option<cmp::Ordering> {
+        other.partial_cmp(self).map(cmp::Ordering::reverse)
+    }
+}
+
+impl PartialEq<i64> for BigInt {
+    fn eq(&self, other: &i64) -> bool {
+        *self == BigInt::new(*other)
+    }
+}
+
+impl PartialEq<BigInt> for i64 {
+    fn eq(&self, other: &BigInt) -> bool {
+        other == self
+    }
+}
+
+impl PartialOrd<i64> for BigInt {
+    fn partial_cmp(&self, other: &i64) -> Option<cmp::Ordering> {
+        Some(self.cmp(&BigInt::new(*other)))
+    }
+}
+
+impl PartialOrd<BigInt> for i64 {
+    fn partial_cmp(&self, other: &BigInt) -> Option<cmp::Ordering> {
+        other.partial_cmp(self).map(cmp::Ordering::reverse)
+    }
+}
+
+impl Minimum for BigInt {
+    fn min<'a>(&'a self, other: &'a Self) -> &'a Self {
+        if self <= other { self } else { other }
+    }
+}
+
+impl ops::Neg for BigInt {
+    type Output = BigInt;
+    fn neg(mut self) -> BigInt {
+        self.sign = -self.sign;
+        self
+    }
+}
+
+impl ops::Neg for &BigInt {
+    type Output = BigInt;
+    fn neg(self) -> BigInt {
+        -self.clone()
+    }
+}
+
+// Add `sign * magnitude` to `a`. This is addition as well as subtraction.
+fn add_signed(a: &BigInt, sign: Sign, magnitude: &BigUint) -> BigInt {
+    if sign == Sign::NoSign {
+        return a.clone();
+    }
+    if a.sign == Sign::NoSign || a.sign == sign {
+        return BigInt::from_biguint(sign, &a.magnitude + magnitude);
+    }
+    // The signs differ, so the larger magnitude wins.
+    match a.magnitude.cmp(magnitude) {
+        cmp::Ordering::Less => BigInt::from_biguint(sign, magnitude - &a.magnitude),
+        cmp::Ordering::Equal => BigInt::zero(),
+        cmp::Ordering::Greater => BigInt::from_biguint(a.sign, &a.magnitude - magnitude),
+    }
+}
+
+impl ops::Add<&BigInt> for &BigInt {
+    type Output = BigInt;
+    fn add(self, rhs: &BigInt) -> BigInt {
+        add_signed(self, rhs.sign, &rhs.magnitude)
+    }
+}
+
+impl ops::Sub<&BigInt> for &BigInt {
+    type Output = BigInt;
+    fn sub(self, rhs: &BigInt) -> BigInt {
+        add_signed(self, -rhs.sign, &rhs.magnitude)
+    }
+}
+
+impl ops::Mul<&BigInt> for &BigInt {
+    type Output = BigInt;
+    fn mul(self, rhs: &BigInt) -> BigInt {
+        BigInt::from_biguint(self.sign * rhs.sign, &self.magnitude * &rhs.magnitude)
+    }
+}
+
+impl ops::Div<&BigInt> for &BigInt {
+    type Output = BigInt;
+    #[inline]
+    fn div(self, rhs: &BigInt) -> BigInt {
+        self.div_rem(rhs).0
+    }
+}
+
+impl ops::Rem<&BigInt> for &BigInt {
+    type Output = BigInt;
+    #[inline]
+    fn rem(self, rhs: &BigInt) -> BigInt {
+        self.div_rem(rhs).1
+    }
+}
+
+// Like for `BigUint`, the variants taking their arguments by value go through the references.
+forward_binop_by_value!(BigInt, impl Add, add);
+forward_binop_by_value!(BigInt, impl Sub, sub);
+forward_binop_by_value!(BigInt, impl Mul, mul);
+forward_binop_by_value!(BigInt, impl Div, div);
+forward_binop_by_value!(BigInt, impl Rem, rem);
+forward_binop_by_value!(BigInt, impl BitAnd, bitand);
+forward_binop_by_value!(BigInt, impl BitOr, bitor);
+forward_binop_by_value!(BigInt, impl BitXor, bitxor);
+
+// The bitwise operations act as if the numbers were written in two's complement with infinitely
+// many digits, like the primitive integers, just wider: A non-negative number continues with 0s
+// forever, a negative one with 1s, and `-m` is `!(m - 1)`, or equivalently `!m + 1`. We write both
+// operands in as many digits as needed for both of them plus one, so that the digit on top only
+// consists of copies of the sign bit, and then apply `op` to the digits and to the infinite rest.
+fn to_twos_complement(n: &BigInt, len: usize) -> Vec<Limb> {
+    let mut digits = n.magnitude.as_limbs().to_vec();
+    digits.resize(len, 0);
+    if n.is_negative() {
+        for digit in digits.iter_mut() {
+            *digit = !*digit;
+        }
+        add_slices_in_place(&mut digits, &[1]);
+    }
+    digits
+}
+
+fn bitwise<F: Fn(Limb, Limb) -> Limb>(a: &BigInt, b: &BigInt, op: F) -> BigInt {
+    let len = cmp::max(a.magnitude.as_limbs().len(), b.magnitude.as_limbs().len()) + 1;
+    let rest = |n: &BigInt| if n.is_negative() { Limb::MAX } else { 0 };
+    let negative = op(rest(a), rest(b)) != 0;
+    let mut digits: Vec<Limb> = to_twos_complement(a, len).into_iter().zip(to_twos_complement(b, len))
+        .map(|(a, b)| op(a, b)).collect();
+    if !negative {
+        return BigInt::from(digits.into_iter().collect::<BigUint>());
+    }
+    // Turn the digits back into a magnitude. The result is negative, so it is not 0, and adding the
+    // 1 cannot carry out of the top.
+    for digit in digits.iter_mut() {
+        *digit = !*digit;
+    }
+    add_slices_in_place(&mut digits, &[1]);
+    BigInt::from_biguint(Sign::Minus, digits.into_iter().collect())
+}
+
+impl ops::BitAnd<&BigInt> for &BigInt {
+    type Output = BigInt;
+    fn bitand(self, rhs: &BigInt) -> BigInt {
+        bitwise(self, rhs, |a, b| a & b)
+    }
+}
+
+impl ops::BitOr<&BigInt> for &BigInt {
+    type Output = BigInt;
+    fn bitor(self, rhs: &BigInt) -> BigInt {
+        bitwise(self, rhs, |a, b| a | b)
+    }
+}
+
+impl ops::BitXor<&BigInt> for &BigInt {
+    type Output = BigInt;
+    fn bitxor(self, rhs: &BigInt) -> BigInt {
+        bitwise(self, rhs, |a, b| a ^ b)
+    }
+}
+
+/// In two's complement, `!x` is `-x - 1`.
+impl ops::Not for &BigInt {
+    type Output = BigInt;
+    fn not(self) -> BigInt {
+        -self - BigInt::one()
+    }
+}
+
+impl ops::Not for BigInt {
+    type Output = BigInt;
+    fn not(self) -> BigInt {
+        !&self
+    }
+}
+
+impl iter::Sum for BigInt {
+    fn sum<I: Iterator<Item = BigInt>>(iter: I) -> Self {
+        iter.fold(BigInt::zero(), |acc, x| acc + x)
+    }
+}
+
+impl<'a> iter::Sum<&'a BigInt> for BigInt {
+    fn sum<I: Iterator<Item = &'a BigInt>>(iter: I) -> Self {
+        iter.fold(BigInt::zero(), |acc, x| acc + x)
+    }
+}
+
+impl iter::Product for BigInt {
+    fn product<I: Iterator<Item = BigInt>>(iter: I) -> Self {
+        iter.fold(BigInt::one(), |acc, x| acc * x)
+    }
+}
+
+impl<'a> iter::Product<&'a BigInt> for BigInt {
+    fn product<I: Iterator<Item = &'a BigInt>>(iter: I) -> Self {
+        iter.fold(BigInt::one(), |acc, x| acc * x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use std::prelude::v1::*;
+    use std::convert::TryFrom;
+    use bigint::BigUint;
+    use locale::{DE_DE, EN_US};
+    use super::{BigInt, Sign};
+
+    // A few numbers around 0 and around the size of a digit, together with their values as `i128`.
+    fn samples() -> Vec<(BigInt, i128)> {
+        let values = [0, 1, -1, 2, -7, 1 << 63, -(1 << 64), (1 << 64) + 5, -(1 << 100), i64::MIN as i128];
+        values.iter().map(|&v| (v.to_string().parse().unwrap(), v)).collect()
+    }
+
+    #[test]
+    fn test_construct() {
+        assert_eq!(BigInt::new(-5).into_parts(), (Sign::Minus, BigUint::new(5)));
+        assert_eq!(BigInt::new(0).sign(), Sign::NoSign);
+        assert_eq!(BigInt::new(i64::MIN).magnitude(), &BigUint::new(1 << 63));
+        assert_eq!(BigInt::from_biguint(Sign::Minus, BigUint::zero()), BigInt::zero());
+        assert_eq!(BigInt::from_biguint(Sign::NoSign, BigUint::new(3)), BigInt::zero());
+        assert_eq!(BigInt::from(BigUint::new(3)), BigInt::new(3));
+        assert_eq!(BigInt::new(-3).to_biguint(), None);
+        assert_eq!(BigInt::new(-3).abs().to_biguint(), Some(BigUint::new(3)));
+        assert_eq!(BigInt::new(-2).pow(3), BigInt::new(-8));
+        assert_eq!(BigInt::new(-2).pow(4), BigInt::new(16));
+        assert!(-BigInt::zero() == BigInt::zero());
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        assert_eq!("-12_345".parse::<BigInt>(), Ok(BigInt::new(-12345)));
+        assert_eq!(" +7 ".parse::<BigInt>(), Ok(BigInt::new(7)));
+        assert_eq!("-0".parse::<BigInt>(), Ok(BigInt::zero()));
+        assert_eq!(BigInt::from_str_radix("-ff", 16), Ok(BigInt::new(-255)));
+        let e = "--1".parse::<BigInt>().unwrap_err();
+        assert_eq!((e.offset(), e.snippet()), (1, "-"));
+        assert_eq!("-".parse::<BigInt>().unwrap_err().offset(), 1);
+        assert_eq!(BigInt::new(-42).to_string(), "-42");
+        assert_eq!(format!("{:>5}|{:+}|{:05}", BigInt::new(-42), BigInt::new(42), BigInt::new(-42)), "  -42|+42|-0042");
+        for (n, v) in samples() {
+            assert_eq!(n.to_string(), v.to_string());
+        }
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        for (a, x) in samples() {
+            for (b, y) in samples() {
+                let check = |n: BigInt, v: i128| assert_eq!(n.to_string(), v.to_string(), "{} and {}", x, y);
+                check(&a + &b, x + y);
+                check(&a - &b, x - y);
+                if x.abs() < 1 << 63 && y.abs() < 1 << 63 {
+                    check(&a * &b, x * y);
+                }
+                if y != 0 {
+                    check(&a / &b, x / y);
+                    check(&a % &b, x % y);
+                }
+                assert_eq!(a.cmp(&b), x.cmp(&y));
+            }
+        }
+        let sum: BigInt = (-3..5).map(BigInt::new).sum();
+        assert_eq!(sum, BigInt::new(4));
+        let product: BigInt = (-3..0).map(BigInt::new).product();
+        assert_eq!(product, BigInt::new(-6));
+        let v: Vec<BigInt> = (-3..0).map(BigInt::new).collect();
+        assert_eq!(v.iter().sum::<BigInt>(), BigInt::new(-6));
+        assert_eq!(v.iter().product::<BigInt>(), BigInt::new(-6));
+    }
+
+    #[test]
+    fn test_from_biguint_functions() {
+        assert_eq!(BigInt::factorial(5), BigInt::new(120));
+        assert_eq!(BigInt::binomial(10, 3), BigInt::new(120));
+        assert_eq!(BigInt::fibonacci(10), BigInt::new(55));
+        assert_eq!(BigInt::new(-12).gcd(&BigInt::new(18)), BigInt::new(6));
+        assert!(BigInt::new(-12).is_even() && BigInt::new(-7).is_odd());
+        assert_eq!(BigInt::new(99).isqrt(), BigInt::new(9));
+        assert_eq!(BigInt::new(-30).nth_root(3), BigInt::new(-3));
+        assert_eq!(BigInt::new(30).nth_root(4), BigInt::new(2));
+        assert_eq!(BigInt::new(-5).abs_diff(&BigInt::new(3)), BigUint::new(8));
+        assert_eq!(BigInt::new(-7).checked_div(&BigInt::new(2)), Some(BigInt::new(-3)));
+        assert_eq!(BigInt::new(-7).checked_rem(&BigInt::new(2)), Some(BigInt::new(-1)));
+        assert_eq!(BigInt::new(-7).checked_div(&BigInt::zero()), None);
+        assert_eq!(BigInt::new(-7).checked_rem(&BigInt::zero()), None);
+        for (n, v) in samples() {
+            assert_eq!(n.to_f64(), v as f64);
+            assert_eq!(BigInt::try_from(v as f64).unwrap().to_f64(), v as f64);
+        }
+        assert_eq!(BigInt::try_from(-2.75), Ok(BigInt::new(-2)));
+        assert_eq!(BigInt::try_from(-0.5), Ok(BigInt::zero()));
+        assert_eq!(BigInt::try_from(f64::NEG_INFINITY).unwrap_err().to_string(), "cannot convert -inf to BigInt");
+        assert_eq!(BigInt::new(-1234567).format_localized(&EN_US), "-1,234,567");
+        assert_eq!(BigInt::new(-1234567).format_localized(&DE_DE), "-1.234.567");
+        assert_eq!(BigInt::new(123).format_localized(&DE_DE), "123");
+    }
+
+    #[test]
+    #[should_panic(expected = "Even root of a negative number")]
+    fn test_even_root_of_negative() {
+        BigInt::new(-16).nth_root(4);
+    }
+
+    #[test]
+    fn test_compare_primitive() {
+        for (a, x) in samples() {
+            for &y in [0u64, 1, 2, 7, 1 << 63, u64::MAX].iter() {
+                assert_eq!(a == y, x == y as i128, "{} and {}", x, y);
+                assert_eq!(a.partial_cmp(&y), Some(x.cmp(&(y as i128))), "{} and {}", x, y);
+                assert_eq!(y.partial_cmp(&a), Some((y as i128).cmp(&x)), "{} and {}", x, y);
+                assert_eq!(y == a, a == y);
+            }
+            for &y in [0i64, 1, -1, -7, i64::MIN, i64::MAX].iter() {
+                assert_eq!(a == y, x == y as i128, "{} and {}", x, y);
+                assert_eq!(a.partial_cmp(&y), Some(x.cmp(&(y as i128))), "{} and {}", x, y);
+                assert_eq!(y.partial_cmp(&a), Some((y as i128).cmp(&x)), "{} and {}", x, y);
+                assert_eq!(y == a, a == y);
+            }
+        }
+        // Counting down, without a `BigInt` for the bound.
+        let mut n = BigInt::new(5);
+        while n > 1u64 {
+            n = n - BigInt::one();
+        }
+        assert!(n == 1i64 && n < 2u64 && -1i64 < n);
+    }
+
+    #[test]
+    fn test_bitwise() {
+        // Numbers right at the sign boundaries of the digits, where the two's complement of a
+        // negative number needs one more digit than its magnitude, or one less.
+        let mut values = vec![-(1i128 << 126), (1 << 126) - 1];
+        for &v in [0i128, 1 << 63, 1 << 64, 1 << 65, 1 << 100, 0x1234_5678_9abc_def0_1122_3344].iter() {
+            for &delta in [-1, 0, 1].iter() {
+                values.push(v + delta);
+                values.push(-(v + delta));
+            }
+        }
+        let big = |v: i128| v.to_string().parse::<BigInt>().unwrap();
+        for &x in values.iter() {
+            let a = big(x);
+            assert_eq!(!&a, big(!x), "!{}", x);
+            for &y in values.iter() {
+                let b = big(y);
+                assert_eq!(&a & &b, big(x & y), "{} & {}", x, y);
+                assert_eq!(&a | &b, big(x | y), "{} | {}", x, y);
+                assert_eq!(&a ^ &b, big(x ^ y), "{} ^ {}", x, y);
+            }
+        }
+        let minus_one = BigInt::new(-1);
+        let n = "123456789012345678901234567890".parse::<BigInt>().unwrap();
+        assert_eq!(&n & &minus_one, n);
+        assert_eq!(&n | &minus_one, minus_one);
+        assert_eq!(&n ^ &minus_one, !n.clone());
+        assert_eq!(!minus_one, BigInt::zero());
+        assert_eq!(-n.clone() & n.clone(), BigInt::new(2));
+        assert_eq!(!BigInt::zero(), BigInt::new(-1));
+    }
+}
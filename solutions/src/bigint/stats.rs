@@ -0,0 +1,64 @@
+// Reductions over a slice of numbers, for processing data sets. They all take the numbers by
+// reference: Nothing is cloned, only the results are new numbers.
+
+use super::BigInt;
+
+/// Add up all the numbers. The sum of no numbers is 0.
+pub fn sum(v: &[BigInt]) -> BigInt {
+    v.iter().sum()
+}
+
+/// Return a pointer to the largest number, the last one if there are several (like
+/// `Iterator::max`).
+pub fn max(v: &[BigInt]) -> Option<&BigInt> {
+    v.iter().max()
+}
+
+/// The mean of the numbers, rounded down (towards negative infinity, not towards 0). There is no
+/// mean of no numbers.
+pub fn mean_floor(v: &[BigInt]) -> Option<BigInt> {
+    if v.is_empty() {
+        return None;
+    }
+    let (quotient, rem) = sum(v).div_rem(&BigInt::new(v.len() as i64));
+    // The remainder has the sign of the sum, so a negative sum was rounded up.
+    Some(if rem.is_negative() { quotient - BigInt::one() } else { quotient })
+}
+
+/// The number of decimal digits of all the numbers together, without the signs. 0 has one digit.
+pub fn total_digits(v: &[BigInt]) -> u64 {
+    v.iter().map(|n| if n.is_zero() { 1 } else { n.magnitude().ilog10() + 1 }).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use std::prelude::v1::*;
+    use bigint::BigInt;
+    use super::{sum, max, mean_floor, total_digits};
+
+    fn numbers(v: &[&str]) -> Vec<BigInt> {
+        v.iter().map(|s| s.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_stats() {
+        let v = numbers(&["100000000000000000000", "-3", "0", "7"]);
+        assert_eq!(sum(&v), "100000000000000000004".parse::<BigInt>().unwrap());
+        assert_eq!(max(&v), Some(&v[0]));
+        assert_eq!(mean_floor(&v), Some("25000000000000000001".parse().unwrap()));
+        assert_eq!(total_digits(&v), 21 + 1 + 1 + 1);
+
+        assert_eq!(sum(&[]), BigInt::zero());
+        assert_eq!(max(&[]), None);
+        assert_eq!(mean_floor(&[]), None);
+        assert_eq!(total_digits(&[]), 0);
+    }
+
+    #[test]
+    fn test_mean_floor() {
+        assert_eq!(mean_floor(&numbers(&["-7", "0"])), Some(BigInt::new(-4)));
+        assert_eq!(mean_floor(&numbers(&["7", "0"])), Some(BigInt::new(3)));
+        assert_eq!(mean_floor(&numbers(&["-8", "0"])), Some(BigInt::new(-4)));
+    }
+}
@@ -0,0 +1,309 @@
+// The storage of the digits of a `BigUint`. Most numbers are small, so we keep up to two digits
+// inline, and only allocate a vector for longer numbers. Through `Deref`, this behaves like a slice;
+// on top of that it offers the parts of the `Vec` API that the algorithms need.
+
+#[cfg(not(feature = "std"))]
+use std::prelude::v1::*;
+use std::cmp;
+use std::fmt;
+use std::mem;
+use std::ops;
+
+// The type of a single digit ("limb"). Multiplying two of them needs a type of twice the width,
+// which is slow on targets that can only multiply 32-bit numbers in hardware; there, the
+// `u32-limbs` feature makes the digits half as wide.
+#[cfg(not(feature = "u32-limbs"))]
+pub type Limb = u64;
+#[cfg(not(feature = "u32-limbs"))]
+pub type DoubleLimb = u128;
+#[cfg(feature = "u32-limbs")]
+pub type Limb = u32;
+#[cfg(feature = "u32-limbs")]
+pub type DoubleLimb = u64;
+pub const LIMB_BITS: u32 = Limb::BITS;
+
+// Convert `x` to a single limb, if it fits.
+#[cfg(not(feature = "u32-limbs"))]
+pub fn small_limb(x: u64) -> Option<Limb> {
+    Some(x)
+}
+#[cfg(feature = "u32-limbs")]
+pub fn small_limb(x: u64) -> Option<Limb> {
+    if x >> LIMB_BITS == 0 { Some(x as Limb) } else { None }
+}
+
+// Widen a limb to a `u64`, which it always fits into.
+#[cfg(not(feature = "u32-limbs"))]
+pub fn limb_to_u64(x: Limb) -> u64 {
+    x
+}
+#[cfg(feature = "u32-limbs")]
+pub fn limb_to_u64(x: Limb) -> u64 {
+    x as u64
+}
+
+// How many digits we store without allocating. It takes (at most) two of them to hold a `u64`.
+const INLINE_CAPACITY: usize = 2;
+
+#[derive(Clone)]
+pub enum Digits {
+    Inline { len: usize, buf: [Limb; INLINE_CAPACITY] },
+    Heap(Vec<Limb>),
+}
+
+impl Digits {
+    pub const fn new() -> Self {
+        Digits::Inline { len: 0, buf: [0; INLINE_CAPACITY] }
+    }
+
+    /// Create storage holding the digits of `x`, without trailing zeros.
+    pub const fn from_u64(x: u64) -> Self {
+        let mut buf = [0; INLINE_CAPACITY];
+        let mut len = 0;
+        let mut rest = x;
+        while rest != 0 {
+            buf[len] = rest as Limb;
+            // With 64-bit limbs, shifting by `LIMB_BITS` would overflow; then nothing is left anyway.
+            rest = match rest.checked_shr(LIMB_BITS) {
+                Some(rest) => rest,
+                None => 0,
+            };
+            len += 1;
+        }
+        Digits::Inline { len, buf }
+    }
+
+    /// Create empty storage, which can hold `capacity` digits before (re)allocating.
+    pub fn with_capacity(capacity: usize) -> Self {
+        if capacity <= INLINE_CAPACITY {
+            Digits::new()
+        } else {
+            Digits::Heap(Vec::with_capacity(capacity))
+        }
+    }
+
+    /// Create storage holding `len` zeros.
+    pub fn zeroed(len: usize) -> Self {
+        if len <= INLINE_CAPACITY {
+            Digits::Inline { len, buf: [0; INLINE_CAPACITY] }
+        } else {
+            Digits::Heap(vec![0; len])
+        }
+    }
+
+    pub fn from_slice(digits: &[Limb]) -> Self {
+        let mut result = Digits::with_capacity(digits.len());
+        result.extend_from_slice(digits);
+        result
+    }
+
+    /// Take over the vector; short ones are moved inline.
+    pub fn from_vec(v: Vec<Limb>) -> Self {
+        if v.len() <= INLINE_CAPACITY {
+            Digits::from_slice(&v)
+        } else {
+            Digits::Heap(v)
+        }
+    }
+
+    /// Take over a vector of 64-bit digits, which have to be split up if our limbs are narrower.
+    #[cfg(not(feature = "u32-limbs"))]
+    pub fn from_u64_vec(v: Vec<u64>) -> Self {
+        Digits::from_vec(v)
+    }
+    #[cfg(feature = "u32-limbs")]
+    pub fn from_u64_vec(v: Vec<u64>) -> Self {
+        Digits::from_vec(v.iter().flat_map(|&x| [x as Limb, (x >> LIMB_BITS) as Limb]).collect())
+    }
+
+    /// Turn the storage into a vector, which allocates for inline digits.
+    pub fn into_vec(self) -> Vec<Limb> {
+        match self {
+            Digits::Inline { len, buf } => buf[..len].to_vec(),
+            Digits::Heap(v) => v,
+        }
+    }
+
+    // Move the digits to the heap, making room for at least `additional` more.
+    fn spill(&mut self, additional: usize) -> &mut Vec<Limb> {
+        if let Digits::Inline { len, buf } = *self {
+            let mut v = Vec::with_capacity(cmp::max(2 * INLINE_CAPACITY, len + additional));
+            v.extend_from_slice(&buf[..len]);
+            *self = Digits::Heap(v);
+        }
+        match *self {
+            Digits::Heap(ref mut v) => v,
+            Digits::Inline { .. } => unreachable!(),
+        }
+    }
+
+    pub fn push(&mut self, digit: Limb) {
+        match *self {
+            Digits::Inline { ref mut len, ref mut buf } if *len < INLINE_CAPACITY => {
+                buf[*len] = digit;
+                *len += 1;
+            }
+            _ => self.spill(1).push(digit),
+        }
+    }
+
+    pub fn pop(&mut self) -> Option<Limb> {
+        match *self {
+            Digits::Inline { ref mut len, ref buf } => {
+                if *len == 0 {
+                    None
+                } else {
+                    *len -= 1;
+                    Some(buf[*len])
+                }
+            }
+            Digits::Heap(ref mut v) => v.pop(),
+        }
+    }
+
+    pub fn truncate(&mut self, new_len: usize) {
+        match *self {
+            Digits::Inline { ref mut len, .. } => *len = cmp::min(*len, new_len),
+            Digits::Heap(ref mut v) => v.truncate(new_len),
+        }
+    }
+
+    pub fn resize(&mut self, new_len: usize, value: Limb) {
+        match *self {
+            Digits::Inline { len, .. } if new_len <= len => self.truncate(new_len),
+            Digits::Inline { ref mut len, ref mut buf } if new_len <= INLINE_CAPACITY => {
+                for digit in buf[*len..new_len].iter_mut() {
+                    *digit = value;
+                }
+                *len = new_len;
+            }
+            _ => {
+                let len = self.len();
+                self.spill(new_len.saturating_sub(len)).resize(new_len, value)
+            }
+        }
+    }
+
+    /// Replace the digits by those in `v`. On the heap, the vectors are swapped, so `v` is left with
+    /// the old allocation (and contents) to be reused.
+    pub fn replace_with_vec(&mut self, v: &mut Vec<Limb>) {
+        match *self {
+            Digits::Heap(ref mut own) => mem::swap(own, v),
+            _ if v.len() <= INLINE_CAPACITY => *self = Digits::from_slice(v),
+            _ => *self = Digits::Heap(mem::take(v)),
+        }
+    }
+
+    pub fn extend_from_slice(&mut self, digits: &[Limb]) {
+        match *self {
+            Digits::Inline { ref mut len, ref mut buf } if *len + digits.len() <= INLINE_CAPACITY => {
+                buf[*len..*len + digits.len()].copy_from_slice(digits);
+                *len += digits.len();
+            }
+            _ => self.spill(digits.len()).extend_from_slice(digits),
+        }
+    }
+}
+
+impl Default for Digits {
+    fn default() -> Self {
+        Digits::new()
+    }
+}
+
+impl ops::Deref for Digits {
+    type Target = [Limb];
+    fn deref(&self) -> &[Limb] {
+        match *self {
+            Digits::Inline { len, ref buf } => &buf[..len],
+            Digits::Heap(ref v) => v,
+        }
+    }
+}
+
+impl ops::DerefMut for Digits {
+    fn deref_mut(&mut self) -> &mut [Limb] {
+        match *self {
+            Digits::Inline { len, ref mut buf } => &mut buf[..len],
+            Digits::Heap(ref mut v) => v,
+        }
+    }
+}
+
+impl PartialEq for Digits {
+    fn eq(&self, other: &Digits) -> bool {
+        **self == **other
+    }
+}
+
+impl Eq for Digits {}
+
+// Print just like the `Vec` we used to have.
+impl fmt::Debug for Digits {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Digits;
+
+    fn is_inline(d: &Digits) -> bool {
+        match *d {
+            Digits::Inline { .. } => true,
+            Digits::Heap(_) => false,
+        }
+    }
+
+    #[test]
+    fn test_push_pop() {
+        let mut d = Digits::new();
+        d.push(1);
+        d.push(2);
+        assert!(is_inline(&d));
+        d.push(3);
+        assert!(!is_inline(&d));
+        assert_eq!(&*d, &[1, 2, 3]);
+        assert_eq!(d.pop(), Some(3));
+        assert_eq!(d.pop(), Some(2));
+        assert_eq!(d.pop(), Some(1));
+        assert_eq!(d.pop(), None);
+        assert!(d.is_empty());
+    }
+
+    #[test]
+    fn test_constructors() {
+        assert!(is_inline(&Digits::from_vec(vec![1, 2])));
+        assert!(!is_inline(&Digits::from_vec(vec![1, 2, 3])));
+        assert_eq!(&*Digits::from_vec(vec![1, 2, 3]), &[1, 2, 3]);
+        assert_eq!(&*Digits::from_slice(&[4]), &[4]);
+        assert_eq!(&*Digits::zeroed(2), &[0, 0]);
+        assert_eq!(&*Digits::zeroed(5), &[0; 5]);
+        assert!(is_inline(&Digits::with_capacity(2)) && !is_inline(&Digits::with_capacity(3)));
+        assert_eq!(Digits::from_slice(&[1, 2]), Digits::from_vec(vec![1, 2]));
+        assert_eq!(format!("{:?}", Digits::from_slice(&[1, 2])), "[1, 2]");
+        assert_eq!(Digits::from_slice(&[1, 2]).into_vec(), vec![1, 2]);
+        assert_eq!(Digits::from_vec(vec![1, 2, 3]).into_vec(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_resize_truncate() {
+        let mut d = Digits::from_slice(&[7]);
+        d.resize(2, 9);
+        assert_eq!(&*d, &[7, 9]);
+        d.resize(4, 0);
+        assert_eq!(&*d, &[7, 9, 0, 0]);
+        d.truncate(1);
+        assert_eq!(&*d, &[7]);
+        d.resize(0, 0);
+        assert!(d.is_empty());
+        let mut d = Digits::from_slice(&[1]);
+        d.extend_from_slice(&[2]);
+        assert!(is_inline(&d));
+        d.extend_from_slice(&[3, 4]);
+        assert_eq!(&*d, &[1, 2, 3, 4]);
+        d[0] = 5;
+        assert_eq!(&*d, &[5, 2, 3, 4]);
+    }
+}
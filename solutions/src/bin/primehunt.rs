@@ -0,0 +1,210 @@
+// Find random primes of a given size, on several threads at once. The workers draw random odd
+// numbers with the top bit set and test them until enough primes have been found; the main thread
+// collects the primes and reports the progress. Ctrl-C stops the search, keeping what was found so
+// far. Every prime is printed in hexadecimal and in base64 (of its big-endian bytes), the two common
+// ways of writing down keys.
+
+extern crate ctrlc;
+extern crate docopt;
+extern crate solutions;
+
+use std::io::{self, Write};
+use std::process;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use solutions::bigint::BigUint;
+
+static USAGE: &str = "
+Usage:
+    primehunt [--threads=<n>] [--seed=<seed>] <bits> <count>
+
+Options:
+    --threads=<n>     Number of worker threads [default: 4].
+    --seed=<seed>     Seed for the random number generator (default: taken from the clock).
+";
+
+// How often the progress is updated.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+// A SplitMix64 generator. It is fast and good enough for finding primes, but *not* suitable for
+// generating actual keys: Its output can be predicted from a few numbers.
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+// Draw an odd number with exactly `bits` bits. `bits` must be at least 2.
+fn random_candidate(bits: u64, rng: &mut Rng) -> BigUint {
+    let limbs = bits.div_ceil(64);
+    let mut digits: Vec<u64> = (0..limbs).map(|_| rng.next_u64()).collect();
+    let top_bits = bits - 64 * (limbs - 1);
+    if top_bits < 64 {
+        *digits.last_mut().unwrap() &= (1 << top_bits) - 1;
+    }
+    let mut n = BigUint::from_vec(digits);
+    n.set_bit(bits - 1, true);
+    n.set_bit(0, true);
+    n
+}
+
+// The big-endian bytes of a number, without leading zeros.
+fn to_bytes_be(n: &BigUint) -> Vec<u8> {
+    let bytes: Vec<u8> = n.iter_msf().flat_map(|d| d.to_be_bytes()).collect();
+    let leading_zeros = bytes.iter().take_while(|&&b| b == 0).count();
+    bytes[leading_zeros..].to_vec()
+}
+
+// Encode bytes in base64, with padding.
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        // Three bytes are four groups of six bits; a shorter chunk has fewer groups, and padding.
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let bits = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[((bits >> (18 - 6 * i)) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+struct Options {
+    bits: u64,
+    count: usize,
+    threads: usize,
+    seed: u64,
+}
+
+fn get_options() -> Options {
+    use docopt::Docopt;
+
+    let args = Docopt::new(USAGE).and_then(|d| d.parse()).unwrap_or_else(|e| e.exit());
+    let parse = |name: &str| -> u64 {
+        args.get_str(name).parse().unwrap_or_else(|_| {
+            println!("{} has to be a non-negative number, not '{}'.", name, args.get_str(name));
+            process::exit(1);
+        })
+    };
+    let bits = parse("<bits>");
+    let count = parse("<count>") as usize;
+    let threads = parse("--threads") as usize;
+    if bits < 2 || threads == 0 {
+        println!("There are no primes with fewer than 2 bits, and we need at least one thread.");
+        process::exit(1);
+    }
+    let seed = if args.get_str("--seed").is_empty() {
+        SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos() as u64).unwrap_or(0)
+    } else {
+        parse("--seed")
+    };
+    Options { bits, count, threads, seed }
+}
+
+pub fn main() {
+    let options = get_options();
+    // Both Ctrl-C and having found enough primes stop the workers through this flag.
+    let stop = Arc::new(AtomicBool::new(false));
+    let cancelled = Arc::new(AtomicBool::new(false));
+    {
+        let (stop, cancelled) = (stop.clone(), cancelled.clone());
+        ctrlc::set_handler(move || {
+            cancelled.store(true, Ordering::SeqCst);
+            stop.store(true, Ordering::SeqCst);
+        }).unwrap_or_else(|e| eprintln!("Cannot handle Ctrl-C, it will stop the program right away: {}", e));
+    }
+    let tested = AtomicUsize::new(0);
+    let start = Instant::now();
+    let mut primes = Vec::with_capacity(options.count);
+
+    thread::scope(|scope| {
+        let (sender, receiver) = channel();
+        for worker in 0..options.threads {
+            let sender = sender.clone();
+            let (stop, tested, bits) = (&*stop, &tested, options.bits);
+            // Every worker gets its own sequence of numbers.
+            let mut rng = Rng(options.seed ^ (worker as u64).wrapping_mul(0xd1b5_4a32_d192_ed03));
+            scope.spawn(move || {
+                while !stop.load(Ordering::Relaxed) {
+                    let candidate = random_candidate(bits, &mut rng);
+                    tested.fetch_add(1, Ordering::Relaxed);
+                    if candidate.is_probable_prime() && sender.send(candidate).is_err() {
+                        return;
+                    }
+                }
+            });
+        }
+        drop(sender);
+
+        while primes.len() < options.count && !stop.load(Ordering::SeqCst) {
+            match receiver.recv_timeout(PROGRESS_INTERVAL) {
+                Ok(prime) => primes.push(prime),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+            eprint!("\rTested {} candidates, found {} of {} primes.", tested.load(Ordering::Relaxed), primes.len(), options.count);
+        }
+        stop.store(true, Ordering::SeqCst);
+    });
+    eprintln!("\rTested {} candidates, found {} of {} primes in {:.1?}.",
+              tested.load(Ordering::Relaxed), primes.len(), options.count, start.elapsed());
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    for prime in primes.iter() {
+        writeln!(out, "{:#x} {}", prime, base64(&to_bytes_be(prime))).unwrap();
+    }
+    if cancelled.load(Ordering::SeqCst) {
+        eprintln!("Cancelled.");
+        process::exit(130);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use solutions::bigint::BigUint;
+    use super::{base64, random_candidate, to_bytes_be, Rng};
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+        assert_eq!(base64(&[0xff, 0xfe]), "//4=");
+    }
+
+    #[test]
+    fn test_bytes() {
+        assert_eq!(to_bytes_be(&BigUint::zero()), Vec::<u8>::new());
+        assert_eq!(to_bytes_be(&BigUint::new(0x1234)), vec![0x12, 0x34]);
+        assert_eq!(to_bytes_be(&BigUint::power_of_2(64)), vec![1, 0, 0, 0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_random_candidate() {
+        let mut rng = Rng(1);
+        for &bits in [2, 3, 63, 64, 65, 128, 1000].iter() {
+            for _ in 0..20 {
+                let n = random_candidate(bits, &mut rng);
+                assert_eq!(n.bit_length(), bits);
+                assert!(n.is_odd());
+            }
+        }
+    }
+}
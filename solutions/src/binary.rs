@@ -0,0 +1,307 @@
+// A small binary format for storing the types of this crate. Every encoded value starts with a
+// header, followed by the payload:
+//
+//     magic "R101" | major version (u8) | minor version (u8) | type tag (u8) | payload length (u64) | payload
+//
+// All integers are little-endian. The version follows the rules of semantic versioning:
+// - A new *minor* version may only add data at the end of a payload (including the payloads of
+//   nested values, which carry their own length for that reason). Readers ignore whatever they do
+//   not know about, so data written by a newer minor version can still be read.
+// - A new *major* version may change anything, and readers refuse data of another major version.
+
+#[cfg(not(feature = "std"))]
+use std::prelude::v1::*;
+use std::error::Error;
+use std::fmt;
+use list::LinkedList;
+
+/// The first bytes of every encoded value.
+pub const MAGIC: [u8; 4] = *b"R101";
+/// The major version of the format written by this version of the crate.
+pub const MAJOR_VERSION: u8 = 1;
+/// The minor version of the format written by this version of the crate.
+pub const MINOR_VERSION: u8 = 0;
+
+// Magic, versions, tag and payload length.
+const HEADER_LEN: usize = 4 + 1 + 1 + 1 + 8;
+
+/// The type tags used in the header. Once assigned, a tag must never be reused for another type.
+pub mod tag {
+    pub const U64: u8 = 1;
+    pub const BIGINT: u8 = 2;
+    pub const LINKED_LIST: u8 = 3;
+    pub const BIGRATIONAL: u8 = 4;
+    pub const BIGDECIMAL: u8 = 5;
+    pub const SIGNED_BIGINT: u8 = 6;
+}
+
+/// A type that can be stored in the binary format.
+pub trait Binary: Sized {
+    /// The tag identifying the type in the header.
+    const TAG: u8;
+
+    /// Append the payload representing `self`.
+    fn encode_payload(&self, out: &mut Vec<u8>);
+
+    /// Read a value from the payload. Data after the part this version knows about is ignored.
+    fn decode_payload(input: &mut Reader) -> Result<Self, DecodeError>;
+}
+
+/// What went wrong while decoding a value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The data does not start with `MAGIC`.
+    BadMagic,
+    /// The data was written by an incompatible version of the format.
+    UnsupportedVersion { major: u8, minor: u8 },
+    /// The data holds a value of another type.
+    WrongType { expected: u8, found: u8 },
+    /// The data ended too early.
+    Truncated,
+    /// The data is not a valid encoding; the message says why.
+    Invalid(&'static str),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DecodeError::BadMagic => write!(f, "not an encoded value (bad magic number)"),
+            DecodeError::UnsupportedVersion { major, minor } =>
+                write!(f, "unsupported format version {}.{} (expected {}.x)", major, minor, MAJOR_VERSION),
+            DecodeError::WrongType { expected, found } =>
+                write!(f, "expected a value with type tag {}, found {}", expected, found),
+            DecodeError::Truncated => write!(f, "unexpected end of data"),
+            DecodeError::Invalid(msg) => write!(f, "invalid data: {}", msg),
+        }
+    }
+}
+
+impl Error for DecodeError {}
+
+/// Encode a value, including the header.
+pub fn encode<T: Binary>(value: &T) -> Vec<u8> {
+    let mut payload = Vec::new();
+    value.encode_payload(&mut payload);
+    let mut out = Vec::with_capacity(HEADER_LEN + payload.len());
+    out.extend_from_slice(&MAGIC);
+    out.push(MAJOR_VERSION);
+    out.push(MINOR_VERSION);
+    out.push(T::TAG);
+    write_u64(&mut out, payload.len() as u64);
+    out.extend_from_slice(&payload);
+    out
+}
+
+/// Decode a value written by `encode`, by this or any other version of the crate with the same
+/// major version of the format.
+pub fn decode<T: Binary>(data: &[u8]) -> Result<T, DecodeError> {
+    let mut input = Reader::new(data);
+    if input.read_bytes(MAGIC.len()).map_err(|_| DecodeError::BadMagic)? != MAGIC {
+        return Err(DecodeError::BadMagic);
+    }
+    let (major, minor) = (input.read_u8()?, input.read_u8()?);
+    if major != MAJOR_VERSION {
+        return Err(DecodeError::UnsupportedVersion { major, minor });
+    }
+    let found = input.read_u8()?;
+    if found != T::TAG {
+        return Err(DecodeError::WrongType { expected: T::TAG, found });
+    }
+    let len = input.read_len()?;
+    let mut payload = Reader::new(input.read_bytes(len)?);
+    if !input.is_empty() {
+        return Err(DecodeError::Invalid("trailing data after the payload"));
+    }
+    T::decode_payload(&mut payload)
+}
+
+/// Append a `u64` to a payload.
+pub fn write_u64(out: &mut Vec<u8>, x: u64) {
+    out.extend_from_slice(&x.to_le_bytes());
+}
+
+/// Append a nested value to a payload, prefixed with its length so that readers can skip data
+/// added by newer versions.
+pub fn write_nested<T: Binary>(out: &mut Vec<u8>, value: &T) {
+    let mut payload = Vec::new();
+    value.encode_payload(&mut payload);
+    write_u64(out, payload.len() as u64);
+    out.extend_from_slice(&payload);
+}
+
+/// Reads the parts of a payload, front to back.
+pub struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Reader { data }
+    }
+
+    /// Check whether everything has been read.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        if len > self.data.len() {
+            return Err(DecodeError::Truncated);
+        }
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(bytes)
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, DecodeError> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    pub fn read_u64(&mut self) -> Result<u64, DecodeError> {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(self.read_bytes(8)?);
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    /// Read a length, which must not exceed what is left of the data. This protects against
+    /// allocating huge amounts of memory for a corrupted length.
+    pub fn read_len(&mut self) -> Result<usize, DecodeError> {
+        let len = self.read_u64()?;
+        if len > self.data.len() as u64 {
+            return Err(DecodeError::Truncated);
+        }
+        Ok(len as usize)
+    }
+
+    /// Read a nested value written by `write_nested`.
+    pub fn read_nested<T: Binary>(&mut self) -> Result<T, DecodeError> {
+        let len = self.read_len()?;
+        T::decode_payload(&mut Reader::new(self.read_bytes(len)?))
+    }
+}
+
+impl Binary for u64 {
+    const TAG: u8 = tag::U64;
+
+    fn encode_payload(&self, out: &mut Vec<u8>) {
+        write_u64(out, *self);
+    }
+
+    fn decode_payload(input: &mut Reader) -> Result<Self, DecodeError> {
+        input.read_u64()
+    }
+}
+
+// A list stores the tag of its elements, their number, and the elements as nested values.
+impl<T: Binary> Binary for LinkedList<T> {
+    const TAG: u8 = tag::LINKED_LIST;
+
+    fn encode_payload(&self, out: &mut Vec<u8>) {
+        out.push(T::TAG);
+        write_u64(out, self.iter().count() as u64);
+        for x in self.iter() {
+            write_nested(out, x);
+        }
+    }
+
+    fn decode_payload(input: &mut Reader) -> Result<Self, DecodeError> {
+        let found = input.read_u8()?;
+        if found != T::TAG {
+            return Err(DecodeError::WrongType { expected: T::TAG, found });
+        }
+        let len = input.read_u64()?;
+        let mut list = LinkedList::new();
+        for _ in 0..len {
+            list.push_back(input.read_nested()?);
+        }
+        Ok(list)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use std::prelude::v1::*;
+    use bigint::{BigInt, BigUint};
+    use list::LinkedList;
+    use super::{encode, decode, DecodeError, tag, HEADER_LEN};
+
+    fn list_of<T>(items: Vec<T>) -> LinkedList<T> {
+        let mut list = LinkedList::new();
+        for x in items {
+            list.push_back(x);
+        }
+        list
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        for n in [BigUint::zero(), BigUint::new(42), BigUint::factorial(100)].iter() {
+            assert_eq!(decode::<BigUint>(&encode(n)), Ok(n.clone()));
+        }
+        assert_eq!(decode::<u64>(&encode(&u64::MAX)), Ok(u64::MAX));
+        for n in [BigInt::zero(), BigInt::new(-42), -BigInt::factorial(30)].iter() {
+            assert_eq!(decode::<BigInt>(&encode(n)), Ok(n.clone()));
+        }
+        let list = list_of(vec![list_of(vec![BigUint::new(1), BigUint::power_of_2(100)]), list_of(vec![])]);
+        let decoded: LinkedList<LinkedList<BigUint>> = decode(&encode(&list)).unwrap();
+        let flat: Vec<Vec<BigUint>> = decoded.iter().map(|l| l.iter().cloned().collect()).collect();
+        assert_eq!(flat, vec![vec![BigUint::new(1), BigUint::power_of_2(100)], vec![]]);
+    }
+
+    #[test]
+    fn test_format_is_stable() {
+        // Changing these bytes breaks reading data written by earlier versions.
+        let mut expected = b"R101\x01\x00\x02".to_vec();
+        expected.extend_from_slice(&[24, 0, 0, 0, 0, 0, 0, 0]);
+        expected.extend_from_slice(&[2, 0, 0, 0, 0, 0, 0, 0]);
+        expected.extend_from_slice(&[5, 0, 0, 0, 0, 0, 0, 0]);
+        expected.extend_from_slice(&[1, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(encode(&(BigUint::power_of_2(64) + BigUint::new(5))), expected);
+    }
+
+    #[test]
+    fn test_newer_minor_version() {
+        // A list of two numbers written by a hypothetical version 1.3, which appends a byte to the
+        // payload of every number and of the list.
+        let mut payload = vec![tag::BIGINT, 2, 0, 0, 0, 0, 0, 0, 0];
+        for &n in [7u8, 9].iter() {
+            payload.extend_from_slice(&[17, 0, 0, 0, 0, 0, 0, 0]);
+            payload.extend_from_slice(&[1, 0, 0, 0, 0, 0, 0, 0, n, 0, 0, 0, 0, 0, 0, 0, 0xff]);
+        }
+        payload.push(0xee);
+        let mut data = b"R101\x01\x03".to_vec();
+        data.push(tag::LINKED_LIST);
+        data.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+        data.extend_from_slice(&payload);
+        let list: LinkedList<BigUint> = decode(&data).unwrap();
+        assert_eq!(list.iter().cloned().collect::<Vec<_>>(), vec![BigUint::new(7), BigUint::new(9)]);
+        // A new major version is rejected.
+        data[4] = 2;
+        assert_eq!(decode::<LinkedList<BigUint>>(&data).err(), Some(DecodeError::UnsupportedVersion { major: 2, minor: 3 }));
+    }
+
+    #[test]
+    fn test_errors() {
+        let data = encode(&BigUint::new(3));
+        assert_eq!(decode::<BigUint>(b"R10").err(), Some(DecodeError::BadMagic));
+        assert_eq!(decode::<BigUint>(b"R102\x01\x00\x02").err(), Some(DecodeError::BadMagic));
+        assert_eq!(decode::<u64>(&data).err(), Some(DecodeError::WrongType { expected: tag::U64, found: tag::BIGINT }));
+        assert_eq!(decode::<LinkedList<u64>>(&encode(&list_of(vec![BigUint::new(1)]))).err(),
+                   Some(DecodeError::WrongType { expected: tag::U64, found: tag::BIGINT }));
+        for len in 4..data.len() {
+            assert_eq!(decode::<BigUint>(&data[..len]).err(), Some(DecodeError::Truncated));
+        }
+        let mut zero = data.clone();
+        zero[HEADER_LEN + 8] = 0;
+        assert_eq!(decode::<BigUint>(&zero).err(), Some(DecodeError::Invalid("leading zero digit")));
+        let mut long = data.clone();
+        long.push(0);
+        assert_eq!(decode::<BigUint>(&long).err(), Some(DecodeError::Invalid("trailing data after the payload")));
+        let mut negative_zero = encode(&BigInt::zero());
+        negative_zero[HEADER_LEN] = 1;
+        assert_eq!(decode::<BigInt>(&negative_zero).err(), Some(DecodeError::Invalid("negative zero")));
+        assert_eq!(DecodeError::UnsupportedVersion { major: 2, minor: 0 }.to_string(),
+                   "unsupported format version 2.0 (expected 1.x)");
+    }
+}
@@ -1,23 +1,155 @@
-use std::rc::Rc;
+use std::any::Any;
+use std::rc::{Rc, Weak};
 use std::cell::RefCell;
+use std::sync::{Arc, Mutex};
 
+/// Identifies a registered callback, so that it can be removed again.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct CallbackHandle(u64);
+
+// The closures are wrapped to return `None` when they did not actually run, which is what a
+// one-shot callback does once it is used up.
+type Callback<E, R> = Rc<RefCell<dyn FnMut(&E) -> Option<R>>>;
+type SyncCallback<E, R> = Arc<Mutex<dyn FnMut(&E) -> Option<R> + Send>>;
+
+#[derive(Clone)]
+struct Entry<C> {
+    handle: CallbackHandle,
+    callback: C,
+    // Whether to remove the callback after it ran in a `call`.
+    once: bool,
+    priority: i32,
+}
+
+// The bookkeeping that both registries share. `C` is the pointer to a closure.
 #[derive(Clone)]
-pub struct Callbacks {
-    callbacks: Vec<Rc<RefCell<FnMut(i32)>>>,
+struct Registry<C> {
+    // Sorted by decreasing priority, and by registration within the same priority.
+    entries: Vec<Entry<C>>,
+    // Handles are never reused, so a stale handle cannot remove someone else's callback.
+    next_handle: u64,
+}
+
+impl<C> Registry<C> {
+    fn new() -> Self {
+        Registry { entries: Vec::new(), next_handle: 0 }
+    }
+
+    fn add(&mut self, callback: C, once: bool, priority: i32) -> CallbackHandle {
+        let handle = CallbackHandle(self.next_handle);
+        self.next_handle += 1;
+        // Behind all the callbacks of the same or a higher priority.
+        let idx = self.entries.partition_point(|entry| entry.priority >= priority);
+        self.entries.insert(idx, Entry { handle, callback, once, priority });
+        handle
+    }
+
+    fn unregister(&mut self, handle: CallbackHandle) -> bool {
+        match self.entries.iter().position(|entry| entry.handle == handle) {
+            Some(idx) => {
+                self.entries.remove(idx);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Remove the one-shot callbacks that ran.
+    fn remove_all(&mut self, handles: &[CallbackHandle]) {
+        if !handles.is_empty() {
+            self.entries.retain(|entry| !handles.contains(&entry.handle));
+        }
+    }
+}
+
+/// A list of callbacks that are all called with the same event, of type `E`. They get the event by
+/// reference, so it can be anything from a number to a big struct. They can also return a result
+/// of type `R`, which makes the registry usable for queries, too.
+pub struct Callbacks<E = i32, R = ()> {
+    registry: Registry<Callback<E, R>>,
+    // The subscribers of the callbacks registered with `register_weak`.
+    subscribers: Vec<(CallbackHandle, Weak<dyn Any>)>,
 }
 
-impl Callbacks {
+// Not derived, since that would require `E: Clone` and `R: Clone`. We only clone the pointers to the closures.
+impl<E, R> Clone for Callbacks<E, R> {
+    fn clone(&self) -> Self {
+        Callbacks { registry: self.registry.clone(), subscribers: self.subscribers.clone() }
+    }
+}
+
+impl<E: 'static, R: 'static> Callbacks<E, R> {
     pub fn new() -> Self {
-        Callbacks { callbacks: Vec::new() }                      /*@*/
+        Callbacks { registry: Registry::new(), subscribers: Vec::new() } /*@*/
+    }
+
+    pub fn register<F: FnMut(&E) -> R + 'static>(&mut self, callback: F) -> CallbackHandle {
+        self.register_with_priority(0, callback)
+    }
+
+    /// Register a callback that runs before all the ones with a lower priority, and after those
+    /// with a higher one. Callbacks of the same priority run in the order they were registered.
+    /// `register` uses priority 0.
+    pub fn register_with_priority<F: FnMut(&E) -> R + 'static>(&mut self, priority: i32, mut callback: F) -> CallbackHandle {
+        let cell = Rc::new(RefCell::new(move |event: &E| Some(callback(event))));
+        self.registry.add(cell, false, priority)
     }
 
-    pub fn register<F: FnMut(i32)+'static>(&mut self, callback: F) {
-        let cell = Rc::new(RefCell::new(callback));
-        self.callbacks.push(cell);                                  /*@*/
+    /// Register a callback for just the next `call`, after which it is removed. The closure is
+    /// only ever run once, even if a clone of the registry also calls it.
+    pub fn register_once<F: FnOnce(&E) -> R + 'static>(&mut self, callback: F) -> CallbackHandle {
+        let mut callback = Some(callback);
+        let cell = Rc::new(RefCell::new(move |event: &E| callback.take().map(|callback| callback(event))));
+        self.registry.add(cell, true, 0)
     }
 
-    pub fn call(&mut self, val: i32) {
-        for callback in self.callbacks.iter() {
+    /// Register a callback on behalf of `subscriber`, which is only held weakly: once the subscriber
+    /// is dropped, the callback goes away, too, without anyone having to unregister it. A callback
+    /// that held on to the subscriber through an `Rc` would keep it alive instead, as long as the
+    /// registry exists. The callback gets mutable access to the subscriber.
+    pub fn register_weak<T, F>(&mut self, subscriber: &Rc<RefCell<T>>, mut callback: F) -> CallbackHandle
+        where T: 'static, F: FnMut(&mut T, &E) -> R + 'static
+    {
+        let weak = Rc::downgrade(subscriber);
+        let cell = Rc::new(RefCell::new(move |event: &E| {
+            weak.upgrade().map(|subscriber| callback(&mut subscriber.borrow_mut(), event))
+        }));
+        let handle = self.registry.add(cell, false, 0);
+        let subscriber: Weak<RefCell<T>> = Rc::downgrade(subscriber);
+        self.subscribers.push((handle, subscriber));
+        handle
+    }
+
+    /// Remove the callback registered under `handle`, and return whether it was still there. A
+    /// clone of the registry shares the callbacks, but not the registrations: removing a callback
+    /// here leaves it in the clone.
+    pub fn unregister(&mut self, handle: CallbackHandle) -> bool {
+        self.subscribers.retain(|&(h, _)| h != handle);
+        self.registry.unregister(handle)
+    }
+
+    // Remove the callbacks whose subscribers are gone.
+    fn remove_orphans(&mut self) {
+        let mut orphans = Vec::new();
+        self.subscribers.retain(|&(handle, ref subscriber)| {
+            let alive = subscriber.strong_count() > 0;
+            if !alive {
+                orphans.push(handle);
+            }
+            alive
+        });
+        self.registry.remove_all(&orphans);
+    }
+
+    // Run the callbacks in order, handing every result to `consume`, until that returns `true`.
+    // One-shot callbacks that did not get to run stay for the next call.
+    fn dispatch<F: FnMut(R) -> bool>(&mut self, event: &E, mut consume: F) {
+        self.remove_orphans();
+        let mut ran_once = Vec::new();
+        for entry in self.registry.entries.iter() {
+            if entry.once {
+                ran_once.push(entry.handle);
+            }
             // We have to *explicitly* borrow the contents of a `RefCell`.
             //@ At run-time, the cell will keep track of the number of outstanding shared and mutable borrows,
             //@ and panic if the rules are violated. Since this function is the only one that borrow the
@@ -33,10 +165,325 @@ impl Callbacks {
             //@ This is called reentrancy. It would imply that we borrow the closure a second time, and
             //@ panic at run-time. I hope this also makes it clear that there's absolutely no hope of Rust
             //@ performing these checks statically, at compile-time: It would have to detect reentrancy!
-            let mut closure = callback.borrow_mut();
+            let mut closure = entry.callback.borrow_mut();
             // Unfortunately, Rust's auto-dereference of pointers is not clever enough here. We thus have to explicitly
             // dereference the smart pointer and obtain a mutable borrow of the target.
-            (&mut *closure)(val);
+            if let Some(result) = (&mut *closure)(event) {
+                if consume(result) {
+                    break;
+                }
+            }
+        }
+        self.registry.remove_all(&ran_once);
+    }
+
+    pub fn call(&mut self, event: &E) {
+        self.dispatch(event, |_| false);
+    }
+
+    /// Call all the callbacks, and return their results in the order they ran.
+    pub fn call_collect(&mut self, event: &E) -> Vec<R> {
+        let mut results = Vec::new();
+        self.dispatch(event, |result| {
+            results.push(result);
+            false
+        });
+        results
+    }
+
+    /// Call the callbacks until one of them returns a result for which `pred` holds, and return
+    /// that result. The remaining callbacks are not called at all. For example, with `R = bool`,
+    /// `call_until(event, |&veto| veto).is_some()` asks whether any callback vetoes `event`.
+    pub fn call_until<P: FnMut(&R) -> bool>(&mut self, event: &E, mut pred: P) -> Option<R> {
+        let mut found = None;
+        self.dispatch(event, |result| {
+            if pred(&result) {
+                found = Some(result);
+                true
+            } else {
+                false
+            }
+        });
+        found
+    }
+}
+
+/// The thread-safe counterpart of `Callbacks`: the closures have to be `Send`, and all methods
+/// take `&self`, so that a registry can be shared between threads and events can come from any of
+/// them. Unlike for `Callbacks`, clones share the registrations, too.
+///
+/// Callbacks may register and unregister callbacks while they run; the changes take effect with the
+/// next call. A callback that causes itself to be called again deadlocks, where `Callbacks` would
+/// panic.
+pub struct SyncCallbacks<E = i32, R = ()> {
+    registry: Arc<Mutex<Registry<SyncCallback<E, R>>>>,
+}
+
+impl<E, R> Clone for SyncCallbacks<E, R> {
+    fn clone(&self) -> Self {
+        SyncCallbacks { registry: self.registry.clone() }
+    }
+}
+
+impl<E: 'static, R: 'static> Default for SyncCallbacks<E, R> {
+    fn default() -> Self {
+        SyncCallbacks::new()
+    }
+}
+
+impl<E: 'static, R: 'static> SyncCallbacks<E, R> {
+    pub fn new() -> Self {
+        SyncCallbacks { registry: Arc::new(Mutex::new(Registry::new())) }
+    }
+
+    pub fn register<F: FnMut(&E) -> R + Send + 'static>(&self, callback: F) -> CallbackHandle {
+        self.register_with_priority(0, callback)
+    }
+
+    /// Register a callback with a priority, see `Callbacks::register_with_priority`.
+    pub fn register_with_priority<F: FnMut(&E) -> R + Send + 'static>(&self, priority: i32, mut callback: F) -> CallbackHandle {
+        let cell = Arc::new(Mutex::new(move |event: &E| Some(callback(event))));
+        self.registry.lock().unwrap().add(cell, false, priority)
+    }
+
+    /// Register a callback for just the next call. Even if several threads call the registry at
+    /// the same time, only one of them runs the closure.
+    pub fn register_once<F: FnOnce(&E) -> R + Send + 'static>(&self, callback: F) -> CallbackHandle {
+        let mut callback = Some(callback);
+        let cell = Arc::new(Mutex::new(move |event: &E| callback.take().map(|callback| callback(event))));
+        self.registry.lock().unwrap().add(cell, true, 0)
+    }
+
+    /// Remove the callback registered under `handle`, in all the clones of the registry, and
+    /// return whether it was still there.
+    pub fn unregister(&self, handle: CallbackHandle) -> bool {
+        self.registry.lock().unwrap().unregister(handle)
+    }
+
+    // Like `Callbacks::dispatch`. We work on a copy of the list, so that the registry is not locked
+    // while the callbacks run: other threads can call it at the same time.
+    fn dispatch<F: FnMut(R) -> bool>(&self, event: &E, mut consume: F) {
+        let entries = self.registry.lock().unwrap().entries.clone();
+        let mut ran_once = Vec::new();
+        for entry in entries.iter() {
+            if entry.once {
+                ran_once.push(entry.handle);
+            }
+            let result = (entry.callback.lock().unwrap())(event);
+            if let Some(result) = result {
+                if consume(result) {
+                    break;
+                }
+            }
+        }
+        self.registry.lock().unwrap().remove_all(&ran_once);
+    }
+
+    pub fn call(&self, event: &E) {
+        self.dispatch(event, |_| false);
+    }
+
+    /// Call all the callbacks, and return their results in the order they ran.
+    pub fn call_collect(&self, event: &E) -> Vec<R> {
+        let mut results = Vec::new();
+        self.dispatch(event, |result| {
+            results.push(result);
+            false
+        });
+        results
+    }
+
+    /// Call the callbacks until one of them returns a result for which `pred` holds, see
+    /// `Callbacks::call_until`.
+    pub fn call_until<P: FnMut(&R) -> bool>(&self, event: &E, mut pred: P) -> Option<R> {
+        let mut found = None;
+        self.dispatch(event, |result| {
+            if pred(&result) {
+                found = Some(result);
+                true
+            } else {
+                false
+            }
+        });
+        found
+    }
+}
+
+// With the `async` feature, the callbacks can return futures, which the registry then awaits.
+// This is still the single-threaded registry: the futures do not have to be `Send`.
+#[cfg(feature = "async")]
+pub use self::async_callbacks::{AsyncCallbacks, CallJoin, CallSequential};
+
+#[cfg(feature = "async")]
+mod async_callbacks {
+    use std::cell::RefCell;
+    use std::future::Future;
+    use std::mem;
+    use std::pin::Pin;
+    use std::rc::Rc;
+    use std::task::{Context, Poll};
+    use super::{CallbackHandle, Registry};
+
+    type BoxFuture<R> = Pin<Box<dyn Future<Output = R>>>;
+    type AsyncCallback<E, R> = Rc<RefCell<dyn FnMut(&E) -> Option<BoxFuture<R>>>>;
+
+    /// Like `Callbacks`, for callbacks that return a future of their result. The future cannot
+    /// borrow the event, so a callback has to copy what it needs from it.
+    pub struct AsyncCallbacks<E = i32, R = ()> {
+        registry: Registry<AsyncCallback<E, R>>,
+    }
+
+    impl<E, R> Clone for AsyncCallbacks<E, R> {
+        fn clone(&self) -> Self {
+            AsyncCallbacks { registry: self.registry.clone() }
+        }
+    }
+
+    impl<E: 'static, R: 'static> Default for AsyncCallbacks<E, R> {
+        fn default() -> Self {
+            AsyncCallbacks::new()
+        }
+    }
+
+    impl<E: 'static, R: 'static> AsyncCallbacks<E, R> {
+        pub fn new() -> Self {
+            AsyncCallbacks { registry: Registry::new() }
+        }
+
+        pub fn register<F, Fut>(&mut self, callback: F) -> CallbackHandle
+            where F: FnMut(&E) -> Fut + 'static, Fut: Future<Output = R> + 'static
+        {
+            self.register_with_priority(0, callback)
+        }
+
+        /// Register a callback with a priority, see `Callbacks::register_with_priority`.
+        pub fn register_with_priority<F, Fut>(&mut self, priority: i32, mut callback: F) -> CallbackHandle
+            where F: FnMut(&E) -> Fut + 'static, Fut: Future<Output = R> + 'static
+        {
+            let cell = Rc::new(RefCell::new(move |event: &E| Some(Box::pin(callback(event)) as BoxFuture<R>)));
+            self.registry.add(cell, false, priority)
+        }
+
+        /// Register a callback for just the next call, see `Callbacks::register_once`.
+        pub fn register_once<F, Fut>(&mut self, callback: F) -> CallbackHandle
+            where F: FnOnce(&E) -> Fut + 'static, Fut: Future<Output = R> + 'static
+        {
+            let mut callback = Some(callback);
+            let cell = Rc::new(RefCell::new(move |event: &E| {
+                callback.take().map(|callback| Box::pin(callback(event)) as BoxFuture<R>)
+            }));
+            self.registry.add(cell, true, 0)
+        }
+
+        /// Remove the callback registered under `handle`, see `Callbacks::unregister`.
+        pub fn unregister(&mut self, handle: CallbackHandle) -> bool {
+            self.registry.unregister(handle)
+        }
+
+        /// Call the callbacks one after the other: the next one is only called once the future of
+        /// the previous one is done. The results come in the order the callbacks ran. If the
+        /// returned future is dropped early, the callbacks it did not get to are not called, and
+        /// one-shot callbacks among them stay registered.
+        pub fn call_async<'a>(&'a mut self, event: &'a E) -> CallSequential<'a, E, R> {
+            CallSequential { callbacks: self, event, next: 0, current: None, ran_once: Vec::new(), results: Vec::new() }
+        }
+
+        /// Call all the callbacks right away, and then wait for all their futures at once. The
+        /// results are in the order of the callbacks, not in the order the futures finished.
+        pub fn call_join(&mut self, event: &E) -> CallJoin<R> {
+            let mut ran_once = Vec::new();
+            let mut pending = Vec::new();
+            for entry in self.registry.entries.iter() {
+                if entry.once {
+                    ran_once.push(entry.handle);
+                }
+                let mut closure = entry.callback.borrow_mut();
+                if let Some(future) = (*closure)(event) {
+                    pending.push(future);
+                }
+            }
+            self.registry.remove_all(&ran_once);
+            let results = pending.iter().map(|_| None).collect();
+            CallJoin { pending: pending.into_iter().map(Some).collect(), results }
+        }
+    }
+
+    /// The future returned by `AsyncCallbacks::call_async`.
+    pub struct CallSequential<'a, E: 'a, R> {
+        callbacks: &'a mut AsyncCallbacks<E, R>,
+        event: &'a E,
+        // The position of the next callback to call, and the future of the one we are waiting for.
+        next: usize,
+        current: Option<BoxFuture<R>>,
+        ran_once: Vec<CallbackHandle>,
+        results: Vec<R>,
+    }
+
+    // We never pin any of the fields: the futures of the callbacks are pinned in their own boxes.
+    impl<'a, E, R> Unpin for CallSequential<'a, E, R> {}
+
+    impl<'a, E, R> Future for CallSequential<'a, E, R> {
+        type Output = Vec<R>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Vec<R>> {
+            let this = self.get_mut();
+            loop {
+                if let Some(ref mut future) = this.current {
+                    match future.as_mut().poll(cx) {
+                        Poll::Ready(result) => this.results.push(result),
+                        Poll::Pending => return Poll::Pending,
+                    }
+                    this.current = None;
+                }
+                match this.callbacks.registry.entries.get(this.next) {
+                    Some(entry) => {
+                        if entry.once {
+                            this.ran_once.push(entry.handle);
+                        }
+                        let mut closure = entry.callback.borrow_mut();
+                        this.current = (*closure)(this.event);
+                        this.next += 1;
+                    }
+                    None => {
+                        this.callbacks.registry.remove_all(&this.ran_once);
+                        return Poll::Ready(mem::take(&mut this.results));
+                    }
+                }
+            }
+        }
+    }
+
+    /// The future returned by `AsyncCallbacks::call_join`.
+    pub struct CallJoin<R> {
+        // The futures that are not done yet, and the results of the others, in the same positions.
+        pending: Vec<Option<BoxFuture<R>>>,
+        results: Vec<Option<R>>,
+    }
+
+    // As for `CallSequential`, the futures are pinned in their own boxes.
+    impl<R> Unpin for CallJoin<R> {}
+
+    impl<R> Future for CallJoin<R> {
+        type Output = Vec<R>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Vec<R>> {
+            let this = self.get_mut();
+            let mut done = true;
+            for (slot, result) in this.pending.iter_mut().zip(this.results.iter_mut()) {
+                if let Some(ref mut future) = *slot {
+                    match future.as_mut().poll(cx) {
+                        Poll::Ready(r) => *result = Some(r),
+                        Poll::Pending => done = false,
+                    }
+                }
+                if result.is_some() {
+                    *slot = None;
+                }
+            }
+            if !done {
+                return Poll::Pending;
+            }
+            Poll::Ready(this.results.iter_mut().map(|r| r.take().expect("CallJoin polled after completion")).collect())
         }
     }
 }
@@ -47,6 +494,223 @@ mod tests {
     use std::cell::RefCell;
     use super::*;
 
+    // A callback that records the values it sees in `log`, tagged with `name`.
+    fn logger(log: &Rc<RefCell<Vec<(&'static str, i32)>>>, name: &'static str) -> impl FnMut(&i32) {
+        let log = log.clone();
+        move |&val| log.borrow_mut().push((name, val))
+    }
+
+    #[test]
+    fn test_unregister() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut c = Callbacks::new();
+        let a = c.register(logger(&log, "a"));
+        let b = c.register(logger(&log, "b"));
+        c.call(&1);
+        let mut clone = c.clone();
+        assert!(c.unregister(a));
+        assert!(!c.unregister(a));
+        c.call(&2);
+        clone.call(&3);
+        assert!(c.unregister(b));
+        c.call(&4);
+        assert_eq!(*log.borrow(), [("a", 1), ("b", 1), ("b", 2), ("a", 3), ("b", 3)]);
+    }
+
+    #[test]
+    fn test_once() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut c = Callbacks::new();
+        c.register(logger(&log, "always"));
+        c.register_once(logger(&log, "once"));
+        let mut clone = c.clone();
+        c.call(&1);
+        c.call(&2);
+        // The clone still has the registration, but the closure is used up.
+        clone.call(&3);
+        let cancelled = c.register_once(logger(&log, "cancelled"));
+        assert!(c.unregister(cancelled));
+        c.call(&4);
+        assert_eq!(*log.borrow(), [("always", 1), ("once", 1), ("always", 2), ("always", 3), ("always", 4)]);
+    }
+
+    #[test]
+    fn test_priority() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut c = Callbacks::new();
+        c.register(logger(&log, "business 1"));
+        c.register_with_priority(-10, logger(&log, "metrics"));
+        c.register_with_priority(10, logger(&log, "logging"));
+        c.register(logger(&log, "business 2"));
+        c.register_once(logger(&log, "business once"));
+        c.call(&0);
+        let order: Vec<&str> = log.borrow().iter().map(|&(name, _)| name).collect();
+        assert_eq!(order, ["logging", "business 1", "business 2", "business once", "metrics"]);
+    }
+
+    #[test]
+    fn test_results() {
+        let mut c = Callbacks::new();
+        c.register(|val| val * 2);
+        c.register_with_priority(1, |val| val + 1);
+        c.register_once(|val| -val);
+        assert_eq!(c.call_collect(&5), [6, 10, -5]);
+        assert_eq!(c.call_collect(&1), [2, 2]);
+
+        // A handler that vetoes odd numbers, and one that counts how often it is asked.
+        let asked = Rc::new(RefCell::new(0));
+        let mut vetoes = Callbacks::new();
+        vetoes.register(|val| val % 2 != 0);
+        let counter = asked.clone();
+        vetoes.register(move |_| { *counter.borrow_mut() += 1; false });
+        assert_eq!(vetoes.call_until(&3, |&veto| veto), Some(true));
+        assert_eq!(vetoes.call_until(&4, |&veto| veto), None);
+        assert_eq!(*asked.borrow(), 1);
+    }
+
+    #[test]
+    fn test_once_short_circuit() {
+        // A one-shot callback that is not reached stays registered.
+        let mut c = Callbacks::new();
+        c.register(|&val| val);
+        c.register_once(|val| val + 100);
+        assert_eq!(c.call_until(&1, |_| true), Some(1));
+        assert_eq!(c.call_collect(&2), [2, 102]);
+        assert_eq!(c.call_collect(&3), [3]);
+    }
+
+    #[test]
+    fn test_events() {
+        use bigint::BigUint;
+
+        // Progress updates of a long computation, with the numbers found so far.
+        struct Progress { done: usize, largest: BigUint }
+        let mut c = Callbacks::new();
+        c.register(|p: &Progress| format!("{}% done, largest so far: {}", p.done, p.largest));
+        let update = Progress { done: 50, largest: BigUint::power_of_2(70) };
+        assert_eq!(c.call_collect(&update), ["50% done, largest so far: 1180591620717411303424"]);
+
+        let mut names: Callbacks<String, usize> = Callbacks::new();
+        names.register(|name| name.len());
+        assert_eq!(names.call_collect(&"rust".to_string()), [4]);
+    }
+
+    #[test]
+    fn test_weak() {
+        struct Window { title: String, resized: Vec<i32> }
+        let window = Rc::new(RefCell::new(Window { title: "main".to_string(), resized: Vec::new() }));
+        let mut c = Callbacks::new();
+        c.register_weak(&window, |w: &mut Window, &width| { w.resized.push(width); w.title.len() });
+        c.register(|&width| width as usize);
+        assert_eq!(c.call_collect(&640), [4, 640]);
+        assert_eq!(window.borrow().resized, [640]);
+        // The registry does not keep the window alive, and forgets the callback once it is gone.
+        let weak = Rc::downgrade(&window);
+        drop(window);
+        assert!(weak.upgrade().is_none());
+        assert_eq!(c.call_collect(&800), [800]);
+        assert_eq!(c.registry.entries.len(), 1);
+        assert!(c.subscribers.is_empty());
+
+        let other = Rc::new(RefCell::new(0));
+        let handle = c.register_weak(&other, |n: &mut i32, &width| { *n += width; 0 });
+        assert!(c.unregister(handle));
+        assert!(c.subscribers.is_empty());
+    }
+
+    #[test]
+    fn test_sync() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+        use std::thread;
+
+        let sum = Arc::new(AtomicUsize::new(0));
+        let once = Arc::new(AtomicUsize::new(0));
+        let c: SyncCallbacks<usize> = SyncCallbacks::new();
+        let counter = sum.clone();
+        c.register(move |&n| { counter.fetch_add(n, Ordering::SeqCst); });
+        let counter = once.clone();
+        c.register_once(move |_| { counter.fetch_add(1, Ordering::SeqCst); });
+        // Events from several threads at once.
+        thread::scope(|scope| {
+            for t in 0..4 {
+                let c = c.clone();
+                scope.spawn(move || for n in 0..100 { c.call(&(t * 100 + n)) });
+            }
+        });
+        assert_eq!(sum.load(Ordering::SeqCst), (0..400).sum());
+        assert_eq!(once.load(Ordering::SeqCst), 1);
+
+        // Registrations are shared between the clones, and may change while the callbacks run.
+        let c: SyncCallbacks<i32, i32> = SyncCallbacks::new();
+        let clone = c.clone();
+        let handle = c.register(move |&n| { clone.register(|&m| m * 10); n });
+        assert_eq!(c.call_collect(&1), [1]);
+        assert!(c.unregister(handle));
+        assert_eq!(c.call_collect(&2), [20]);
+        assert_eq!(c.call_until(&3, |&r| r > 0), Some(30));
+    }
+
+    // Run a future to completion, by polling it over and over again.
+    #[cfg(feature = "async")]
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        use std::task::{Context, Poll, Waker};
+        let mut future = Box::pin(future);
+        let mut cx = Context::from_waker(Waker::noop());
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    // A future that is not ready the first time it is polled, and logs when it is done.
+    #[cfg(feature = "async")]
+    struct Delayed {
+        value: i32,
+        polled: bool,
+        log: Rc<RefCell<Vec<String>>>,
+    }
+
+    #[cfg(feature = "async")]
+    impl std::future::Future for Delayed {
+        type Output = i32;
+        fn poll(mut self: std::pin::Pin<&mut Self>, _: &mut std::task::Context) -> std::task::Poll<i32> {
+            if !self.polled {
+                self.polled = true;
+                return std::task::Poll::Pending;
+            }
+            self.log.borrow_mut().push(format!("done {}", self.value));
+            std::task::Poll::Ready(self.value)
+        }
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn test_async() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let handler = |log: &Rc<RefCell<Vec<String>>>, factor: i32| {
+            let log = log.clone();
+            move |&n: &i32| {
+                log.borrow_mut().push(format!("start {}", n * factor));
+                Delayed { value: n * factor, polled: false, log: log.clone() }
+            }
+        };
+        let mut c = AsyncCallbacks::new();
+        c.register(handler(&log, 1));
+        c.register(handler(&log, 2));
+        c.register_once(handler(&log, 3));
+
+        // One after the other...
+        assert_eq!(block_on(c.call_async(&1)), [1, 2, 3]);
+        assert_eq!(*log.borrow(), ["start 1", "done 1", "start 2", "done 2", "start 3", "done 3"]);
+        // ... or all at once. The one-shot callback is gone.
+        log.borrow_mut().clear();
+        assert_eq!(block_on(c.call_join(&10)), [10, 20]);
+        assert_eq!(*log.borrow(), ["start 10", "start 20", "done 10", "done 20"]);
+        assert_eq!(block_on(c.call_join(&0)), [0, 0]);
+    }
+
     #[test]
     #[should_panic]
     fn test_reentrant() {
@@ -63,7 +727,7 @@ mod tests {
             // below is printed.
             let mut guard = c2.borrow_mut();
             println!("Callback called with {}, ready to go for nested call.", val);
-            guard.call(val+val)
+            guard.call(&(val+val))
         } );
 
         // We do a clone of the `Callbacks` to ensure that the `RefCell` we created for the cycle is closed.
@@ -71,6 +735,6 @@ mod tests {
         // but rather the one inside `Callbacks::call`.
         let mut c2: Callbacks = c.borrow().clone();
         drop(c); // This is not strictly necessary. It demonstrates that we are not holding any reference to the `RefCell` any more.
-        c2.call(42);
+        c2.call(&42);
     }
 }
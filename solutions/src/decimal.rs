@@ -0,0 +1,545 @@
+// Decimal numbers of arbitrary precision. A `BigDecimal` is a `BigUint` mantissa together with a
+// scale, and stands for `mantissa * 10^-scale`: The mantissa 1999 with scale 2 is 19.99. Unlike with
+// floating point, every decimal fraction is represented exactly, so `0.1 + 0.2` really is `0.3`.
+// That is what computations with money need.
+//
+// Addition, subtraction and multiplication are exact; they just grow the scale as needed. Division
+// generally is not, so it takes the scale of the result, and how to round to it.
+//
+// The scale is part of the value as far as printing goes (`1.50` stays `1.50`), but comparisons
+// only look at the number: `1.50 == 1.5`. Like `BigUint`, a `BigDecimal` cannot be negative.
+
+#[cfg(not(feature = "std"))]
+use std::prelude::v1::*;
+use std::cmp;
+use std::fmt;
+use std::iter;
+use std::ops;
+use std::str::FromStr;
+use bigint::{BigUint, ParseBigIntError, ParseErrorKind};
+use binary::{self, Binary, DecodeError, Reader};
+use locale::Locale;
+
+/// A non-negative decimal number: `mantissa * 10^-scale`.
+#[derive(Clone, Debug)]
+pub struct BigDecimal {
+    mantissa: BigUint,
+    scale: i64,
+}
+
+/// How to round a result that does not fit the requested scale.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RoundingMode {
+    /// Drop the digits that do not fit (round towards zero).
+    Down,
+    /// Round away from zero if any of the dropped digits is not 0.
+    Up,
+    /// Round to the nearest number, and away from zero if both are equally near.
+    HalfUp,
+    /// Round to the nearest number, and towards zero if both are equally near.
+    HalfDown,
+    /// Round to the nearest number, and to the even one if both are equally near. This is also known
+    /// as banker's rounding, as it does not favor rounding up over rounding down.
+    HalfEven,
+}
+
+// Compute `10^k`. Panics if `k` is too large to ever fit into memory.
+fn pow10(k: u64) -> BigUint {
+    assert!(k <= u32::MAX as u64, "Scale of BigDecimal out of range");
+    BigUint::new(10).pow(k as u32)
+}
+
+// The result of a computation on scales. Like `pow10`, panic if it does not fit in an `i64`.
+fn scale_of(scale: Option<i64>) -> i64 {
+    scale.expect("Scale of BigDecimal out of range")
+}
+
+// Compute `n / d`, rounded according to `mode`.
+fn div_rounded(n: &BigUint, d: &BigUint, mode: RoundingMode) -> BigUint {
+    let (q, r) = n.div_rem(d);
+    if r.is_zero() {
+        return q;
+    }
+    // Compare the remainder to half of the divisor.
+    let half = (&r << 1).cmp(d);
+    let round_up = match mode {
+        RoundingMode::Down => false,
+        RoundingMode::Up => true,
+        RoundingMode::HalfUp => half != cmp::Ordering::Less,
+        RoundingMode::HalfDown => half == cmp::Ordering::Greater,
+        RoundingMode::HalfEven => half == cmp::Ordering::Greater || (half == cmp::Ordering::Equal && q.is_odd()),
+    };
+    if round_up { q.add_u64(1) } else { q }
+}
+
+impl BigDecimal {
+    /// Construct the number `mantissa * 10^-scale`.
+    pub fn new(mantissa: BigUint, scale: i64) -> Self {
+        BigDecimal { mantissa, scale }
+    }
+
+    pub fn from_integer(n: BigUint) -> Self {
+        BigDecimal::new(n, 0)
+    }
+
+    pub fn zero() -> Self {
+        BigDecimal::from_integer(BigUint::zero())
+    }
+
+    pub fn one() -> Self {
+        BigDecimal::from_integer(BigUint::one())
+    }
+
+    pub fn mantissa(&self) -> &BigUint {
+        &self.mantissa
+    }
+
+    /// The number of digits after the decimal point. If this is negative, the mantissa is missing
+    /// that many zeros at the end.
+    pub fn scale(&self) -> i64 {
+        self.scale
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.mantissa.is_zero()
+    }
+
+    // The mantissa, changed to a larger scale.
+    fn mantissa_at(&self, scale: i64) -> BigUint {
+        debug_assert!(scale >= self.scale);
+        &self.mantissa * &pow10(scale_of(scale.checked_sub(self.scale)) as u64)
+    }
+
+    /// Change the scale to `scale`, rounding according to `mode` if that drops digits.
+    pub fn with_scale(&self, scale: i64, mode: RoundingMode) -> BigDecimal {
+        if scale >= self.scale {
+            BigDecimal::new(self.mantissa_at(scale), scale)
+        } else {
+            let mantissa = div_rounded(&self.mantissa, &pow10(scale_of(self.scale.checked_sub(scale)) as u64), mode);
+            BigDecimal::new(mantissa, scale)
+        }
+    }
+
+    /// Drop the zeros at the end of the mantissa, making the scale as small as possible. Like
+    /// `BigUint`, 0 has no digits, so its scale becomes 0.
+    pub fn normalized(&self) -> BigDecimal {
+        if self.mantissa.is_zero() {
+            return BigDecimal::zero();
+        }
+        let mut result = self.clone();
+        loop {
+            let (q, r) = result.mantissa.div_rem_u64(10);
+            if r != 0 {
+                return result;
+            }
+            result = BigDecimal::new(q, result.scale - 1);
+        }
+    }
+
+    /// Render the number like `Display` does, but with the digits before the decimal point grouped,
+    /// and the decimal separator of the given locale.
+    pub fn format_localized(&self, locale: &Locale) -> String {
+        let s = self.to_string();
+        let (int, frac) = match s.find('.') {
+            Some(pos) => (&s[..pos], Some(&s[pos + 1..])),
+            None => (&s[..], None),
+        };
+        let mut result = locale.group_digits(int);
+        if let Some(frac) = frac {
+            result.push(locale.decimal_separator);
+            result.push_str(frac);
+        }
+        result
+    }
+
+    /// Divide by `divisor`, and round the quotient to `scale` digits after the decimal point
+    /// according to `mode`. Panics if `divisor` is 0.
+    pub fn div(&self, divisor: &BigDecimal, scale: i64, mode: RoundingMode) -> BigDecimal {
+        assert!(!divisor.is_zero(), "Division of BigDecimal by zero");
+        // The quotient of the mantissas has scale `self.scale - divisor.scale`, so we need to
+        // multiply it by `10^shift` to get to the requested scale.
+        let shift = scale_of(scale.checked_sub(self.scale).and_then(|s| s.checked_add(divisor.scale)));
+        let mantissa = if shift >= 0 {
+            div_rounded(&(&self.mantissa * &pow10(shift as u64)), &divisor.mantissa, mode)
+        } else {
+            div_rounded(&self.mantissa, &(&divisor.mantissa * &pow10(shift.unsigned_abs())), mode)
+        };
+        BigDecimal::new(mantissa, scale)
+    }
+}
+
+impl Default for BigDecimal {
+    fn default() -> Self {
+        BigDecimal::zero()
+    }
+}
+
+impl From<BigUint> for BigDecimal {
+    fn from(n: BigUint) -> Self {
+        BigDecimal::from_integer(n)
+    }
+}
+
+impl From<u64> for BigDecimal {
+    fn from(n: u64) -> Self {
+        BigDecimal::from_integer(BigUint::new(n))
+    }
+}
+
+impl fmt::Display for BigDecimal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let digits = self.mantissa.to_string();
+        if self.scale <= 0 {
+            let zeros = if self.mantissa.is_zero() { 0 } else { self.scale.unsigned_abs() as usize };
+            return f.pad(&(digits + &"0".repeat(zeros)));
+        }
+        // Pad with zeros so that there is a digit before the decimal point.
+        let scale = self.scale as usize;
+        let digits = if digits.len() <= scale { "0".repeat(scale + 1 - digits.len()) + &digits } else { digits };
+        let (int, frac) = digits.split_at(digits.len() - scale);
+        f.pad(&format!("{}.{}", int, frac))
+    }
+}
+
+impl FromStr for BigDecimal {
+    type Err = ParseBigIntError;
+
+    /// Parse a decimal number like `19.99`, `.5` or `1.5e-3`. The errors point at the problem in the
+    /// input, like the ones of `BigUint`.
+    fn from_str(s: &str) -> Result<BigDecimal, ParseBigIntError> {
+        let (number, exponent) = match s.find(['e', 'E']) {
+            Some(pos) => (&s[..pos], Some(&s[pos + 1..])),
+            None => (s, None),
+        };
+        let mut digits = String::with_capacity(number.len());
+        let mut scale = 0i64;
+        let mut seen_point = false;
+        for (offset, c) in number.char_indices() {
+            match c {
+                '0'..='9' => {
+                    digits.push(c);
+                    if seen_point {
+                        scale += 1;
+                    }
+                }
+                '.' if !seen_point => seen_point = true,
+                '-' if offset == 0 => {
+                    let suggestion = Some("BigDecimal cannot represent negative numbers".to_string());
+                    return Err(ParseBigIntError::new(ParseErrorKind::InvalidDigit { found: c, radix: 10 }, s, 0, 1, suggestion));
+                }
+                _ => return Err(ParseBigIntError::invalid_digit(s, offset, c, 10)),
+            }
+        }
+        if digits.is_empty() {
+            return Err(ParseBigIntError::new(ParseErrorKind::Empty, s, number.len(), 0, None));
+        }
+        if let Some(exponent) = exponent {
+            let invalid = || ParseBigIntError::new(ParseErrorKind::InvalidExponent, s, number.len() + 1, exponent.len(), None);
+            let exponent: i64 = exponent.parse().map_err(|_| invalid())?;
+            scale = scale.checked_sub(exponent).ok_or_else(invalid)?;
+        }
+        Ok(BigDecimal::new(digits.parse().unwrap(), scale))
+    }
+}
+
+impl PartialEq for BigDecimal {
+    fn eq(&self, other: &BigDecimal) -> bool {
+        self.cmp(other) == cmp::Ordering::Equal
+    }
+}
+
+impl Eq for BigDecimal {}
+
+impl Ord for BigDecimal {
+    fn cmp(&self, other: &BigDecimal) -> cmp::Ordering {
+        match (self.is_zero(), other.is_zero()) {
+            (true, true) => return cmp::Ordering::Equal,
+            (true, false) => return cmp::Ordering::Less,
+            (false, true) => return cmp::Ordering::Greater,
+            (false, false) => {}
+        }
+        // First compare the orders of magnitude, the position of the leading digit. The scales can
+        // be anything, so bringing both numbers to the same scale could need a power of 10 with
+        // billions of digits. We compute these positions in `i128`, so that they cannot overflow.
+        let magnitude = |x: &BigDecimal| x.mantissa.ilog10() as i128 - x.scale as i128;
+        match magnitude(self).cmp(&magnitude(other)) {
+            cmp::Ordering::Equal => {}
+            ord => return ord,
+        }
+        // With the leading digits at the same position, the scales differ by at most the number of
+        // digits of the mantissas, so scaling one of them is fine.
+        let scale = cmp::max(self.scale, other.scale);
+        self.mantissa_at(scale).cmp(&other.mantissa_at(scale))
+    }
+}
+
+impl PartialOrd for BigDecimal {
+    #[inline]
+    fn partial_cmp(&self, other: &BigDecimal) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl ops::Add<&BigDecimal> for &BigDecimal {
+    type Output = BigDecimal;
+    fn add(self, rhs: &BigDecimal) -> BigDecimal {
+        let scale = cmp::max(self.scale, rhs.scale);
+        BigDecimal::new(self.mantissa_at(scale) + rhs.mantissa_at(scale), scale)
+    }
+}
+
+impl ops::Sub<&BigDecimal> for &BigDecimal {
+    type Output = BigDecimal;
+    fn sub(self, rhs: &BigDecimal) -> BigDecimal {
+        let scale = cmp::max(self.scale, rhs.scale);
+        let mantissa = self.mantissa_at(scale).checked_sub(&rhs.mantissa_at(scale));
+        BigDecimal::new(mantissa.expect("Wrapping subtraction of BigDecimal"), scale)
+    }
+}
+
+impl ops::Mul<&BigDecimal> for &BigDecimal {
+    type Output = BigDecimal;
+    fn mul(self, rhs: &BigDecimal) -> BigDecimal {
+        BigDecimal::new(&self.mantissa * &rhs.mantissa, scale_of(self.scale.checked_add(rhs.scale)))
+    }
+}
+
+// Like for `BigUint`, the variants taking their arguments by value go through the references.
+forward_binop_by_value!(BigDecimal, impl Add, add);
+forward_binop_by_value!(BigDecimal, impl Sub, sub);
+forward_binop_by_value!(BigDecimal, impl Mul, mul);
+
+impl iter::Sum for BigDecimal {
+    fn sum<I: Iterator<Item = BigDecimal>>(iter: I) -> BigDecimal {
+        iter.fold(BigDecimal::zero(), |acc, x| acc + x)
+    }
+}
+
+impl<'a> iter::Sum<&'a BigDecimal> for BigDecimal {
+    fn sum<I: Iterator<Item = &'a BigDecimal>>(iter: I) -> BigDecimal {
+        iter.fold(BigDecimal::zero(), |acc, x| acc + x)
+    }
+}
+
+// In the binary format, a decimal is its mantissa as a nested value, followed by the scale.
+impl Binary for BigDecimal {
+    const TAG: u8 = binary::tag::BIGDECIMAL;
+
+    fn encode_payload(&self, out: &mut Vec<u8>) {
+        binary::write_nested(out, &self.mantissa);
+        binary::write_u64(out, self.scale as u64);
+    }
+
+    fn decode_payload(input: &mut Reader) -> Result<Self, DecodeError> {
+        let mantissa = input.read_nested()?;
+        let scale = input.read_u64()? as i64;
+        Ok(BigDecimal::new(mantissa, scale))
+    }
+}
+
+#[cfg(feature = "num")]
+mod num_impl {
+    use num_traits::{One, Zero};
+    use super::BigDecimal;
+
+    impl Zero for BigDecimal {
+        fn zero() -> Self {
+            BigDecimal::zero()
+        }
+
+        fn is_zero(&self) -> bool {
+            BigDecimal::is_zero(self)
+        }
+    }
+
+    impl One for BigDecimal {
+        fn one() -> Self {
+            BigDecimal::one()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use std::prelude::v1::*;
+    use bigint::BigUint;
+    #[cfg(feature = "std")]
+    use std::fmt::Write;
+    use binary::{decode, encode};
+    #[cfg(feature = "std")]
+    use snapshot::assert_snapshot;
+    use locale::{DE_DE, EN_IN, FR_FR, POSIX};
+    use bigint::ParseErrorKind;
+    use super::{BigDecimal, RoundingMode};
+
+    fn dec(s: &str) -> BigDecimal {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        for &s in ["0", "19.99", "0.05", "1.50", "100", "0.000", "123456789012345678901234567890.1"].iter() {
+            assert_eq!(dec(s).to_string(), s);
+        }
+        assert_eq!(dec(".5").to_string(), "0.5");
+        assert_eq!(dec("5.").to_string(), "5");
+        assert_eq!(dec("1.5e-3").to_string(), "0.0015");
+        assert_eq!(dec("1.5E3").to_string(), "1500");
+        assert_eq!(dec("12e+2").scale(), -2);
+        assert_eq!(BigDecimal::new(BigUint::new(7), -3).to_string(), "7000");
+        assert_eq!(BigDecimal::new(BigUint::zero(), -3).to_string(), "0");
+        assert_eq!(format!("{:>8}", dec("1.5")), "     1.5");
+        assert_eq!(format!("{:*<6}", BigDecimal::new(BigUint::new(7), -3)), "7000**");
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_format_snapshot() {
+        let values = [
+            ("0", 0),
+            ("0.000", 0),
+            ("19.99", 0),
+            ("1.50", 0),
+            ("0.05", 0),
+            ("7", -3),
+            ("1.5e-3", 0),
+            ("1.5e3", 0),
+            ("0.10", 0),
+            ("123456789012345678901234567890.000000000000000000001", 0),
+            ("1", 40),
+            ("42", -25),
+            ("100.00", 0),
+        ];
+        let mut out = String::new();
+        for &(s, shift) in values.iter() {
+            let d = dec(s);
+            let d = BigDecimal::new(d.mantissa().clone(), d.scale() + shift);
+            writeln!(out, "{}", d).unwrap();
+            writeln!(out, "  mantissa {:?} scale {}", d.mantissa(), d.scale()).unwrap();
+            writeln!(out, "  normalized {}", d.normalized()).unwrap();
+        }
+        assert_snapshot("decimal_format", &out);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = |s: &str| s.parse::<BigDecimal>().unwrap_err();
+        assert_eq!((err("").kind(), err("").offset()), (&ParseErrorKind::Empty, 0));
+        assert_eq!((err(".").kind(), err(".").offset()), (&ParseErrorKind::Empty, 1));
+        let e = err("1.2.3");
+        assert_eq!(*e.kind(), ParseErrorKind::InvalidDigit { found: '.', radix: 10 });
+        assert_eq!((e.offset(), e.snippet()), (3, "."));
+        let e = err("-1");
+        assert_eq!((e.offset(), e.suggestion()), (0, Some("BigDecimal cannot represent negative numbers")));
+        let e = err("1.5f");
+        assert_eq!((e.offset(), e.snippet()), (3, "f"));
+        let e = err("1e");
+        assert_eq!((e.kind(), e.offset(), e.snippet()), (&ParseErrorKind::InvalidExponent, 2, ""));
+        let e = err("2.5e99999999999999999999");
+        assert_eq!((e.kind(), e.offset(), e.snippet()), (&ParseErrorKind::InvalidExponent, 4, "99999999999999999999"));
+        assert_eq!(e.to_string(), "invalid exponent at byte 4\n  |\n  | 2.5e99999999999999999999\n  |     ^^^^^^^^^^^^^^^^^^^^");
+        assert_eq!(err("0.1e-9223372036854775808").kind(), &ParseErrorKind::InvalidExponent);
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(dec("0.1") + dec("0.2"), dec("0.3"));
+        assert_eq!((dec("19.99") * dec("3")).to_string(), "59.97");
+        assert_eq!((dec("1.10") + dec("2.205")).to_string(), "3.305");
+        assert_eq!((dec("10") - dec("0.01")).to_string(), "9.99");
+        assert_eq!((dec("1.5") * dec("1.5")).to_string(), "2.25");
+        let total: BigDecimal = ["0.10", "0.20", "0.30"].iter().map(|s| dec(s)).sum();
+        assert_eq!(total.to_string(), "0.60");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_sub_underflow() {
+        let _ = dec("0.1") - dec("0.2");
+    }
+
+    #[test]
+    fn test_comparisons() {
+        assert_eq!(dec("1.50"), dec("1.5"));
+        assert_eq!(dec("1500"), dec("1.5e3"));
+        assert!(dec("0.09") < dec("0.1"));
+        assert!(dec("2") > dec("1.999"));
+        assert_eq!(dec("1.500").normalized().to_string(), "1.5");
+        assert_eq!(dec("1500").normalized().scale(), -2);
+        assert_eq!(dec("0.00").normalized().scale(), 0);
+        // Scales far apart do not need the huge powers of 10.
+        assert!(dec("1e-5000000000") > dec("0"));
+        assert!(dec("1e-5000000000") < dec("1e5000000000"));
+        assert!(dec("0e-9223372036854775807") == dec("0e9223372036854775807"));
+        assert!(dec("5e9223372036854775807") > dec("1e-9223372036854775807"));
+        assert!(dec("0.0010e-5000000000") == dec("1e-5000000003"));
+    }
+
+    #[test]
+    fn test_rounding() {
+        let cases = [
+            ("2.5", RoundingMode::Down, "2"), ("2.5", RoundingMode::Up, "3"),
+            ("2.5", RoundingMode::HalfUp, "3"), ("2.5", RoundingMode::HalfDown, "2"),
+            ("2.5", RoundingMode::HalfEven, "2"), ("3.5", RoundingMode::HalfEven, "4"),
+            ("2.51", RoundingMode::HalfDown, "3"), ("2.49", RoundingMode::HalfUp, "2"),
+            ("2.01", RoundingMode::Up, "3"), ("2.00", RoundingMode::Up, "2"),
+        ];
+        for &(s, mode, expected) in cases.iter() {
+            assert_eq!(dec(s).with_scale(0, mode).to_string(), expected, "{} {:?}", s, mode);
+        }
+        assert_eq!(dec("1.5").with_scale(3, RoundingMode::Down).to_string(), "1.500");
+        assert_eq!(dec("1234").with_scale(-2, RoundingMode::HalfUp).to_string(), "1200");
+    }
+
+    #[test]
+    fn test_format_localized() {
+        assert_eq!(dec("1234567.891").format_localized(&DE_DE), "1.234.567,891");
+        assert_eq!(dec("1234.5").format_localized(&FR_FR), "1\u{202f}234,5");
+        assert_eq!(dec("0.05").format_localized(&DE_DE), "0,05");
+        assert_eq!(dec("12e3").format_localized(&DE_DE), "12.000");
+        assert_eq!(dec("1234567.50").format_localized(&EN_IN), "12,34,567.50");
+        assert_eq!(dec("1234567.50").format_localized(&POSIX), "1234567.50");
+    }
+
+    #[test]
+    fn test_div() {
+        assert_eq!(dec("100").div(&dec("3"), 2, RoundingMode::HalfEven).to_string(), "33.33");
+        assert_eq!(dec("2").div(&dec("3"), 2, RoundingMode::HalfUp).to_string(), "0.67");
+        assert_eq!(dec("2").div(&dec("3"), 2, RoundingMode::Down).to_string(), "0.66");
+        assert_eq!(dec("1.000").div(&dec("0.125"), 0, RoundingMode::Down).to_string(), "8");
+        assert_eq!(dec("12345").div(&dec("0.1"), -3, RoundingMode::HalfUp).to_string(), "123000");
+        // Splitting a bill: The shares need not add up exactly, which is why we choose the rounding.
+        let share = dec("100.00").div(&dec("7"), 2, RoundingMode::Up);
+        assert_eq!(share.to_string(), "14.29");
+        assert!(&share * &dec("7") >= dec("100"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_div_by_zero() {
+        dec("1").div(&dec("0.0"), 2, RoundingMode::HalfUp);
+    }
+
+    #[test]
+    #[should_panic(expected = "Scale of BigDecimal out of range")]
+    fn test_mul_scale_overflow() {
+        let tiny = BigDecimal::new(BigUint::new(1), i64::MAX);
+        let _ = &tiny * &tiny;
+    }
+
+    #[test]
+    #[should_panic(expected = "Scale of BigDecimal out of range")]
+    fn test_add_scale_overflow() {
+        let _ = BigDecimal::new(BigUint::new(1), i64::MAX) + BigDecimal::new(BigUint::new(1), i64::MIN);
+    }
+
+    #[test]
+    fn test_binary() {
+        for &s in ["0", "19.99", "1.5e-30", "7e30"].iter() {
+            let decoded: BigDecimal = decode(&encode(&dec(s))).unwrap();
+            assert_eq!(decoded.to_string(), dec(s).to_string());
+            assert_eq!(decoded.scale(), dec(s).scale());
+        }
+    }
+}
@@ -0,0 +1,114 @@
+// Hashing the contents of files, for instance to find duplicates. We use the 128-bit variant of the
+// FNV-1a hash: It is not cryptographically secure, but simple, and wide enough that accidental
+// collisions are practically impossible. The files are hashed in parallel, on a task graph.
+
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::thread;
+use taskgraph::TaskGraph;
+
+const FNV_OFFSET_BASIS: u128 = 0x6c62272e07bb014262b821756295c58d;
+const FNV_PRIME: u128 = 0x0000000001000000000000000000013b;
+
+/// The state of an FNV-1a hash computation, for hashing data that arrives in pieces.
+#[derive(Clone, Copy, Debug)]
+pub struct Fnv128 {
+    state: u128,
+}
+
+impl Fnv128 {
+    pub fn new() -> Self {
+        Fnv128 { state: FNV_OFFSET_BASIS }
+    }
+
+    pub fn update(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.state ^= byte as u128;
+            self.state = self.state.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    pub fn finish(&self) -> u128 {
+        self.state
+    }
+}
+
+impl Default for Fnv128 {
+    fn default() -> Self {
+        Fnv128::new()
+    }
+}
+
+/// Hash `bytes` in one go.
+pub fn fnv1a_128(bytes: &[u8]) -> u128 {
+    let mut hasher = Fnv128::new();
+    hasher.update(bytes);
+    hasher.finish()
+}
+
+/// Hash the contents of a file.
+pub fn hash_file<P: AsRef<Path>>(path: P) -> io::Result<u128> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Fnv128::new();
+    let mut buf = vec![0; 64 * 1024];
+    loop {
+        match file.read(&mut buf) {
+            Ok(0) => return Ok(hasher.finish()),
+            Ok(read) => hasher.update(&buf[..read]),
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Hash many files in parallel, using one thread per core. The results are in the order of `paths`.
+/// If any of the files cannot be read, the first such error is returned.
+pub fn hash_files<P: AsRef<Path>>(paths: &[P]) -> io::Result<Vec<(PathBuf, u128)>> {
+    let threads = thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    let mut graph = TaskGraph::new();
+    for path in paths {
+        let path = path.as_ref().to_path_buf();
+        graph.add(&[], move |_| hash_file(&path));
+    }
+    let hashes = graph.run(threads);
+    paths.iter().zip(hashes).map(|(path, hash)| Ok((path.as_ref().to_path_buf(), hash?))).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fnv1a_128, hash_file, hash_files, Fnv128};
+    use testfs::TestTree;
+
+    #[test]
+    fn test_fnv1a_128() {
+        // Test vectors from the reference implementation.
+        assert_eq!(fnv1a_128(b""), 0x6c62272e07bb014262b821756295c58d);
+        assert_eq!(fnv1a_128(b"a"), 0xd228cb696f1a8caf78912b704e4a8964);
+        assert_eq!(fnv1a_128(b"foobar"), 0x343e1662793c64bf6f0d3597ba446f18);
+        let mut hasher = Fnv128::new();
+        hasher.update(b"foo");
+        hasher.update(b"bar");
+        assert_eq!(hasher.finish(), fnv1a_128(b"foobar"));
+    }
+
+    #[test]
+    fn test_hash_files() {
+        let big: Vec<u8> = (0..200_000).map(|i| (i % 251) as u8).collect();
+        let dir = TestTree::new()
+            .file("a", "foobar")
+            .file("b", "")
+            .file("copy_of_a", "foobar")
+            .file("big", &big)
+            .create();
+        let paths: Vec<_> = ["a", "b", "copy_of_a", "big"].iter().map(|p| dir.path(p)).collect();
+        let hashes = hash_files(&paths).unwrap();
+        assert_eq!(hashes.iter().map(|h| &h.0).collect::<Vec<_>>(), paths.iter().collect::<Vec<_>>());
+        assert_eq!(hashes[0].1, fnv1a_128(b"foobar"));
+        assert_eq!(hashes[1].1, fnv1a_128(b""));
+        assert_eq!(hashes[0].1, hashes[2].1);
+        assert_eq!(hashes[3].1, fnv1a_128(&big));
+        assert_eq!(hash_file(dir.path("big")).unwrap(), fnv1a_128(&big));
+        assert!(hash_files(&[dir.path("a"), dir.path("missing")]).is_err());
+    }
+}
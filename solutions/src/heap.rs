@@ -0,0 +1,290 @@
+// A binary min-heap: a priority queue that always hands out its smallest element first. The elements
+// live in a vector that is read as a complete binary tree, where the children of the element at `i`
+// are at `2i + 1` and `2i + 2`. Every element is at most as large as its children, so the smallest
+// one is at the root. Adding or removing an element breaks this in one place only, which we repair by
+// moving that element up or down the tree ("sifting"), in logarithmic time.
+//
+// The standard library has a heap as well, but it is a *max*-heap. Having our own makes the order
+// explicit where we rely on it. To get a max-heap from this one, wrap the elements in `Reverse`.
+
+#[cfg(not(feature = "std"))]
+use std::prelude::v1::*;
+use std::cmp::Reverse;
+use std::iter::FromIterator;
+use std::mem;
+use std::ops::{Deref, DerefMut};
+
+/// A priority queue that returns the smallest element first.
+#[derive(Clone, Debug)]
+pub struct MinHeap<T: Ord> {
+    data: Vec<T>,
+}
+
+impl<T: Ord> MinHeap<T> {
+    pub fn new() -> Self {
+        MinHeap { data: Vec::new() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Return the smallest element, without removing it.
+    pub fn peek(&self) -> Option<&T> {
+        self.data.first()
+    }
+
+    /// Return the smallest element for changing it. Once the returned guard goes away, the element
+    /// is moved to its new place in the heap.
+    pub fn peek_mut(&mut self) -> Option<PeekMut<'_, T>> {
+        if self.data.is_empty() {
+            None
+        } else {
+            Some(PeekMut { heap: self, sift: true })
+        }
+    }
+
+    pub fn push(&mut self, t: T) {
+        self.data.push(t);
+        let last = self.data.len() - 1;
+        self.sift_up(last);
+    }
+
+    /// Remove the smallest element and return it.
+    pub fn pop(&mut self) -> Option<T> {
+        // Move the last element to the root, and from there down to where it belongs.
+        let last = self.data.pop()?;
+        if self.data.is_empty() {
+            return Some(last);
+        }
+        let min = mem::replace(&mut self.data[0], last);
+        self.sift_down(0);
+        Some(min)
+    }
+
+    /// Return all elements, sorted in increasing order.
+    pub fn into_sorted_vec(mut self) -> Vec<T> {
+        // Repeatedly swap the root to the end of the part that is still a heap. That sorts in
+        // decreasing order, so we reverse at the end.
+        let mut end = self.data.len();
+        while end > 1 {
+            end -= 1;
+            self.data.swap(0, end);
+            self.sift_down_until(0, end);
+        }
+        self.data.reverse();
+        self.data
+    }
+
+    /// Return all elements, in no particular order.
+    pub fn into_vec(self) -> Vec<T> {
+        self.data
+    }
+
+    fn sift_up(&mut self, mut i: usize) {
+        while i > 0 {
+            let parent = (i - 1) / 2;
+            if self.data[parent] <= self.data[i] {
+                break;
+            }
+            self.data.swap(parent, i);
+            i = parent;
+        }
+    }
+
+    fn sift_down(&mut self, i: usize) {
+        let len = self.data.len();
+        self.sift_down_until(i, len);
+    }
+
+    // Sift down within the first `end` elements only.
+    fn sift_down_until(&mut self, mut i: usize, end: usize) {
+        loop {
+            let mut smallest = i;
+            for child in [2 * i + 1, 2 * i + 2].iter().cloned() {
+                if child < end && self.data[child] < self.data[smallest] {
+                    smallest = child;
+                }
+            }
+            if smallest == i {
+                return;
+            }
+            self.data.swap(i, smallest);
+            i = smallest;
+        }
+    }
+}
+
+impl<T: Ord> Default for MinHeap<T> {
+    fn default() -> Self {
+        MinHeap::new()
+    }
+}
+
+// Building a heap from a vector sifts down all inner nodes, from the last one up. This takes linear
+// time, which is faster than pushing the elements one by one.
+impl<T: Ord> From<Vec<T>> for MinHeap<T> {
+    fn from(data: Vec<T>) -> Self {
+        let mut heap = MinHeap { data };
+        for i in (0..heap.data.len() / 2).rev() {
+            heap.sift_down(i);
+        }
+        heap
+    }
+}
+
+impl<T: Ord> FromIterator<T> for MinHeap<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        MinHeap::from(iter.into_iter().collect::<Vec<T>>())
+    }
+}
+
+impl<T: Ord> Extend<T> for MinHeap<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for t in iter {
+            self.push(t);
+        }
+    }
+}
+
+/// The smallest element of a `MinHeap`, borrowed for changing it. See `MinHeap::peek_mut`.
+pub struct PeekMut<'a, T: 'a + Ord> {
+    heap: &'a mut MinHeap<T>,
+    // Whether the root still has to be moved to its place when the guard goes away.
+    sift: bool,
+}
+
+impl<'a, T: Ord> PeekMut<'a, T> {
+    /// Remove the element from the heap and return it.
+    pub fn pop(mut this: PeekMut<'a, T>) -> T {
+        // `pop` restores the heap by itself.
+        this.sift = false;
+        this.heap.pop().unwrap()
+    }
+}
+
+impl<'a, T: Ord> Deref for PeekMut<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.heap.data[0]
+    }
+}
+
+impl<'a, T: Ord> DerefMut for PeekMut<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.heap.data[0]
+    }
+}
+
+impl<'a, T: Ord> Drop for PeekMut<'a, T> {
+    fn drop(&mut self) {
+        if self.sift {
+            self.heap.sift_down(0);
+        }
+    }
+}
+
+/// Return the `k` smallest items, in increasing order. This keeps only `k` items around at any time:
+/// the smallest ones so far, in a heap whose root is the largest of them, to be replaced by the next
+/// smaller item.
+pub fn k_smallest<T: Ord, I: IntoIterator<Item = T>>(items: I, k: usize) -> Vec<T> {
+    if k == 0 {
+        return Vec::new();
+    }
+    let mut heap = MinHeap::new();
+    for item in items {
+        if heap.len() < k {
+            heap.push(Reverse(item));
+        } else {
+            let mut largest = heap.peek_mut().unwrap();
+            if item < largest.0 {
+                *largest = Reverse(item);
+            }
+        }
+    }
+    // The heap sorts the reversed items in decreasing order.
+    let mut result: Vec<T> = heap.into_sorted_vec().into_iter().map(|Reverse(item)| item).collect();
+    result.reverse();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use std::prelude::v1::*;
+    use std::cmp::Reverse;
+    use bigint::BigUint;
+    use testdata::numbers;
+    use super::{MinHeap, PeekMut, k_smallest};
+
+    #[test]
+    fn test_push_pop() {
+        let mut heap = MinHeap::new();
+        assert_eq!(heap.pop(), None);
+        for n in numbers(500) {
+            heap.push(n);
+        }
+        assert_eq!(heap.len(), 500);
+        assert_eq!(heap.peek(), Some(&0));
+        let mut popped = Vec::new();
+        while let Some(n) = heap.pop() {
+            popped.push(n);
+        }
+        let mut sorted = numbers(500);
+        sorted.sort();
+        assert_eq!(popped, sorted);
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn test_from_vec() {
+        for len in 0..40 {
+            let heap = MinHeap::from(numbers(len));
+            let mut sorted = numbers(len);
+            sorted.sort();
+            assert_eq!(heap.into_sorted_vec(), sorted);
+        }
+        let mut heap: MinHeap<_> = vec![BigUint::factorial(20), BigUint::power_of_2(70)].into_iter().collect();
+        heap.extend(vec![BigUint::new(3), BigUint::zero()]);
+        assert_eq!(heap.into_sorted_vec(),
+                   vec![BigUint::zero(), BigUint::new(3), BigUint::factorial(20), BigUint::power_of_2(70)]);
+    }
+
+    #[test]
+    fn test_peek_mut() {
+        let mut heap = MinHeap::from(vec![5, 1, 8, 3]);
+        *heap.peek_mut().unwrap() = 10;
+        assert_eq!(heap.peek(), Some(&3));
+        *heap.peek_mut().unwrap() = 2;
+        assert_eq!(heap.peek(), Some(&2));
+        assert_eq!(PeekMut::pop(heap.peek_mut().unwrap()), 2);
+        assert_eq!(heap.into_sorted_vec(), vec![5, 8, 10]);
+        assert!(MinHeap::<u8>::new().peek_mut().is_none());
+    }
+
+    #[test]
+    fn test_max_heap() {
+        let heap: MinHeap<_> = numbers(20).into_iter().map(Reverse).collect();
+        let mut sorted = numbers(20);
+        sorted.sort();
+        sorted.reverse();
+        assert_eq!(heap.into_sorted_vec().into_iter().map(|Reverse(n)| n).collect::<Vec<_>>(), sorted);
+    }
+
+    #[test]
+    fn test_k_smallest() {
+        let mut sorted = numbers(300);
+        sorted.sort();
+        for &k in [0, 1, 5, 299, 300, 1000].iter() {
+            let expected = &sorted[..k.min(sorted.len())];
+            assert_eq!(k_smallest(numbers(300), k), expected);
+        }
+        let big: Vec<BigUint> = (0..50).map(|i| BigUint::power_of_2(100 - i)).collect();
+        assert_eq!(k_smallest(big.iter(), 2), vec![&BigUint::power_of_2(51), &BigUint::power_of_2(52)]);
+    }
+}
@@ -0,0 +1,85 @@
+// This crate contains solutions to *some* of the exercises, and it bundles
+// the projects that span multiple parts together in one file per project.
+// It is not always up-to-date with the code in the actual course, and mainly
+// serves as draft board for new parts or exercises.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(feature = "std"))]
+#[macro_use]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate docopt;
+#[cfg(feature = "rayon")]
+extern crate rayon;
+#[cfg(feature = "num")]
+extern crate num_traits;
+#[cfg(feature = "num")]
+extern crate num_integer;
+#[cfg(feature = "quickcheck")]
+extern crate quickcheck;
+#[cfg(feature = "proptest")]
+extern crate proptest;
+#[cfg(feature = "num-bigint")]
+extern crate num_bigint;
+#[cfg(feature = "ibig")]
+extern crate ibig;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(feature = "regex")]
+extern crate regex;
+
+// Without the standard library, we put together the parts of it that we need from `core` and
+// `alloc`, so that the code can keep using `std` paths either way. The modules that allocate import
+// `std::prelude::v1::*` to get `Vec`, `String` and friends, which are otherwise in scope implicitly.
+#[cfg(not(feature = "std"))]
+mod std {
+    pub use core::*;
+    pub use alloc::{collections, fmt, rc, slice, str, sync, vec};
+
+    pub mod prelude {
+        pub mod v1 {
+            pub use alloc::boxed::Box;
+            pub use alloc::string::{String, ToString};
+            pub use alloc::vec::Vec;
+        }
+    }
+}
+
+#[macro_use]
+mod macros;
+#[cfg(feature = "alloc-stats")]
+#[macro_use]
+pub mod alloc_stats;
+pub mod bigint;
+pub mod rational;
+pub mod decimal;
+#[cfg(feature = "std")]
+pub mod vec;
+#[cfg(feature = "std")]
+pub mod rgrep;
+#[cfg(feature = "std")]
+pub mod callbacks;
+#[cfg(feature = "std")]
+pub mod counter;
+pub mod list;
+pub mod locale;
+#[cfg(feature = "std")]
+pub mod taskgraph;
+#[cfg(feature = "std")]
+pub mod filehash;
+pub mod binary;
+pub mod trie;
+pub mod heap;
+pub mod skiplist;
+pub mod rope;
+#[cfg(feature = "rayon")]
+pub mod parallel;
+pub mod prelude;
+
+#[cfg(all(test, feature = "std"))]
+mod snapshot;
+#[cfg(all(test, feature = "std"))]
+mod testfs;
+#[cfg(test)]
+mod testdata;
@@ -1,3 +1,5 @@
+#[cfg(not(feature = "std"))]
+use std::prelude::v1::*;
 use std::ptr;
 use std::mem;
 use std::marker::PhantomData;
@@ -105,11 +107,34 @@ impl<T> LinkedList<T> {
         }
     }
 
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter { next: self.first, _marker: PhantomData }
+    }
+
     pub fn iter_mut(&mut self) -> IterMut<T> {
         IterMut { next: self.first, _marker: PhantomData  }
     }
 }
 
+pub struct Iter<'a, T> where T: 'a {
+    next: NodePtr<T>,
+    _marker: PhantomData<&'a T>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next.is_null() {
+            None
+        } else {
+            let ret = unsafe { &(*self.next).data };
+            self.next = unsafe { (*self.next).next };
+            Some(ret)
+        }
+    }
+}
+
 pub struct IterMut<'a, T> where T: 'a {
     next: NodePtr<T>,
     _marker: PhantomData<&'a T>,
@@ -142,6 +167,8 @@ impl<T> Drop for LinkedList<T> {
 
 #[cfg(test)]
 mod tests {
+    #[cfg(not(feature = "std"))]
+    use std::prelude::v1::*;
     use std::rc::Rc;
     use std::cell::Cell;
     use super::LinkedList;
@@ -220,4 +247,14 @@ mod tests {
             assert_eq!(n as i32, *i);
         }
     }
+
+    #[test]
+    fn test_iter() {
+        let mut l = LinkedList::<i32>::new();
+        assert_eq!(l.iter().next(), None);
+        for i in 0..5 {
+            l.push_back(i);
+        }
+        assert_eq!(l.iter().cloned().collect::<Vec<_>>(), vec![0, 1, 2, 3, 4]);
+    }
 }
@@ -0,0 +1,85 @@
+// A small table of number formatting conventions, for the `format_localized` functions of our number
+// types. This is not meant to replace a real localization library, but it covers the cases that
+// usually come up in reports: the choice of decimal separator, the group separator, and how many
+// digits go into each group.
+
+#[cfg(not(feature = "std"))]
+use std::prelude::v1::*;
+
+/// How numbers are written in some locale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Locale {
+    pub name: &'static str,
+    pub decimal_separator: char,
+    pub group_separator: char,
+    /// The sizes of the digit groups, starting next to the decimal separator. The last size repeats;
+    /// an empty list means that digits are not grouped at all.
+    pub grouping: &'static [usize],
+}
+
+/// No grouping, and a '.' as the decimal separator (what `Display` does).
+pub const POSIX: Locale = Locale { name: "C", decimal_separator: '.', group_separator: ',', grouping: &[] };
+pub const EN_US: Locale = Locale { name: "en_US", decimal_separator: '.', group_separator: ',', grouping: &[3] };
+pub const DE_DE: Locale = Locale { name: "de_DE", decimal_separator: ',', group_separator: '.', grouping: &[3] };
+/// French uses a narrow no-break space to group digits.
+pub const FR_FR: Locale = Locale { name: "fr_FR", decimal_separator: ',', group_separator: '\u{202f}', grouping: &[3] };
+pub const DE_CH: Locale = Locale { name: "de_CH", decimal_separator: '.', group_separator: '\'', grouping: &[3] };
+/// Indian numbering groups the lowest three digits, and pairs of digits above that (lakh, crore).
+pub const EN_IN: Locale = Locale { name: "en_IN", decimal_separator: '.', group_separator: ',', grouping: &[3, 2] };
+
+/// All the locales we know about.
+pub const LOCALES: &[Locale] = &[POSIX, EN_US, DE_DE, FR_FR, DE_CH, EN_IN];
+
+impl Locale {
+    /// Look up a locale by its name, e.g. "de_DE".
+    pub fn by_name(name: &str) -> Option<&'static Locale> {
+        LOCALES.iter().find(|locale| locale.name == name)
+    }
+
+    /// Insert group separators into a string of (ASCII) digits.
+    pub fn group_digits(&self, digits: &str) -> String {
+        debug_assert!(digits.bytes().all(|b| b.is_ascii_digit()));
+        if self.grouping.is_empty() {
+            return digits.to_string();
+        }
+        // Cut the groups off the end, then put them together in the right order.
+        let mut groups = Vec::new();
+        let mut end = digits.len();
+        let mut sizes = self.grouping.iter();
+        let mut size = self.grouping[0];
+        while end > 0 {
+            size = *sizes.next().unwrap_or(&size);
+            let start = end.saturating_sub(size);
+            groups.push(&digits[start..end]);
+            end = start;
+        }
+        groups.reverse();
+        groups.join(&self.group_separator.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_group_digits() {
+        assert_eq!(EN_US.group_digits("0"), "0");
+        assert_eq!(EN_US.group_digits("123"), "123");
+        assert_eq!(EN_US.group_digits("1234"), "1,234");
+        assert_eq!(EN_US.group_digits("1234567"), "1,234,567");
+        assert_eq!(DE_DE.group_digits("1234567"), "1.234.567");
+        assert_eq!(DE_CH.group_digits("123456"), "123'456");
+        assert_eq!(FR_FR.group_digits("1234"), "1\u{202f}234");
+        assert_eq!(POSIX.group_digits("1234567"), "1234567");
+        assert_eq!(EN_IN.group_digits("1234"), "1,234");
+        assert_eq!(EN_IN.group_digits("12345"), "12,345");
+        assert_eq!(EN_IN.group_digits("1234567890"), "1,23,45,67,890");
+    }
+
+    #[test]
+    fn test_by_name() {
+        assert_eq!(Locale::by_name("en_IN"), Some(&EN_IN));
+        assert_eq!(Locale::by_name("xx_XX"), None);
+    }
+}
@@ -0,0 +1,33 @@
+// Macros shared by the number types. This module comes first in `lib.rs`, so that the macros are
+// available in all the modules after it.
+
+// The arithmetic operators of our number types are implemented on references, so that they do not
+// need to consume their operands. This adds the three variants taking (some of) their arguments by
+// value, which just borrow them: `forward_binop_by_value!(BigUint, impl Mul, mul);`
+macro_rules! forward_binop_by_value {
+    ($t:ty, impl $imp:ident, $method:ident) => {
+        impl ::std::ops::$imp<$t> for &$t {
+            type Output = $t;
+            #[inline]
+            fn $method(self, rhs: $t) -> $t {
+                ::std::ops::$imp::$method(self, &rhs)
+            }
+        }
+
+        impl ::std::ops::$imp<&$t> for $t {
+            type Output = $t;
+            #[inline]
+            fn $method(self, rhs: &$t) -> $t {
+                ::std::ops::$imp::$method(&self, rhs)
+            }
+        }
+
+        impl ::std::ops::$imp<$t> for $t {
+            type Output = $t;
+            #[inline]
+            fn $method(self, rhs: $t) -> $t {
+                ::std::ops::$imp::$method(&self, &rhs)
+            }
+        }
+    }
+}
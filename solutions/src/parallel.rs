@@ -0,0 +1,51 @@
+// Data-parallel helpers running on rayon's global thread pool. Programs that already use rayon
+// should take these rather than `taskgraph`, which starts threads of its own: Two pools competing
+// for the same cores just get in each other's way.
+
+use rayon;
+use rayon::prelude::*;
+use bigint::BigUint;
+
+// Below this many factors, `par_product_tree` multiplies sequentially; smaller tasks are not worth
+// the overhead of splitting.
+const PRODUCT_LEAF_SIZE: usize = 64;
+
+/// Add up the numbers in parallel.
+pub fn par_sum_big(numbers: &[BigUint]) -> BigUint {
+    numbers.par_iter()
+        .fold(BigUint::zero, |sum, n| sum + n)
+        .reduce(BigUint::zero, |a, b| a + b)
+}
+
+/// Multiply the numbers in parallel. The product is computed as a balanced tree, so the factors of
+/// every multiplication have about the same size, which is much faster than multiplying a growing
+/// product by one number after the other.
+pub fn par_product_tree(numbers: &[BigUint]) -> BigUint {
+    if numbers.len() <= PRODUCT_LEAF_SIZE {
+        return numbers.iter().product();
+    }
+    let (left, right) = numbers.split_at(numbers.len() / 2);
+    let (a, b) = rayon::join(|| par_product_tree(left), || par_product_tree(right));
+    a * b
+}
+
+#[cfg(test)]
+mod tests {
+    use bigint::BigUint;
+    use super::{par_sum_big, par_product_tree};
+
+    #[test]
+    fn test_par_sum_big() {
+        assert_eq!(par_sum_big(&[]), BigUint::zero());
+        let numbers: Vec<BigUint> = (0..1000).map(BigUint::power_of_2).collect();
+        assert_eq!(par_sum_big(&numbers), BigUint::power_of_2(1000) - BigUint::one());
+    }
+
+    #[test]
+    fn test_par_product_tree() {
+        assert_eq!(par_product_tree(&[]), BigUint::one());
+        let numbers: Vec<BigUint> = (1..1001).map(BigUint::new).collect();
+        assert_eq!(par_product_tree(&numbers), BigUint::factorial(1000));
+        assert_eq!(par_product_tree(&numbers[..3]), BigUint::new(6));
+    }
+}
@@ -0,0 +1,12 @@
+// The items that are used all over the place, so that code using this crate can get them with a
+// single `use solutions::prelude::*;` instead of going through the module paths, which keep
+// changing as the course grows.
+
+pub use bigint::{BigInt, BigUint};
+#[cfg(feature = "std")]
+pub use vec::part0203::{SomethingOrNothing, Something, Nothing, Minimum, Maximum, MinimumExt, fold_min, fold_max};
+#[cfg(feature = "std")]
+pub use callbacks::{Callbacks, SyncCallbacks};
+#[cfg(feature = "std")]
+pub use rgrep::Options as GrepOptions;
+pub use list::LinkedList;
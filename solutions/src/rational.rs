@@ -0,0 +1,540 @@
+// Fractions of big integers. A `BigRational` is a pair of a numerator and a denominator, which we
+// always keep in lowest terms: The two have no common factor, and the denominator is positive. So
+// every number has exactly one representation, and comparing for equality can just compare the
+// parts. The price is a `gcd` after every operation.
+//
+// The sign is in the numerator, which is a `BigInt`. The denominator is a `BigUint`, since it is
+// never negative anyway.
+
+#[cfg(not(feature = "std"))]
+use std::prelude::v1::*;
+use std::cmp;
+use std::fmt;
+use std::iter;
+use std::ops;
+use std::str::FromStr;
+use bigint::{BigInt, BigUint, ParseBigIntError, ParseErrorKind, Sign};
+use binary::{self, Binary, DecodeError, Reader};
+
+/// A fraction of big integers, in lowest terms.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BigRational {
+    numer: BigInt,
+    denom: BigUint,
+}
+
+impl BigRational {
+    /// Construct the fraction `numer / denom`, and reduce it to lowest terms. Panics if `denom` is 0.
+    pub fn new(numer: BigInt, denom: BigInt) -> Self {
+        assert!(!denom.is_zero(), "BigRational with denominator 0");
+        let (denom_sign, denom) = denom.into_parts();
+        let (sign, numer) = numer.into_parts();
+        BigRational::reduced(sign * denom_sign, numer, denom)
+    }
+
+    // Construct `sign * numer / denom` from a non-zero `denom`, reducing it to lowest terms.
+    fn reduced(sign: Sign, numer: BigUint, denom: BigUint) -> Self {
+        let g = numer.gcd(&denom);
+        if g.is_one() {
+            BigRational { numer: BigInt::from_biguint(sign, numer), denom }
+        } else {
+            BigRational { numer: BigInt::from_biguint(sign, numer / &g), denom: denom / &g }
+        }
+    }
+
+    pub fn from_integer(n: BigInt) -> Self {
+        BigRational { numer: n, denom: BigUint::one() }
+    }
+
+    pub fn zero() -> Self {
+        BigRational::from_integer(BigInt::zero())
+    }
+
+    pub fn one() -> Self {
+        BigRational::from_integer(BigInt::one())
+    }
+
+    pub fn numer(&self) -> &BigInt {
+        &self.numer
+    }
+
+    pub fn denom(&self) -> &BigUint {
+        &self.denom
+    }
+
+    /// Return the numerator and the denominator.
+    pub fn into_parts(self) -> (BigInt, BigUint) {
+        (self.numer, self.denom)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.numer.is_zero()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.numer.is_negative()
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.denom.is_one()
+    }
+
+    pub fn abs(&self) -> BigRational {
+        BigRational { numer: self.numer.abs(), denom: self.denom.clone() }
+    }
+
+    /// Round down (towards negative infinity) to an integer.
+    pub fn floor(&self) -> BigInt {
+        // The division rounds towards 0, which is up for negative numbers.
+        let (q, r) = self.numer.div_rem(&BigInt::from(self.denom.clone()));
+        if r.is_negative() { q - BigInt::one() } else { q }
+    }
+
+    /// Round towards 0 to an integer, that is, drop the fractional part.
+    pub fn trunc(&self) -> BigInt {
+        self.numer.div_rem(&BigInt::from(self.denom.clone())).0
+    }
+
+    /// Round up (towards positive infinity) to an integer.
+    pub fn ceil(&self) -> BigInt {
+        let (q, r) = self.numer.div_rem(&BigInt::from(self.denom.clone()));
+        if r.is_positive() { q + BigInt::one() } else { q }
+    }
+
+    /// Return `1 / self`. Panics if the number is 0.
+    pub fn recip(&self) -> BigRational {
+        assert!(!self.is_zero(), "Division of BigRational by zero");
+        // Swapping the parts keeps them in lowest terms; the sign stays with the numerator.
+        BigRational {
+            numer: BigInt::from_biguint(self.numer.sign(), self.denom.clone()),
+            denom: self.numer.magnitude().clone(),
+        }
+    }
+
+    pub fn pow(&self, exp: u32) -> BigRational {
+        // Powers of coprime numbers are still coprime.
+        BigRational { numer: self.numer.pow(exp), denom: self.denom.pow(exp) }
+    }
+
+    /// Parse a fraction like `-3/4`, or an integer like `5`, in the given base. Both parts are parsed
+    /// like a `BigInt`, and the errors point into the complete input. The result is reduced to lowest
+    /// terms.
+    pub fn from_str_radix(s: &str, radix: u32) -> Result<BigRational, ParseBigIntError> {
+        let (numer, denom_start) = match s.find('/') {
+            Some(pos) => (&s[..pos], pos + 1),
+            None => return BigInt::from_str_radix(s, radix).map(BigRational::from_integer),
+        };
+        let numer = BigInt::from_str_radix(numer, radix).map_err(|e| e.within(s, 0))?;
+        let denom = BigInt::from_str_radix(&s[denom_start..], radix).map_err(|e| e.within(s, denom_start))?;
+        if denom.is_zero() {
+            let rest = s[denom_start..].trim_start();
+            let offset = s.len() - rest.len();
+            return Err(ParseBigIntError::new(ParseErrorKind::ZeroDenominator, s, offset, rest.trim_end().len(), None));
+        }
+        Ok(BigRational::new(numer, denom))
+    }
+}
+
+impl Default for BigRational {
+    fn default() -> Self {
+        BigRational::zero()
+    }
+}
+
+impl From<BigInt> for BigRational {
+    fn from(n: BigInt) -> Self {
+        BigRational::from_integer(n)
+    }
+}
+
+impl From<BigUint> for BigRational {
+    fn from(n: BigUint) -> Self {
+        BigRational::from_integer(BigInt::from(n))
+    }
+}
+
+impl From<i64> for BigRational {
+    fn from(n: i64) -> Self {
+        BigRational::from_integer(BigInt::new(n))
+    }
+}
+
+impl fmt::Display for BigRational {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(&format!("{}/{}", self.numer, self.denom))
+    }
+}
+
+impl FromStr for BigRational {
+    type Err = ParseBigIntError;
+    /// Parse a number in base 10.
+    fn from_str(s: &str) -> Result<BigRational, ParseBigIntError> {
+        BigRational::from_str_radix(s, 10)
+    }
+}
+
+impl Ord for BigRational {
+    fn cmp(&self, other: &BigRational) -> cmp::Ordering {
+        // `a/b < c/d` exactly if `a*d < c*b`, since the denominators are positive.
+        if self.denom == other.denom {
+            return self.numer.cmp(&other.numer);
+        }
+        (&self.numer * &BigInt::from(other.denom.clone())).cmp(&(&other.numer * &BigInt::from(self.denom.clone())))
+    }
+}
+
+impl PartialOrd for BigRational {
+    #[inline]
+    fn partial_cmp(&self, other: &BigRational) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl ops::Neg for BigRational {
+    type Output = BigRational;
+    fn neg(self) -> BigRational {
+        BigRational { numer: -self.numer, denom: self.denom }
+    }
+}
+
+impl ops::Neg for &BigRational {
+    type Output = BigRational;
+    fn neg(self) -> BigRational {
+        -self.clone()
+    }
+}
+
+// Compute `a + b` from the numerators and denominators, for subtraction as well as for addition.
+fn add_parts(n1: &BigInt, d1: &BigUint, n2: &BigInt, d2: &BigUint) -> BigRational {
+    let (numer, denom) = if d1 == d2 {
+        (n1 + n2, d1.clone())
+    } else {
+        (n1 * &BigInt::from(d2.clone()) + n2 * &BigInt::from(d1.clone()), d1 * d2)
+    };
+    let (sign, numer) = numer.into_parts();
+    BigRational::reduced(sign, numer, denom)
+}
+
+impl ops::Add<&BigRational> for &BigRational {
+    type Output = BigRational;
+    fn add(self, rhs: &BigRational) -> BigRational {
+        add_parts(&self.numer, &self.denom, &rhs.numer, &rhs.denom)
+    }
+}
+
+impl ops::Sub<&BigRational> for &BigRational {
+    type Output = BigRational;
+    fn sub(self, rhs: &BigRational) -> BigRational {
+        add_parts(&self.numer, &self.denom, &-&rhs.numer, &rhs.denom)
+    }
+}
+
+// Compute `(n1 / d1) * (n2 / d2)` for fractions in lowest terms, with `sign` the sign of the result.
+// Cancelling crosswise before multiplying keeps the numbers small, and the result is then already in
+// lowest terms.
+fn multiply(sign: Sign, n1: &BigUint, d1: &BigUint, n2: &BigUint, d2: &BigUint) -> BigRational {
+    let g1 = n1.gcd(d2);
+    let g2 = n2.gcd(d1);
+    if g1.is_zero() || g2.is_zero() {
+        // One of the numerators is 0 (the denominators are not).
+        return BigRational::zero();
+    }
+    BigRational { numer: BigInt::from_biguint(sign, (n1 / &g1) * (n2 / &g2)), denom: (d1 / &g2) * (d2 / &g1) }
+}
+
+impl ops::Mul<&BigRational> for &BigRational {
+    type Output = BigRational;
+    fn mul(self, rhs: &BigRational) -> BigRational {
+        let sign = self.numer.sign() * rhs.numer.sign();
+        multiply(sign, self.numer.magnitude(), &self.denom, rhs.numer.magnitude(), &rhs.denom)
+    }
+}
+
+impl ops::Div<&BigRational> for &BigRational {
+    type Output = BigRational;
+    fn div(self, rhs: &BigRational) -> BigRational {
+        assert!(!rhs.is_zero(), "Division of BigRational by zero");
+        let sign = self.numer.sign() * rhs.numer.sign();
+        multiply(sign, self.numer.magnitude(), &self.denom, &rhs.denom, rhs.numer.magnitude())
+    }
+}
+
+// The remainder of the division that rounds the quotient towards 0, so it has the sign of `self`, like
+// for `BigInt`.
+impl ops::Rem<&BigRational> for &BigRational {
+    type Output = BigRational;
+    fn rem(self, rhs: &BigRational) -> BigRational {
+        let quotient = BigRational::from_integer((self / rhs).trunc());
+        self - &(rhs * &quotient)
+    }
+}
+
+// Like for `BigUint`, the variants taking their arguments by value go through the references.
+forward_binop_by_value!(BigRational, impl Add, add);
+forward_binop_by_value!(BigRational, impl Sub, sub);
+forward_binop_by_value!(BigRational, impl Mul, mul);
+forward_binop_by_value!(BigRational, impl Div, div);
+forward_binop_by_value!(BigRational, impl Rem, rem);
+
+impl iter::Sum for BigRational {
+    fn sum<I: Iterator<Item = BigRational>>(iter: I) -> BigRational {
+        iter.fold(BigRational::zero(), |acc, x| acc + x)
+    }
+}
+
+impl iter::Product for BigRational {
+    fn product<I: Iterator<Item = BigRational>>(iter: I) -> BigRational {
+        iter.fold(BigRational::one(), |acc, x| acc * x)
+    }
+}
+
+// In the binary format, a fraction is its numerator followed by its denominator, as nested values.
+impl Binary for BigRational {
+    const TAG: u8 = binary::tag::BIGRATIONAL;
+
+    fn encode_payload(&self, out: &mut Vec<u8>) {
+        binary::write_nested(out, &self.numer);
+        binary::write_nested(out, &self.denom);
+    }
+
+    fn decode_payload(input: &mut Reader) -> Result<Self, DecodeError> {
+        let numer: BigInt = input.read_nested()?;
+        let denom: BigUint = input.read_nested()?;
+        if denom.is_zero() {
+            return Err(DecodeError::Invalid("denominator 0"));
+        }
+        if !numer.magnitude().gcd(&denom).is_one() {
+            return Err(DecodeError::Invalid("fraction not in lowest terms"));
+        }
+        Ok(BigRational { numer, denom })
+    }
+}
+
+#[cfg(feature = "num")]
+mod num_impl {
+    use num_traits::{Num, One, Signed, Zero};
+    use bigint::ParseBigIntError;
+    use super::BigRational;
+
+    impl Zero for BigRational {
+        fn zero() -> Self {
+            BigRational::zero()
+        }
+
+        fn is_zero(&self) -> bool {
+            BigRational::is_zero(self)
+        }
+    }
+
+    impl One for BigRational {
+        fn one() -> Self {
+            BigRational::one()
+        }
+    }
+
+    impl Num for BigRational {
+        type FromStrRadixErr = ParseBigIntError;
+
+        fn from_str_radix(src: &str, radix: u32) -> Result<Self, ParseBigIntError> {
+            BigRational::from_str_radix(src, radix)
+        }
+    }
+
+    impl Signed for BigRational {
+        fn abs(&self) -> Self {
+            BigRational::abs(self)
+        }
+
+        fn abs_sub(&self, other: &Self) -> Self {
+            if self <= other { BigRational::zero() } else { self - other }
+        }
+
+        fn signum(&self) -> Self {
+            BigRational::from_integer(self.numer.signum())
+        }
+
+        fn is_positive(&self) -> bool {
+            self.numer.is_positive()
+        }
+
+        fn is_negative(&self) -> bool {
+            BigRational::is_negative(self)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use std::prelude::v1::*;
+    use bigint::{BigInt, BigUint, ParseErrorKind};
+    #[cfg(feature = "std")]
+    use std::fmt::Write;
+    use binary::{decode, encode, DecodeError};
+    #[cfg(feature = "std")]
+    use snapshot::assert_snapshot;
+    use super::BigRational;
+
+    fn ratio(numer: i64, denom: i64) -> BigRational {
+        BigRational::new(BigInt::new(numer), BigInt::new(denom))
+    }
+
+    #[test]
+    fn test_lowest_terms() {
+        let r = ratio(6, 8);
+        assert_eq!((r.numer(), r.denom()), (&BigInt::new(3), &BigUint::new(4)));
+        let r = ratio(6, -8);
+        assert_eq!((r.numer(), r.denom()), (&BigInt::new(-3), &BigUint::new(4)));
+        assert_eq!(ratio(-6, -8), ratio(3, 4));
+        assert_eq!(ratio(0, 5), BigRational::zero());
+        assert_eq!(ratio(0, 5).denom(), &BigUint::one());
+        assert_eq!(ratio(10, 5), BigRational::from(2));
+        assert!(ratio(10, 5).is_integer() && !ratio(10, 4).is_integer());
+        let big = BigInt::factorial(30);
+        assert_eq!(BigRational::new(&big * &BigInt::new(7), &big * &BigInt::new(-21)), ratio(-1, 3));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_zero_denominator() {
+        ratio(1, 0);
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(ratio(1, 2) + ratio(1, 3), ratio(5, 6));
+        assert_eq!(ratio(1, 6) + ratio(1, 6), ratio(1, 3));
+        assert_eq!(ratio(1, 2) - ratio(1, 3), ratio(1, 6));
+        assert_eq!(ratio(3, 4) - ratio(3, 4), BigRational::zero());
+        assert_eq!(ratio(1, 3) - ratio(1, 2), ratio(-1, 6));
+        assert_eq!(ratio(-1, 3) + ratio(1, 2), ratio(1, 6));
+        assert_eq!(ratio(-1, 4) - ratio(-1, 4), BigRational::zero());
+        assert_eq!(ratio(-2, 3) * ratio(9, -4), ratio(3, 2));
+        assert_eq!(ratio(2, 3) / ratio(-4, 9), ratio(-3, 2));
+        assert_eq!(-ratio(2, 3), ratio(-2, 3));
+        assert_eq!(-&ratio(-2, 3), ratio(2, 3));
+        assert_eq!(ratio(-2, 3).abs(), ratio(2, 3));
+        assert_eq!(ratio(-2, 3).recip(), ratio(-3, 2));
+        assert_eq!(ratio(-2, 3).pow(3), ratio(-8, 27));
+        assert_eq!(ratio(7, 2) % ratio(1, 1), ratio(1, 2));
+        assert_eq!(ratio(-7, 2) % ratio(2, 3), ratio(-1, 6));
+        assert_eq!(ratio(7, 2) % ratio(-2, 3), ratio(1, 6));
+        assert_eq!((ratio(-7, 2).trunc(), ratio(7, 2).trunc()), (BigInt::new(-3), BigInt::new(3)));
+        assert_eq!(ratio(2, 3) * ratio(9, 4), ratio(3, 2));
+        assert_eq!(ratio(2, 3) * BigRational::zero(), BigRational::zero());
+        assert_eq!(ratio(2, 3) / ratio(4, 9), ratio(3, 2));
+        assert_eq!(&ratio(2, 3) + &ratio(1, 3), BigRational::one());
+        assert_eq!(ratio(2, 3).recip(), ratio(3, 2));
+        assert_eq!(ratio(2, 3).pow(3), ratio(8, 27));
+        // The harmonic number H_20.
+        let h: BigRational = (1..21).map(|n| ratio(1, n)).sum();
+        assert_eq!(h.to_string(), "55835135/15519504");
+        let p: BigRational = (1..10).map(|n| ratio(n, n + 1)).product();
+        assert_eq!(p, ratio(1, 10));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_div_by_zero() {
+        let _ = ratio(1, 3) / BigRational::zero();
+    }
+
+    #[test]
+    fn test_ordering() {
+        assert!(ratio(1, 3) < ratio(1, 2));
+        assert!(ratio(2, 3) > ratio(3, 5));
+        assert!(ratio(5, 7) > ratio(4, 7));
+        assert!(ratio(-1, 2) < ratio(-1, 3) && ratio(-1, 2) < BigRational::zero());
+        assert!(ratio(-5, 7) < ratio(-4, 7));
+        assert_eq!(ratio(2, 4).cmp(&ratio(1, 2)), ::std::cmp::Ordering::Equal);
+        assert_eq!((ratio(7, 2).floor(), ratio(7, 2).ceil()), (BigInt::new(3), BigInt::new(4)));
+        assert_eq!((ratio(-7, 2).floor(), ratio(-7, 2).ceil()), (BigInt::new(-4), BigInt::new(-3)));
+        assert_eq!((ratio(6, 2).floor(), ratio(6, 2).ceil()), (BigInt::new(3), BigInt::new(3)));
+        assert_eq!((ratio(-6, 2).floor(), ratio(-6, 2).ceil()), (BigInt::new(-3), BigInt::new(-3)));
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(ratio(3, 4).to_string(), "3/4");
+        assert_eq!(BigRational::from(5).to_string(), "5/1");
+        assert_eq!(ratio(-3, 4).to_string(), "-3/4");
+        let r = BigRational::new(BigInt::one(), BigInt::from(BigUint::power_of_2(64)));
+        assert_eq!(r.to_string(), "1/18446744073709551616");
+        assert_eq!(format!("[{:>6}|{:<6}|{:^7}]", ratio(3, 4), ratio(-3, 4), ratio(1, 2)), "[   3/4|-3/4  |  1/2  ]");
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_format_snapshot() {
+        let values = [
+            BigRational::zero(),
+            ratio(3, 4),
+            ratio(-3, 4),
+            ratio(10, 5),
+            ratio(-7, 1),
+            ratio(1, i64::MAX),
+            BigRational::new(BigInt::one(), BigInt::from(BigUint::power_of_2(64))),
+            BigRational::new(-BigInt::from(BigUint::power_of_2(100)) + BigInt::one(), BigInt::factorial(40)),
+            BigRational::new(BigInt::fibonacci(200), BigInt::fibonacci(199)),
+        ];
+        let mut out = String::new();
+        for r in values.iter() {
+            writeln!(out, "{}", r).unwrap();
+            writeln!(out, "  numer {:?}", r.numer()).unwrap();
+            writeln!(out, "  denom {:?}", r.denom()).unwrap();
+        }
+        assert_snapshot("rational_format", &out);
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("6/8".parse::<BigRational>(), Ok(ratio(3, 4)));
+        assert_eq!(" -6 / 8 ".parse::<BigRational>(), Ok(ratio(-3, 4)));
+        assert_eq!("3/-4".parse::<BigRational>(), Ok(ratio(-3, 4)));
+        assert_eq!("-5".parse::<BigRational>(), Ok(ratio(-5, 1)));
+        let err = |s: &str| s.parse::<BigRational>().unwrap_err();
+        let e = err("1/2/3");
+        assert_eq!((e.offset(), e.snippet(), e.input()), (3, "/", "1/2/3"));
+        let e = err("12/0x1f");
+        assert_eq!((e.offset(), e.suggestion()), (4, Some("drop the '0x' prefix and parse in base 16")));
+        let e = err("1x/2");
+        assert_eq!((e.offset(), e.snippet()), (1, "x"));
+        let e = err("3/ 00 ");
+        assert_eq!((e.kind(), e.offset(), e.snippet()), (&ParseErrorKind::ZeroDenominator, 3, "00"));
+        assert_eq!(e.to_string(), "zero denominator at byte 3\n  |\n  | 3/ 00 \n  |    ^^");
+        assert_eq!((err("1/").kind(), err("1/").offset()), (&ParseErrorKind::Empty, 2));
+        assert_eq!(err("").kind(), &ParseErrorKind::Empty);
+    }
+
+    #[cfg(feature = "num")]
+    #[test]
+    fn test_num_traits() {
+        use num_traits::{Num, Signed};
+        // Generic code that knows nothing about `BigRational`.
+        fn mean<T: Num + Clone>(xs: &[T]) -> T {
+            let (sum, n) = xs.iter().fold((T::zero(), T::zero()), |(sum, n), x| (sum + x.clone(), n + T::one()));
+            sum / n
+        }
+        assert_eq!(mean(&[ratio(1, 2), ratio(-1, 3), ratio(1, 1)]), ratio(7, 18));
+        assert_eq!(<BigRational as Num>::from_str_radix("-a/10", 16), Ok(ratio(-5, 8)));
+        assert_eq!(ratio(-3, 4).signum(), ratio(-1, 1));
+        assert_eq!(Signed::abs(&ratio(-3, 4)), ratio(3, 4));
+        assert_eq!(ratio(1, 4).abs_sub(&ratio(1, 2)), BigRational::zero());
+        assert!(ratio(1, 4).is_positive() && !BigRational::zero().is_positive());
+    }
+
+    #[test]
+    fn test_binary() {
+        let big = BigRational::new(-BigInt::factorial(25), BigInt::from(BigUint::power_of_2(90)));
+        for r in [BigRational::zero(), ratio(3, 4), ratio(-3, 4), big].iter() {
+            assert_eq!(decode::<BigRational>(&encode(r)), Ok(r.clone()));
+        }
+        let mut data = encode(&ratio(2, 3));
+        // The last digit is the one of the denominator.
+        let last = data.len() - 8;
+        data[last] = 4;
+        assert_eq!(decode::<BigRational>(&data), Err(DecodeError::Invalid("fraction not in lowest terms")));
+        data[last] = 0;
+        assert_eq!(decode::<BigRational>(&data), Err(DecodeError::Invalid("leading zero digit")));
+    }
+}
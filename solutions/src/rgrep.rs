@@ -1,58 +1,350 @@
 use std::io::prelude::*;
 use std::{io, fs, thread, process, cmp};
 use std::sync::mpsc::{sync_channel, SyncSender, Receiver};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::ops::Range;
+use std::collections::BTreeMap;
+use std::fmt;
+use bigint::BigUint;
 
+/// What to do with the matching lines.
 #[derive(Clone,Copy)]
-enum OutputMode {
+pub enum OutputMode {
     Print,
     SortAndPrint,
     Count,
+    NumberStats,
 }
 use self::OutputMode::*;
 
-struct Options {
-    files: Vec<String>,
-    pattern: String,
-    output_mode: OutputMode,
+/// How the pattern is matched against the lines.
+#[derive(Clone,Copy,PartialEq,Eq,Debug)]
+pub enum MatchMode {
+    /// The line contains the pattern as it is.
+    Substring,
+    /// The pattern is a regular expression, in the syntax of the `regex` crate.
+    #[cfg(feature = "regex")]
+    Regex,
 }
 
+/// The configuration of a run of rgrep, usually obtained from the command-line.
+pub struct Options {
+    pub files: Vec<String>,
+    pub pattern: String,
+    pub match_mode: MatchMode,
+    pub output_mode: OutputMode,
+    /// Highlight the match when printing a line.
+    pub highlight: bool,
+    /// Record the lines passing through the pipeline to this log.
+    pub record: Option<String>,
+    /// Take the input lines from this log instead of reading `files`.
+    pub replay: Option<String>,
+}
+
+// A line does not own its text. Instead, it points into a chunk of the file, which is shared by all
+// the lines in that chunk. That saves allocating a `String` for every line.
 struct Line {
-    data: String,
+    chunk: Arc<[u8]>,
+    range: Range<usize>,
     file: usize,
     line: usize,
+    // Where in `data` the pattern matched, once the line passed the filter.
+    matched: Range<usize>,
+}
+
+impl Line {
+    // Create a line that has a chunk all to itself.
+    fn new(data: &[u8], file: usize, line: usize) -> Line {
+        Line { chunk: data.into(), range: 0..data.len(), file, line, matched: 0..0 }
+    }
+
+    // The text of the line, without the line break.
+    fn data(&self) -> &[u8] {
+        &self.chunk[self.range.clone()]
+    }
 }
 
 impl PartialEq for Line {
     fn eq(&self, other: &Line) -> bool {
-        self.data.eq(&other.data)
+        self.data().eq(other.data())
     }
 }
 impl PartialOrd for Line {
     fn partial_cmp(&self, other: &Line) -> Option<cmp::Ordering> {
-        self.data.partial_cmp(&other.data)
+        self.data().partial_cmp(other.data())
+    }
+}
+
+// How much we read from a file at once.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+// Read `input` in chunks of (about) `chunk_size` bytes, and send out the lines in there. A chunk must
+// end at a line break, so we carry over what comes after the last one into the next chunk.
+fn read_lines<R: Read>(mut input: R, chunk_size: usize, file: usize, out_channel: &SyncSender<Line>) -> io::Result<()> {
+    let mut line = 0;
+    let mut pending: Vec<u8> = Vec::new();
+    loop {
+        let start = pending.len();
+        pending.resize(start + chunk_size, 0);
+        let read = match input.read(&mut pending[start..]) {
+            Ok(read) => read,
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {
+                pending.truncate(start);
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        pending.truncate(start + read);
+        let eof = read == 0 && pending.len() == start;
+        let end = if eof {
+            // At the end of the file, the last line does not need to end in a line break.
+            pending.len()
+        } else {
+            match pending[start..].iter().rposition(|&b| b == b'\n') {
+                Some(pos) => start + pos + 1,
+                None => continue,
+            }
+        };
+        let rest = pending.split_off(end);
+        let chunk: Arc<[u8]> = pending.into();
+        pending = rest;
+        let mut line_start = 0;
+        while line_start < chunk.len() {
+            let line_end = chunk[line_start..].iter().position(|&b| b == b'\n').map_or(chunk.len(), |pos| line_start + pos);
+            // Like `BufRead::lines`, we also remove the `\r` of a Windows line break.
+            let data_end = if line_end > line_start && chunk[line_end - 1] == b'\r' { line_end - 1 } else { line_end };
+            out_channel.send(Line { chunk: chunk.clone(), range: line_start..data_end, file, line, matched: 0..0 }).unwrap();
+            line += 1;
+            line_start = line_end + 1;
+        }
+        if eof {
+            return Ok(());
+        }
     }
 }
 
 fn read_files(options: Arc<Options>, out_channel: SyncSender<Line>) {
     for (fileidx, file) in options.files.iter().enumerate() {
         let file = fs::File::open(file).unwrap();
-        let file = io::BufReader::new(file);
-        for (lineidx, line) in file.lines().enumerate() {
-            let line = Line { data: line.unwrap(), file: fileidx, line: lineidx };
+        read_lines(file, CHUNK_SIZE, fileidx, &out_channel).unwrap();
+    }
+}
+
+// Find the first occurrence of `needle` in `haystack`.
+fn find(haystack: &[u8], needle: &[u8]) -> Option<Range<usize>> {
+    if needle.is_empty() {
+        return Some(0..0);
+    }
+    haystack.windows(needle.len()).position(|window| window == needle).map(|start| start..start + needle.len())
+}
+
+// The pattern, prepared for matching.
+enum Matcher {
+    Substring(Vec<u8>),
+    #[cfg(feature = "regex")]
+    Regex(regex::bytes::Regex),
+}
+
+impl Matcher {
+    fn new(options: &Options) -> Result<Matcher, String> {
+        match options.match_mode {
+            MatchMode::Substring => Ok(Matcher::Substring(options.pattern.as_bytes().to_vec())),
+            #[cfg(feature = "regex")]
+            MatchMode::Regex => regex::bytes::Regex::new(&options.pattern).map(Matcher::Regex).map_err(|e| e.to_string()),
+        }
+    }
+
+    // The range of the first match in `data`, if there is one.
+    fn find(&self, data: &[u8]) -> Option<Range<usize>> {
+        match *self {
+            Matcher::Substring(ref pattern) => find(data, pattern),
+            #[cfg(feature = "regex")]
+            Matcher::Regex(ref regex) => regex.find(data).map(|m| m.range()),
+        }
+    }
+
+    // Check the line, and remember the match in it.
+    fn matches(&self, line: &mut Line) -> bool {
+        match self.find(line.data()) {
+            Some(range) => {
+                line.matched = range;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+// The pattern was checked by `run` already.
+fn matcher(options: &Options) -> Matcher {
+    Matcher::new(options).expect("rgrep: invalid pattern")
+}
+
+#[cfg(not(feature = "rayon"))]
+fn filter_lines(options: Arc<Options>, in_channel: Receiver<Line>, out_channel: SyncSender<Line>) {
+    let matcher = matcher(&options);
+    for mut line in in_channel.iter() {
+        if matcher.matches(&mut line) {
             out_channel.send(line).unwrap();
         }
     }
 }
 
+// How many lines the parallel filter checks at once.
+#[cfg(feature = "rayon")]
+const FILTER_BATCH_SIZE: usize = 1024;
+
+// With rayon, the filter collects the lines into batches, and checks all lines of a batch on rayon's
+// thread pool. The matching lines are sent on in their original order.
+#[cfg(feature = "rayon")]
 fn filter_lines(options: Arc<Options>, in_channel: Receiver<Line>, out_channel: SyncSender<Line>) {
-    for line in in_channel.iter() {
-        if line.data.contains(&options.pattern) {
+    use rayon::prelude::*;
+    let matcher = matcher(&options);
+    let mut lines = in_channel.iter().peekable();
+    while lines.peek().is_some() {
+        let batch: Vec<Line> = lines.by_ref().take(FILTER_BATCH_SIZE).collect();
+        let matching: Vec<Line> = batch.into_par_iter()
+            .filter_map(|mut line| if matcher.matches(&mut line) { Some(line) } else { None })
+            .collect();
+        for line in matching {
             out_channel.send(line).unwrap();
         }
     }
 }
 
+// For debugging ordering problems, all messages passed between the stages can be recorded into a log,
+// and the lines read from the files can later be fed back into the pipeline from that log. The log
+// is a text file: The first line lists the input files, every further line is one message. All
+// fields are separated by tabs, and escaped so that they do not contain any tabs or newlines.
+//
+//     files   <file>   <file> ...
+//     <channel>   <sequence number>   <file index>   <line number>   <data>
+
+// The channel from `read_files` to `filter_lines`, which is where replaying feeds in.
+const INPUT_CHANNEL: &str = "lines";
+// The channel from `filter_lines` to `output_lines`.
+const FILTERED_CHANNEL: &str = "filtered";
+
+type Log = Arc<Mutex<io::BufWriter<fs::File>>>;
+
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn unescape(s: &str) -> Result<String, String> {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        out.push(match chars.next() {
+            Some('\\') => '\\',
+            Some('t') => '\t',
+            Some('n') => '\n',
+            Some('r') => '\r',
+            other => return Err(format!("Invalid escape sequence {:?}", other)),
+        });
+    }
+    Ok(out)
+}
+
+fn format_entry(channel: &str, seq: usize, line: &Line) -> String {
+    format!("{}\t{}\t{}\t{}\t{}", channel, seq, line.file, line.line, escape(&String::from_utf8_lossy(line.data())))
+}
+
+fn parse_entry(entry: &str) -> Result<(String, usize, Line), String> {
+    let fields: Vec<&str> = entry.splitn(5, '\t').collect();
+    if fields.len() != 5 {
+        return Err(format!("Expected 5 fields, found {}", fields.len()));
+    }
+    let number = |field: &str| field.parse::<usize>().map_err(|e| format!("Invalid number {:?}: {}", field, e));
+    let line = Line::new(unescape(fields[4])?.as_bytes(), number(fields[2])?, number(fields[3])?);
+    Ok((unescape(fields[0])?, number(fields[1])?, line))
+}
+
+fn create_log(path: &str, files: &[String]) -> io::Result<Log> {
+    let mut log = io::BufWriter::new(fs::File::create(path)?);
+    let files: Vec<String> = files.iter().map(|file| escape(file)).collect();
+    writeln!(log, "files\t{}", files.join("\t"))?;
+    Ok(Arc::new(Mutex::new(log)))
+}
+
+// Read the input files and the messages of the given channel from a log.
+fn read_log(path: &str, channel: &str) -> io::Result<(Vec<String>, Vec<Line>)> {
+    let invalid = |lineidx: usize, msg: String| {
+        io::Error::new(io::ErrorKind::InvalidData, format!("{}:{}: {}", path, lineidx + 1, msg))
+    };
+    let file = io::BufReader::new(fs::File::open(path)?);
+    let mut entries = file.lines().enumerate();
+    let files = match entries.next() {
+        Some((_, header)) => {
+            let header = header?;
+            let mut fields = header.split('\t');
+            if fields.next() != Some("files") {
+                return Err(invalid(0, "Missing list of files".to_string()));
+            }
+            fields.map(unescape).collect::<Result<Vec<_>, _>>().map_err(|e| invalid(0, e))?
+        }
+        None => return Err(invalid(0, "Empty log".to_string())),
+    };
+    let mut lines = Vec::new();
+    for (lineidx, entry) in entries {
+        let (entry_channel, seq, line) = parse_entry(&entry?).map_err(|e| invalid(lineidx, e))?;
+        if entry_channel != channel {
+            continue;
+        }
+        // The messages of one channel are logged in order, so a gap means that the log is broken.
+        if seq != lines.len() {
+            return Err(invalid(lineidx, format!("Expected message {} of channel {}, found {}", lines.len(), channel, seq)));
+        }
+        lines.push(line);
+    }
+    Ok((files, lines))
+}
+
+// Pass on all messages, and log them.
+fn record_channel(name: &'static str, log: Log, in_channel: Receiver<Line>, out_channel: SyncSender<Line>) {
+    for (seq, line) in in_channel.iter().enumerate() {
+        writeln!(log.lock().unwrap(), "{}", format_entry(name, seq, &line)).unwrap();
+        out_channel.send(line).unwrap();
+    }
+}
+
+fn replay_lines(lines: Vec<Line>, out_channel: SyncSender<Line>) {
+    for line in lines {
+        out_channel.send(line).unwrap();
+    }
+}
+
+// Create a channel between two stages. When recording, a recorder thread is put in between.
+fn pipeline_channel(name: &'static str, log: &Option<Log>, handles: &mut Vec<JoinHandle<()>>)
+    -> (SyncSender<Line>, Receiver<Line>)
+{
+    let (sender, receiver) = sync_channel(16);
+    match *log {
+        None => (sender, receiver),
+        Some(ref log) => {
+            let (recorded_sender, recorded_receiver) = sync_channel(16);
+            let log = log.clone();
+            handles.push(thread::spawn(move || record_channel(name, log, receiver, recorded_sender)));
+            (sender, recorded_receiver)
+        }
+    }
+}
+
 fn sort<T: PartialOrd>(data: &mut [T]) {
     if data.len() < 2 { return; }
 
@@ -80,11 +372,88 @@ fn sort<T: PartialOrd>(data: &mut [T]) {
     sort(part2);
 }
 
+// Find the numbers in a line: all maximal runs of decimal digits.
+fn extract_numbers(data: &[u8]) -> Vec<BigUint> {
+    data.split(|b| !b.is_ascii_digit())
+        .filter(|digits| !digits.is_empty())
+        .map(|digits| BigUint::from_str_radix(::std::str::from_utf8(digits).unwrap(), 10).unwrap())
+        .collect()
+}
+
+// Exact statistics of the numbers found in the matching lines. The histogram counts how many numbers
+// have how many decimal digits.
+struct Stats {
+    lines: usize,
+    count: usize,
+    sum: BigUint,
+    min: Option<BigUint>,
+    max: Option<BigUint>,
+    histogram: BTreeMap<usize, usize>,
+}
+
+impl Stats {
+    fn new() -> Self {
+        Stats { lines: 0, count: 0, sum: BigUint::zero(), min: None, max: None, histogram: BTreeMap::new() }
+    }
+
+    fn add_line(&mut self, data: &[u8]) {
+        self.lines += 1;
+        for n in extract_numbers(data) {
+            self.count += 1;
+            *self.histogram.entry(n.to_string().len()).or_insert(0) += 1;
+            if self.min.as_ref().is_none_or(|min| n < *min) {
+                self.min = Some(n.clone());
+            }
+            if self.max.as_ref().is_none_or(|max| n > *max) {
+                self.max = Some(n.clone());
+            }
+            self.sum = &self.sum + &n;
+        }
+    }
+}
+
+// The longest bar of the histogram.
+const HISTOGRAM_WIDTH: usize = 40;
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{} numbers in {} lines.", self.count, self.lines)?;
+        if let (Some(min), Some(max)) = (self.min.as_ref(), self.max.as_ref()) {
+            writeln!(f, "sum: {}", self.sum)?;
+            writeln!(f, "min: {}", min)?;
+            writeln!(f, "max: {}", max)?;
+            writeln!(f, "digits  count")?;
+            let most = self.histogram.values().cloned().max().unwrap_or(1);
+            for (digits, &count) in self.histogram.iter() {
+                let bar = (count * HISTOGRAM_WIDTH).div_ceil(most);
+                writeln!(f, "{:>6}  {:>5} {}", digits, count, "#".repeat(bar))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// The colors of a terminal, to highlight the match.
+const HIGHLIGHT_START: &str = "\x1b[1;31m";
+const HIGHLIGHT_END: &str = "\x1b[0m";
+
+fn format_line(options: &Options, line: &Line) -> String {
+    let data = line.data();
+    let text = if options.highlight && !line.matched.is_empty() {
+        format!("{}{}{}{}{}", String::from_utf8_lossy(&data[..line.matched.start]), HIGHLIGHT_START,
+                String::from_utf8_lossy(&data[line.matched.clone()]), HIGHLIGHT_END,
+                String::from_utf8_lossy(&data[line.matched.end..]))
+    } else {
+        String::from_utf8_lossy(data).into_owned()
+    };
+    format!("{}:{}: {}", options.files[line.file], line.line, text)
+}
+
 fn output_lines(options: Arc<Options>, in_channel: Receiver<Line>) {
     match options.output_mode {
         Print => {
             for line in in_channel.iter() {
-                println!("{}:{}: {}", options.files[line.file], line.line, line.data);
+                println!("{}", format_line(&options, &line));
             }
         },
         Count => {
@@ -95,18 +464,32 @@ fn output_lines(options: Arc<Options>, in_channel: Receiver<Line>) {
             let mut data: Vec<Line> = in_channel.iter().collect();
             sort(&mut data[..]);
             for line in data.iter() {
-                println!("{}:{}: {}", options.files[line.file], line.line, line.data);
+                println!("{}", format_line(&options, line));
             }
         }
+        NumberStats => {
+            let mut stats = Stats::new();
+            for line in in_channel.iter() {
+                stats.add_line(line.data());
+            }
+            print!("{}", stats);
+        }
     }
 }
 
 static USAGE: &'static str = "
-Usage: rgrep [-c] [-s] <pattern> <file>...
+Usage:
+    rgrep [-c] [-s] [-n] [-E] [--color] [--record=<log>] <pattern> <file>...
+    rgrep [-c] [-s] [-n] [-E] [--color] --replay=<log> <pattern>
 
 Options:
-    -c, --count  Count number of matching lines (rather than printing them).
-    -s, --sort   Sort the lines before printing.
+    -E, --regex       Match the pattern as a regular expression.
+    --color           Highlight the match in the printed lines.
+    -c, --count       Count number of matching lines (rather than printing them).
+    -s, --sort        Sort the lines before printing.
+    -n, --numbers     Print exact statistics of the numbers in the matching lines.
+    --record=<log>    Record all messages passed between the stages into <log>.
+    --replay=<log>    Instead of reading files, process the lines recorded in <log>.
 ";
 
 fn get_options() -> Options {
@@ -116,39 +499,288 @@ fn get_options() -> Options {
     let args = Docopt::new(USAGE).and_then(|d| d.parse()).unwrap_or_else(|e| e.exit());
     let count = args.get_bool("-c");
     let sort = args.get_bool("-s");
+    let numbers = args.get_bool("-n");
+    let regex = args.get_bool("-E");
     let pattern = args.get_str("<pattern>");
     let files = args.get_vec("<file>");
-    if count && sort {
-        println!("Setting both '-c' and '-s' at the same time does not make any sense.");
+    let record = args.get_str("--record");
+    let replay = args.get_str("--replay");
+    if [count, sort, numbers].iter().filter(|&&flag| flag).count() > 1 {
+        println!("Setting more than one of '-c', '-s' and '-n' at the same time does not make any sense.");
         process::exit(1);
     }
+    let match_mode = if regex { regex_mode() } else { MatchMode::Substring };
 
     // We need to make the strings owned to construct the `Options` instance.
     Options {
         files: files.iter().map(|file| file.to_string()).collect(),
         pattern: pattern.to_string(),
-        output_mode: if count { Count } else if sort { SortAndPrint } else { Print },
+        match_mode,
+        output_mode: if count { Count } else if sort { SortAndPrint } else if numbers { NumberStats } else { Print },
+        record: if record.is_empty() { None } else { Some(record.to_string()) },
+        replay: if replay.is_empty() { None } else { Some(replay.to_string()) },
+        highlight: args.get_bool("--color"),
     }
 }
 
-fn run(options: Options) {
+#[cfg(feature = "regex")]
+fn regex_mode() -> MatchMode {
+    MatchMode::Regex
+}
+
+#[cfg(not(feature = "regex"))]
+fn regex_mode() -> MatchMode {
+    println!("This rgrep was built without regular expressions; enable the 'regex' feature.");
+    process::exit(1);
+}
+
+/// Search the files for the pattern, as configured by `options`.
+pub fn run(mut options: Options) {
+    if let Err(e) = Matcher::new(&options) {
+        println!("Invalid pattern: {}", e);
+        process::exit(1);
+    }
+    // When replaying, the lines and the names of the files they came from are taken from the log.
+    let replayed = match options.replay {
+        Some(ref path) => {
+            let (files, lines) = read_log(path, INPUT_CHANNEL).unwrap_or_else(|e| {
+                println!("Failed to read log: {}", e);
+                process::exit(1);
+            });
+            options.files = files;
+            Some(lines)
+        }
+        None => None,
+    };
+    let log = options.record.as_ref().map(|path| create_log(path, &options.files).unwrap());
     let options = Arc::new(options);
 
     // This sets up the chain of threads. Use `sync_channel` with buffer-size of 16 to avoid needlessly filling RAM.
-    let (line_sender, line_receiver) = sync_channel(16);
-    let (filtered_sender, filtered_receiver) = sync_channel(16);
+    let mut handles = Vec::new();
+    let (line_sender, line_receiver) = pipeline_channel(INPUT_CHANNEL, &log, &mut handles);
+    let (filtered_sender, filtered_receiver) = pipeline_channel(FILTERED_CHANNEL, &log, &mut handles);
+    // The recorders hold on to the log as long as they need it; it is flushed when the last one is done.
+    drop(log);
 
     let options1 = options.clone();
-    let handle1 = thread::spawn(move || read_files(options1, line_sender));
+    handles.push(match replayed {
+        Some(lines) => thread::spawn(move || replay_lines(lines, line_sender)),
+        None => thread::spawn(move || read_files(options1, line_sender)),
+    });
     let options2 = options.clone();
-    let handle2 = thread::spawn(move || filter_lines(options2, line_receiver, filtered_sender));
+    handles.push(thread::spawn(move || filter_lines(options2, line_receiver, filtered_sender)));
     let options3 = options.clone();
-    let handle3 = thread::spawn(move || output_lines(options3, filtered_receiver));
-    handle1.join().unwrap();
-    handle2.join().unwrap();
-    handle3.join().unwrap();
+    handles.push(thread::spawn(move || output_lines(options3, filtered_receiver)));
+    for handle in handles {
+        handle.join().unwrap();
+    }
 }
 
 pub fn main() {
     run(get_options());
 }
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::io::prelude::*;
+    use std::thread;
+    use std::sync::Arc;
+    use std::sync::mpsc::sync_channel;
+    use testfs::TestTree;
+    use super::{Stats, extract_numbers, Options, OutputMode, MatchMode, Matcher, format_line, read_files, filter_lines, Line, find, read_lines, parse_entry, format_entry, create_log, read_log, pipeline_channel, INPUT_CHANNEL, FILTERED_CHANNEL};
+
+    fn line(data: &str, file: usize, line: usize) -> Line {
+        Line::new(data.as_bytes(), file, line)
+    }
+
+    #[test]
+    fn test_entry_roundtrip() {
+        let l = line("tab\there\\, newline\nthere\r", 3, 14);
+        let entry = format_entry("lines", 7, &l);
+        assert!(!entry.contains('\n'));
+        let (channel, seq, parsed) = parse_entry(&entry).unwrap();
+        assert_eq!((channel.as_str(), seq, parsed.file, parsed.line), ("lines", 7, 3, 14));
+        assert_eq!(parsed.data(), l.data());
+        assert!(parse_entry("lines\t1\t2").is_err());
+        assert!(parse_entry("lines\tx\t0\t0\tdata").is_err());
+        assert!(parse_entry("lines\t0\t0\t0\tbad \\q escape").is_err());
+    }
+
+    #[test]
+    fn test_record_and_replay() {
+        let dir = TestTree::new().create();
+        let path = dir.path("rgrep.log");
+        let path = path.to_str().unwrap();
+        let files = vec!["a.txt".to_string(), "with\ttab.txt".to_string()];
+        let log = Some(create_log(path, &files).unwrap());
+        // Send some lines through both recorded channels, filtering out those containing "skip".
+        let mut handles = Vec::new();
+        let (sender, receiver) = pipeline_channel(INPUT_CHANNEL, &log, &mut handles);
+        let (filtered_sender, filtered_receiver) = pipeline_channel(FILTERED_CHANNEL, &log, &mut handles);
+        drop(log);
+        let input = [line("first", 0, 0), line("skip me", 0, 1), line("third\tline", 1, 0)];
+        let sent: Vec<_> = input.iter().map(|l| Line::new(l.data(), l.file, l.line)).collect();
+        handles.push(thread::spawn(move || for l in sent { sender.send(l).unwrap(); }));
+        handles.push(thread::spawn(move || for l in receiver.iter() {
+            if find(l.data(), b"skip").is_none() { filtered_sender.send(l).unwrap(); }
+        }));
+        let output: Vec<Line> = filtered_receiver.iter().collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(output.len(), 2);
+
+        let (replayed_files, replayed) = read_log(path, INPUT_CHANNEL).unwrap();
+        assert_eq!(replayed_files, files);
+        assert_eq!(replayed.len(), 3);
+        for (a, b) in replayed.iter().zip(input.iter()) {
+            assert_eq!((a.data(), a.file, a.line), (b.data(), b.file, b.line));
+        }
+        let (_, filtered) = read_log(path, FILTERED_CHANNEL).unwrap();
+        assert_eq!(filtered.iter().map(|l| l.data()).collect::<Vec<_>>(), vec![&b"first"[..], &b"third\tline"[..]]);
+
+        // A missing message is detected.
+        let contents = fs::read_to_string(path).unwrap();
+        let broken: Vec<&str> = contents.lines().filter(|entry| !entry.starts_with("lines\t1\t")).collect();
+        fs::File::create(path).unwrap().write_all(broken.join("\n").as_bytes()).unwrap();
+        let err = read_log(path, INPUT_CHANNEL).err().expect("The broken log was accepted");
+        assert!(err.to_string().contains("Expected message 1 of channel lines, found 2"), "{}", err);
+    }
+
+    #[test]
+    fn test_read_lines() {
+        let input = "first\n\nthird\r\nfourth line, somewhat longer\nno newline at the end";
+        let expected: Vec<&str> = input.lines().collect();
+        for &chunk_size in [1, 3, 7, 1000].iter() {
+            let (sender, receiver) = sync_channel(1000);
+            read_lines(input.as_bytes(), chunk_size, 2, &sender).unwrap();
+            drop(sender);
+            let lines: Vec<Line> = receiver.iter().collect();
+            let data: Vec<&[u8]> = lines.iter().map(|l| l.data()).collect();
+            assert_eq!(data, expected.iter().map(|l| l.as_bytes()).collect::<Vec<_>>(), "chunk size {}", chunk_size);
+            assert!(lines.iter().enumerate().all(|(i, l)| l.line == i && l.file == 2));
+        }
+        // Lines from the same chunk share it.
+        let (sender, receiver) = sync_channel(10);
+        read_lines(&b"a\nb\n"[..], 1000, 0, &sender).unwrap();
+        drop(sender);
+        let lines: Vec<Line> = receiver.iter().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(Arc::ptr_eq(&lines[0].chunk, &lines[1].chunk));
+    }
+
+    #[test]
+    fn test_find() {
+        assert_eq!(find(b"hello world", b"o w"), Some(4..7));
+        assert_eq!(find(b"hello", b""), Some(0..0));
+        assert_eq!(find(b"hello", b"hello!"), None);
+        assert_eq!(find(b"", b"x"), None);
+    }
+
+    fn options(pattern: &str, match_mode: MatchMode) -> Options {
+        Options {
+            files: vec!["log.txt".to_string()], pattern: pattern.to_string(), match_mode,
+            output_mode: OutputMode::Print, highlight: true, record: None, replay: None,
+        }
+    }
+
+    #[test]
+    fn test_highlight() {
+        let options = options("wor", MatchMode::Substring);
+        let mut l = line("hello world", 0, 3);
+        assert!(Matcher::new(&options).unwrap().matches(&mut l));
+        assert_eq!(format_line(&options, &l), "log.txt:3: hello \x1b[1;31mwor\x1b[0mld");
+        assert!(!Matcher::new(&options).unwrap().matches(&mut line("hello", 0, 0)));
+        // An empty match has nothing to highlight.
+        let options = self::options("", MatchMode::Substring);
+        let mut l = line("hello", 0, 0);
+        assert!(Matcher::new(&options).unwrap().matches(&mut l));
+        assert_eq!(format_line(&options, &l), "log.txt:0: hello");
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn test_regex() {
+        let matcher = Matcher::new(&options(r"\d+ms", MatchMode::Regex)).unwrap();
+        assert_eq!(matcher.find(b"request took 15ms, then 200ms"), Some(13..17));
+        assert_eq!(matcher.find(b"took 15 s"), None);
+        assert_eq!(matcher.find(b"not UTF-8: \xff 7ms"), Some(13..16));
+        // In substring mode, the same pattern is just text.
+        assert_eq!(Matcher::new(&options(r"\d+", MatchMode::Substring)).unwrap().find(b"a\\d+"), Some(1..4));
+        assert!(Matcher::new(&options("(unclosed", MatchMode::Regex)).is_err());
+    }
+
+    #[test]
+    fn test_read_files() {
+        let dir = TestTree::new()
+            .file("a.txt", "one\ntwo\n")
+            .file("sub/b.txt", "three")
+            .symlink("link.txt", "sub/b.txt")
+            .create();
+        let files = ["a.txt", "sub/b.txt", "link.txt"].iter().map(|f| dir.path(f).to_str().unwrap().to_string()).collect();
+        let options = Arc::new(Options {
+            files,
+            pattern: String::new(),
+            match_mode: MatchMode::Substring,
+            output_mode: OutputMode::Print,
+            highlight: false,
+            record: None,
+            replay: None,
+        });
+        let (sender, receiver) = sync_channel(16);
+        let reader = thread::spawn(move || read_files(options, sender));
+        let lines: Vec<(usize, usize, String)> = receiver.iter()
+            .map(|l| (l.file, l.line, String::from_utf8(l.data().to_vec()).unwrap()))
+            .collect();
+        reader.join().unwrap();
+        assert_eq!(lines, vec![(0, 0, "one".to_string()), (0, 1, "two".to_string()),
+                               (1, 0, "three".to_string()), (2, 0, "three".to_string())]);
+    }
+
+    #[test]
+    fn test_filter_lines() {
+        let options = Arc::new(Options {
+            files: vec![], pattern: "7".to_string(), match_mode: MatchMode::Substring, output_mode: OutputMode::Print,
+            highlight: false, record: None, replay: None,
+        });
+        let (sender, receiver) = sync_channel(16);
+        let (filtered_sender, filtered_receiver) = sync_channel(16);
+        let feeder = thread::spawn(move || for i in 0..5000 {
+            sender.send(line(&i.to_string(), 0, i)).unwrap();
+        });
+        let filter = thread::spawn(move || filter_lines(options, receiver, filtered_sender));
+        let filtered: Vec<usize> = filtered_receiver.iter().map(|l| l.line).collect();
+        feeder.join().unwrap();
+        filter.join().unwrap();
+        assert_eq!(filtered, (0..5000).filter(|i| i.to_string().contains('7')).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_extract_numbers() {
+        let numbers: Vec<String> = extract_numbers(b"took 12ms (007 retries), id=123456789012345678901234567890.")
+            .iter().map(|n| n.to_string()).collect();
+        assert_eq!(numbers, vec!["12", "7", "123456789012345678901234567890"]);
+        assert!(extract_numbers(b"no numbers here").is_empty());
+    }
+
+    #[test]
+    fn test_stats() {
+        let mut stats = Stats::new();
+        assert_eq!(stats.to_string(), "0 numbers in 0 lines.\n");
+        stats.add_line(b"request took 15ms");
+        stats.add_line(b"request took 3ms, 2 retries");
+        stats.add_line(b"no number");
+        stats.add_line(b"request took 18446744073709551616ms");
+        assert_eq!(stats.to_string(), "\
+4 numbers in 4 lines.
+sum: 18446744073709551636
+min: 2
+max: 18446744073709551616
+digits  count
+     1      2 ########################################
+     2      1 ####################
+    20      1 ####################
+");
+    }
+}
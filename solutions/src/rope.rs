@@ -0,0 +1,441 @@
+// A rope: a string stored as a balanced binary tree of chunks. Inserting into or removing from the
+// middle of a `String` moves everything behind that position, which is slow for large texts. A rope
+// instead splits the tree at the position and joins the pieces in a new order, which only touches
+// the nodes along a path from the root, so it takes logarithmic time.
+//
+// The tree is kept balanced the way an AVL tree is: The heights of the two children of any node
+// differ by at most one. The nodes are never changed once created, and are shared through `Rc`,
+// so cloning a rope, or taking a slice of it, is cheap too.
+
+#[cfg(not(feature = "std"))]
+use std::prelude::v1::*;
+use std::cmp;
+use std::fmt;
+use std::ops::{Bound, RangeBounds};
+use std::rc::Rc;
+
+// Chunks are at most this many bytes long. Joining two small chunks makes them one, so that many
+// small edits do not leave behind lots of tiny chunks.
+const MAX_CHUNK: usize = 512;
+
+enum Node {
+    Leaf(String),
+    Branch { left: Rc<Node>, right: Rc<Node>, len: usize, height: usize },
+}
+
+impl Node {
+    fn len(&self) -> usize {
+        match *self {
+            Node::Leaf(ref s) => s.len(),
+            Node::Branch { len, .. } => len,
+        }
+    }
+
+    fn height(&self) -> usize {
+        match *self {
+            Node::Leaf(_) => 0,
+            Node::Branch { height, .. } => height,
+        }
+    }
+}
+
+fn branch(left: Rc<Node>, right: Rc<Node>) -> Rc<Node> {
+    let len = left.len() + right.len();
+    let height = 1 + cmp::max(left.height(), right.height());
+    Rc::new(Node::Branch { left, right, len, height })
+}
+
+// The children of a node that is known to be a branch.
+fn children(node: &Rc<Node>) -> (Rc<Node>, Rc<Node>) {
+    match **node {
+        Node::Branch { ref left, ref right, .. } => (left.clone(), right.clone()),
+        Node::Leaf(_) => unreachable!("a leaf has no children"),
+    }
+}
+
+// Make a node of `left` and `right`, whose heights differ by at most two, rotating if they differ by
+// two.
+fn balance(left: Rc<Node>, right: Rc<Node>) -> Rc<Node> {
+    if left.height() > right.height() + 1 {
+        let (ll, lr) = children(&left);
+        if ll.height() >= lr.height() {
+            branch(ll, branch(lr, right))
+        } else {
+            let (lrl, lrr) = children(&lr);
+            branch(branch(ll, lrl), branch(lrr, right))
+        }
+    } else if right.height() > left.height() + 1 {
+        let (rl, rr) = children(&right);
+        if rr.height() >= rl.height() {
+            branch(branch(left, rl), rr)
+        } else {
+            let (rll, rlr) = children(&rl);
+            branch(branch(left, rll), branch(rlr, rr))
+        }
+    } else {
+        branch(left, right)
+    }
+}
+
+// Join two trees into one, with the text of `left` first. This takes time proportional to the
+// difference of their heights: We go down the taller tree until we reach a subtree as tall as the
+// other one, and rebalance on the way back up.
+fn join(left: Rc<Node>, right: Rc<Node>) -> Rc<Node> {
+    if left.height() > right.height() + 1 {
+        let (ll, lr) = children(&left);
+        balance(ll, join(lr, right))
+    } else if right.height() > left.height() + 1 {
+        let (rl, rr) = children(&right);
+        balance(join(left, rl), rr)
+    } else {
+        if let (Node::Leaf(a), Node::Leaf(b)) = (&*left, &*right) {
+            if a.len() + b.len() <= MAX_CHUNK {
+                return Rc::new(Node::Leaf(a.clone() + b));
+            }
+        }
+        branch(left, right)
+    }
+}
+
+fn join_options(left: Option<Rc<Node>>, right: Option<Rc<Node>>) -> Option<Rc<Node>> {
+    match (left, right) {
+        (Some(left), Some(right)) => Some(join(left, right)),
+        (left, None) => left,
+        (None, right) => right,
+    }
+}
+
+// Split a tree into the first `at` bytes, and the rest. Panics if `at` is not at a character boundary.
+fn split(node: &Rc<Node>, at: usize) -> (Option<Rc<Node>>, Option<Rc<Node>>) {
+    if at == 0 {
+        return (None, Some(node.clone()));
+    }
+    if at == node.len() {
+        return (Some(node.clone()), None);
+    }
+    match **node {
+        Node::Leaf(ref s) => {
+            assert!(s.is_char_boundary(at), "Rope index is not at a character boundary");
+            (Some(Rc::new(Node::Leaf(s[..at].to_string()))), Some(Rc::new(Node::Leaf(s[at..].to_string()))))
+        }
+        Node::Branch { ref left, ref right, .. } => {
+            if at <= left.len() {
+                let (a, b) = split(left, at);
+                (a, join_options(b, Some(right.clone())))
+            } else {
+                let (a, b) = split(right, at - left.len());
+                (join_options(Some(left.clone()), a), b)
+            }
+        }
+    }
+}
+
+// Build a balanced tree of the chunks of `s`.
+fn build(s: &str) -> Option<Rc<Node>> {
+    let mut leaves = Vec::new();
+    let mut rest = s;
+    while !rest.is_empty() {
+        let mut end = cmp::min(MAX_CHUNK, rest.len());
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        leaves.push(Rc::new(Node::Leaf(rest[..end].to_string())));
+        rest = &rest[end..];
+    }
+    if leaves.is_empty() { None } else { Some(build_from_leaves(&leaves)) }
+}
+
+// Splitting the leaves in the middle makes the heights of the two halves differ by at most one.
+fn build_from_leaves(leaves: &[Rc<Node>]) -> Rc<Node> {
+    if leaves.len() == 1 {
+        return leaves[0].clone();
+    }
+    let (left, right) = leaves.split_at(leaves.len() / 2);
+    branch(build_from_leaves(left), build_from_leaves(right))
+}
+
+/// A string stored as a balanced tree of chunks, which supports inserting, removing and slicing in
+/// logarithmic time. Positions are byte offsets, like for `String`.
+#[derive(Clone, Default)]
+pub struct Rope {
+    root: Option<Rc<Node>>,
+}
+
+// Turn a range of byte offsets into a start and end.
+fn bounds<R: RangeBounds<usize>>(range: R, len: usize) -> (usize, usize) {
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start + 1,
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => end + 1,
+        Bound::Excluded(&end) => end,
+        Bound::Unbounded => len,
+    };
+    assert!(start <= end && end <= len, "Rope range {}..{} out of bounds for length {}", start, end, len);
+    (start, end)
+}
+
+impl Rope {
+    pub fn new() -> Self {
+        Rope { root: None }
+    }
+
+    /// The length of the text, in bytes.
+    pub fn len(&self) -> usize {
+        self.root.as_ref().map_or(0, |root| root.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    // Split at `at`, which must not be larger than the length.
+    fn split_at(&self, at: usize) -> (Option<Rc<Node>>, Option<Rc<Node>>) {
+        assert!(at <= self.len(), "Rope index {} out of bounds for length {}", at, self.len());
+        match self.root {
+            Some(ref root) => split(root, at),
+            None => (None, None),
+        }
+    }
+
+    /// Insert `s` at byte offset `at`. Panics if `at` is beyond the end, or not at a character
+    /// boundary.
+    pub fn insert(&mut self, at: usize, s: &str) {
+        let (before, after) = self.split_at(at);
+        self.root = join_options(join_options(before, build(s)), after);
+    }
+
+    /// Append `s` at the end.
+    pub fn push_str(&mut self, s: &str) {
+        self.root = join_options(self.root.take(), build(s));
+    }
+
+    /// Append the text of another rope at the end.
+    pub fn append(&mut self, other: &Rope) {
+        self.root = join_options(self.root.take(), other.root.clone());
+    }
+
+    /// Remove the text in `range`. Panics if the range is out of bounds, or its ends are not at
+    /// character boundaries.
+    pub fn remove<R: RangeBounds<usize>>(&mut self, range: R) {
+        let (start, end) = bounds(range, self.len());
+        let (before, rest) = self.split_at(end);
+        let before = Rope { root: before };
+        let (before, _) = before.split_at(start);
+        self.root = join_options(before, rest);
+    }
+
+    /// Replace the text in `range` by `s`.
+    pub fn replace_range<R: RangeBounds<usize>>(&mut self, range: R, s: &str) {
+        let (start, end) = bounds(range, self.len());
+        self.remove(start..end);
+        self.insert(start, s);
+    }
+
+    /// Return the text in `range`, as a rope sharing the chunks of this one.
+    pub fn slice<R: RangeBounds<usize>>(&self, range: R) -> Rope {
+        let (start, end) = bounds(range, self.len());
+        let (before, _) = self.split_at(end);
+        let (_, slice) = Rope { root: before }.split_at(start);
+        Rope { root: slice }
+    }
+
+    /// Split the rope in two at `at`, keeping the text before `at` in `self`.
+    pub fn split_off(&mut self, at: usize) -> Rope {
+        let (before, after) = self.split_at(at);
+        self.root = before;
+        Rope { root: after }
+    }
+
+    /// Iterate over the chunks making up the text, in order.
+    pub fn chunks(&self) -> Chunks<'_> {
+        Chunks { stack: self.root.iter().map(|root| &**root).collect() }
+    }
+
+    /// Iterate over the characters of the text.
+    pub fn chars(&self) -> impl Iterator<Item = char> + '_ {
+        self.chunks().flat_map(|chunk| chunk.chars())
+    }
+}
+
+impl<'a> From<&'a str> for Rope {
+    fn from(s: &'a str) -> Self {
+        Rope { root: build(s) }
+    }
+}
+
+impl From<String> for Rope {
+    fn from(s: String) -> Self {
+        Rope::from(&s[..])
+    }
+}
+
+impl fmt::Display for Rope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for chunk in self.chunks() {
+            f.write_str(chunk)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Rope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&self.to_string(), f)
+    }
+}
+
+impl PartialEq for Rope {
+    fn eq(&self, other: &Rope) -> bool {
+        self.len() == other.len() && self.chunks().flat_map(str::bytes).eq(other.chunks().flat_map(str::bytes))
+    }
+}
+
+impl Eq for Rope {}
+
+impl PartialEq<str> for Rope {
+    fn eq(&self, other: &str) -> bool {
+        self.len() == other.len() && self.chunks().flat_map(str::bytes).eq(other.bytes())
+    }
+}
+
+impl<'a> PartialEq<&'a str> for Rope {
+    fn eq(&self, other: &&'a str) -> bool {
+        *self == **other
+    }
+}
+
+/// An iterator over the chunks of a `Rope`, created by `chunks`.
+pub struct Chunks<'a> {
+    // The subtrees still to visit, the next one last.
+    stack: Vec<&'a Node>,
+}
+
+impl<'a> Iterator for Chunks<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        loop {
+            match *self.stack.pop()? {
+                Node::Leaf(ref s) => return Some(s),
+                Node::Branch { ref left, ref right, .. } => {
+                    self.stack.push(right);
+                    self.stack.push(left);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use std::prelude::v1::*;
+    use super::{Node, Rope, MAX_CHUNK};
+
+    // Check that the tree is balanced and its lengths are right, and return its height.
+    fn check(node: &Node) -> usize {
+        match *node {
+            Node::Leaf(ref s) => {
+                assert!(!s.is_empty());
+                0
+            }
+            Node::Branch { ref left, ref right, len, height } => {
+                let (l, r) = (check(left), check(right));
+                assert!(l <= r + 1 && r <= l + 1, "unbalanced: {} vs {}", l, r);
+                assert_eq!(height, 1 + l.max(r));
+                assert_eq!(len, left.len() + right.len());
+                height
+            }
+        }
+    }
+
+    fn check_rope(rope: &Rope) {
+        if let Some(ref root) = rope.root {
+            check(root);
+        }
+    }
+
+    #[test]
+    fn test_basics() {
+        let mut rope = Rope::from("Hello world");
+        rope.insert(5, ",");
+        rope.push_str("!");
+        assert_eq!(rope, "Hello, world!");
+        rope.remove(5..6);
+        assert_eq!(rope.to_string(), "Hello world!");
+        rope.replace_range(6..11, "rope");
+        assert_eq!(rope, "Hello rope!");
+        assert_eq!(rope.slice(6..=9), "rope");
+        assert_eq!(rope.slice(..), rope);
+        let tail = rope.split_off(5);
+        assert_eq!((rope.len(), tail.len()), (5, 6));
+        rope.append(&tail);
+        assert_eq!(rope, "Hello rope!");
+        assert!(Rope::new().is_empty() && Rope::from("").is_empty());
+        assert_eq!(Rope::from("äöü").chars().collect::<Vec<_>>(), vec!['ä', 'ö', 'ü']);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_char_boundary() {
+        Rope::from("äöü").insert(1, "x");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_out_of_bounds() {
+        Rope::from("abc").remove(2..4);
+    }
+
+    #[test]
+    fn test_large() {
+        let text: String = (0..20_000).map(|i| format!("line {}\n", i)).collect();
+        let rope = Rope::from(&text[..]);
+        check_rope(&rope);
+        assert!(rope.chunks().all(|chunk| chunk.len() <= MAX_CHUNK));
+        assert_eq!(rope.to_string(), text);
+        assert_eq!(rope.slice(1000..50_000), &text[1000..50_000]);
+        // A clone shares all the chunks.
+        let copy = rope.clone();
+        assert_eq!(copy, rope);
+    }
+
+    #[test]
+    fn test_random_edits() {
+        // Apply the same edits to a rope and a string, and compare.
+        let mut rope = Rope::new();
+        let mut string = String::new();
+        let mut seed: u64 = 12345;
+        let mut next = |bound: usize| {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (seed >> 33) as usize % (bound + 1)
+        };
+        for i in 0..2000 {
+            let at = next(string.len());
+            if i % 3 == 2 {
+                let end = at + next(string.len() - at).min(300);
+                rope.remove(at..end);
+                string.replace_range(at..end, "");
+            } else {
+                let s = "abcdefghij".repeat(next(100));
+                rope.insert(at, &s);
+                string.insert_str(at, &s);
+            }
+            if i % 100 == 0 {
+                check_rope(&rope);
+                assert_eq!(rope, &string[..]);
+            }
+        }
+        check_rope(&rope);
+        assert_eq!(rope.to_string(), string);
+        // Many small edits do not leave lots of tiny chunks behind.
+        let mut rope = Rope::new();
+        for _ in 0..1000 {
+            rope.push_str("x");
+        }
+        assert!(rope.chunks().count() <= 2 * 1000 / MAX_CHUNK + 1);
+    }
+}
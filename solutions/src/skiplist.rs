@@ -0,0 +1,364 @@
+// A skip list: an ordered map made of a sorted linked list, plus a hierarchy of "express lanes"
+// that skip over more and more nodes. Every node is part of the lowest level; with probability 1/2
+// it is also part of the next level, and so on. Searching starts at the top level and goes down a
+// level whenever the next node would be too far, which takes logarithmic time on average.
+//
+// Like `LinkedList`, the nodes are linked with raw pointers and owned by the list: Every node is
+// reachable along the lowest level exactly once, which is how `Drop` frees them.
+//
+// The map itself needs `&mut self` for changes, so it cannot be shared between threads as is.
+// `ConcurrentSkipListMap` wraps it in a single lock, which is the simplest way to share it.
+
+#[cfg(not(feature = "std"))]
+use std::prelude::v1::*;
+use std::cmp;
+use std::marker::PhantomData;
+use std::mem;
+use std::ops::{Bound, RangeBounds};
+use std::ptr;
+#[cfg(feature = "std")]
+use std::sync::Mutex;
+
+// The number of levels. With every level having half as many nodes as the one below, this is plenty
+// for `2^32` nodes.
+const MAX_LEVEL: usize = 32;
+
+struct Node<K, V> {
+    key: K,
+    value: V,
+    // The next node on every level this node is part of.
+    next: Vec<NodePtr<K, V>>,
+}
+type NodePtr<K, V> = *mut Node<K, V>;
+
+/// An ordered map, implemented as a skip list.
+pub struct SkipListMap<K: Ord, V> {
+    // The first node on every level.
+    head: Vec<NodePtr<K, V>>,
+    len: usize,
+    // The state of the random number generator choosing the levels of new nodes.
+    rng: u64,
+    _marker: PhantomData<Box<Node<K, V>>>,
+}
+
+// The map owns its keys and values, just like a `BTreeMap` would.
+unsafe impl<K: Ord + Send, V: Send> Send for SkipListMap<K, V> {}
+unsafe impl<K: Ord + Sync, V: Sync> Sync for SkipListMap<K, V> {}
+
+impl<K: Ord, V> SkipListMap<K, V> {
+    pub fn new() -> Self {
+        SkipListMap { head: vec![ptr::null_mut(); MAX_LEVEL], len: 0, rng: 0x2545_f491_4f6c_dd1d, _marker: PhantomData }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // Choose the number of levels of a new node: `n` levels with probability `2^-n`. This is an
+    // xorshift generator, which is plenty random for the purpose.
+    fn random_height(&mut self) -> usize {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        cmp::min(1 + self.rng.trailing_ones() as usize, MAX_LEVEL)
+    }
+
+    // Find the first node whose key is not `before` (which must be true for a prefix of the keys).
+    fn first_not_before<F: Fn(&K) -> bool>(&self, before: F) -> NodePtr<K, V> {
+        let mut links: &[NodePtr<K, V>] = &self.head;
+        for level in (0..MAX_LEVEL).rev() {
+            loop {
+                let next = links[level];
+                if next.is_null() || !before(unsafe { &(*next).key }) {
+                    break;
+                }
+                links = unsafe { &(*next).next };
+            }
+        }
+        links[0]
+    }
+
+    // For every level, find the link pointing to the first node whose key is at least `key`.
+    fn links_to(&mut self, key: &K) -> [*mut NodePtr<K, V>; MAX_LEVEL] {
+        let mut result = [ptr::null_mut(); MAX_LEVEL];
+        // The links of the node we are at, starting at the head. We only ever work with raw pointers
+        // into the vectors of links, so that the ones we already found stay valid.
+        let mut links: *mut NodePtr<K, V> = self.head.as_mut_ptr();
+        for level in (0..MAX_LEVEL).rev() {
+            unsafe {
+                loop {
+                    let next = *links.add(level);
+                    if next.is_null() || (*next).key >= *key {
+                        break;
+                    }
+                    links = (*next).next.as_mut_ptr();
+                }
+                result[level] = links.add(level);
+            }
+        }
+        result
+    }
+
+    /// Associate `value` with `key`, returning the value that was there before.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let links = self.links_to(&key);
+        let found = unsafe { *links[0] };
+        if !found.is_null() && unsafe { (*found).key == key } {
+            return Some(mem::replace(unsafe { &mut (*found).value }, value));
+        }
+        // Link the new node in after the links we found, on all its levels.
+        let height = self.random_height();
+        let next = links[..height].iter().map(|&link| unsafe { *link }).collect();
+        let node = Box::into_raw(Box::new(Node { key, value, next }));
+        for &link in links[..height].iter() {
+            unsafe { *link = node };
+        }
+        self.len += 1;
+        None
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let node = self.first_not_before(|k| k < key);
+        if !node.is_null() && unsafe { (*node).key == *key } {
+            Some(unsafe { &(*node).value })
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let node = self.first_not_before(|k| k < key);
+        if !node.is_null() && unsafe { (*node).key == *key } {
+            Some(unsafe { &mut (*node).value })
+        } else {
+            None
+        }
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Remove `key` from the map, returning its value.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let links = self.links_to(key);
+        let found = unsafe { *links[0] };
+        if found.is_null() || unsafe { (*found).key != *key } {
+            return None;
+        }
+        // On every level of the node, the link we found points to the node. We make it skip it.
+        let node = unsafe { Box::from_raw(found) };
+        for (level, &next) in node.next.iter().enumerate() {
+            unsafe { *links[level] = next };
+        }
+        self.len -= 1;
+        Some(node.value)
+    }
+
+    /// Iterate over the entries, in increasing order of the keys.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter { node: self.head[0], end: Bound::Unbounded, _marker: PhantomData }
+    }
+
+    /// Iterate over the entries with keys in `range`, in increasing order of the keys.
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Iter<'_, K, V> where K: Clone {
+        let node = match range.start_bound() {
+            Bound::Included(start) => self.first_not_before(|k| k < start),
+            Bound::Excluded(start) => self.first_not_before(|k| k <= start),
+            Bound::Unbounded => self.head[0],
+        };
+        Iter { node, end: range.end_bound().cloned(), _marker: PhantomData }
+    }
+}
+
+impl<K: Ord, V> Default for SkipListMap<K, V> {
+    fn default() -> Self {
+        SkipListMap::new()
+    }
+}
+
+impl<K: Ord, V> Drop for SkipListMap<K, V> {
+    fn drop(&mut self) {
+        let mut node = self.head[0];
+        while !node.is_null() {
+            let boxed = unsafe { Box::from_raw(node) };
+            node = boxed.next[0];
+        }
+    }
+}
+
+/// An iterator over the entries of a `SkipListMap`, created by `iter` and `range`.
+pub struct Iter<'a, K: 'a, V: 'a> {
+    node: *const Node<K, V>,
+    end: Bound<K>,
+    _marker: PhantomData<&'a Node<K, V>>,
+}
+
+impl<'a, K: Ord, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.node.is_null() {
+            return None;
+        }
+        let node = unsafe { &*self.node };
+        let in_range = match self.end {
+            Bound::Included(ref end) => node.key <= *end,
+            Bound::Excluded(ref end) => node.key < *end,
+            Bound::Unbounded => true,
+        };
+        if !in_range {
+            self.node = ptr::null();
+            return None;
+        }
+        self.node = node.next[0];
+        Some((&node.key, &node.value))
+    }
+}
+
+/// A `SkipListMap` that can be shared between threads. Every operation takes a lock on the whole
+/// map, so the threads never actually work on it at the same time.
+#[cfg(feature = "std")]
+pub struct ConcurrentSkipListMap<K: Ord, V> {
+    map: Mutex<SkipListMap<K, V>>,
+}
+
+#[cfg(feature = "std")]
+impl<K: Ord, V> ConcurrentSkipListMap<K, V> {
+    pub fn new() -> Self {
+        ConcurrentSkipListMap { map: Mutex::new(SkipListMap::new()) }
+    }
+
+    /// Run `f` on the map, while holding the lock. This makes several operations atomic.
+    pub fn with<R, F: FnOnce(&mut SkipListMap<K, V>) -> R>(&self, f: F) -> R {
+        f(&mut self.map.lock().unwrap())
+    }
+
+    pub fn len(&self) -> usize {
+        self.with(|map| map.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.with(|map| map.is_empty())
+    }
+
+    pub fn insert(&self, key: K, value: V) -> Option<V> {
+        self.with(|map| map.insert(key, value))
+    }
+
+    /// Return a copy of the value of `key`, since the map may change once the lock is released.
+    pub fn get(&self, key: &K) -> Option<V> where V: Clone {
+        self.with(|map| map.get(key).cloned())
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.with(|map| map.contains_key(key))
+    }
+
+    pub fn remove(&self, key: &K) -> Option<V> {
+        self.with(|map| map.remove(key))
+    }
+
+    /// Return copies of the entries with keys in `range`, in increasing order of the keys.
+    pub fn range_to_vec<R: RangeBounds<K>>(&self, range: R) -> Vec<(K, V)> where K: Clone, V: Clone {
+        self.with(|map| map.range(range).map(|(k, v)| (k.clone(), v.clone())).collect())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K: Ord, V> Default for ConcurrentSkipListMap<K, V> {
+    fn default() -> Self {
+        ConcurrentSkipListMap::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use std::prelude::v1::*;
+    use std::collections::BTreeMap;
+    use std::sync::Arc;
+    #[cfg(feature = "std")]
+    use std::thread;
+    use testdata::numbers;
+    use super::SkipListMap;
+    #[cfg(feature = "std")]
+    use super::ConcurrentSkipListMap;
+
+    #[test]
+    fn test_insert_get_remove() {
+        let mut map = SkipListMap::new();
+        let mut expected = BTreeMap::new();
+        for (i, n) in numbers(2000).into_iter().enumerate() {
+            assert_eq!(map.insert(n, i), expected.insert(n, i));
+        }
+        assert_eq!(map.len(), expected.len());
+        for n in 0..1100 {
+            assert_eq!(map.get(&n), expected.get(&n));
+        }
+        *map.get_mut(&5).unwrap() = 0;
+        expected.insert(5, 0);
+        for n in numbers(700) {
+            assert_eq!(map.remove(&n), expected.remove(&n));
+        }
+        assert_eq!(map.len(), expected.len());
+        assert!(map.iter().eq(expected.iter()));
+        assert!(!map.contains_key(&0) && map.contains_key(&4));
+    }
+
+    #[test]
+    fn test_range() {
+        let mut map = SkipListMap::new();
+        for n in numbers(1000) {
+            map.insert(n * 2, ());
+        }
+        let keys = |iter: super::Iter<u64, ()>| iter.map(|(&k, _)| k).collect::<Vec<_>>();
+        assert_eq!(keys(map.range(10..20)), vec![10, 12, 14, 16, 18]);
+        assert_eq!(keys(map.range(11..=20)), vec![12, 14, 16, 18, 20]);
+        assert_eq!(keys(map.range(1990..)), vec![1990, 1992, 1994, 1996, 1998]);
+        assert_eq!(keys(map.range(..3)), vec![0, 2]);
+        assert!(keys(map.range(3000..)).is_empty());
+        assert_eq!(map.range(..).count(), 1000);
+        assert!(SkipListMap::<u64, ()>::new().iter().next().is_none());
+    }
+
+    #[test]
+    fn test_drops_values() {
+        let value = Arc::new(());
+        {
+            let mut map = SkipListMap::new();
+            for n in 0..100 {
+                map.insert(n, value.clone());
+            }
+            map.remove(&3);
+            assert_eq!(Arc::strong_count(&value), 100);
+        }
+        assert_eq!(Arc::strong_count(&value), 1);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_concurrent() {
+        let map = Arc::new(ConcurrentSkipListMap::new());
+        let threads: Vec<_> = (0..4).map(|t| {
+            let map = map.clone();
+            thread::spawn(move || {
+                for n in 0..250 {
+                    map.insert(n * 4 + t, t);
+                }
+            })
+        }).collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        assert_eq!(map.len(), 1000);
+        assert_eq!(map.get(&6), Some(2));
+        assert_eq!(map.range_to_vec(8..11), vec![(8, 0), (9, 1), (10, 2)]);
+        assert_eq!(map.remove(&6), Some(2));
+        assert!(!map.contains_key(&6));
+    }
+}
@@ -0,0 +1,56 @@
+// Golden-file ("snapshot") testing. A test renders some output to a string and compares it against
+// the file `tests/snapshots/<name>.snap`. This catches formatting regressions that would otherwise
+// only be noticed by eyeballing the output.
+//
+// To (re-)generate the golden files after an intended change, run the tests with the environment
+// variable `UPDATE_SNAPSHOTS` set, and review the resulting diff before committing it.
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+fn snapshot_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("snapshots").join(format!("{}.snap", name))
+}
+
+/// Compare `actual` against the golden file called `name`, panicking with a line-by-line diff if
+/// they differ.
+pub fn assert_snapshot(name: &str, actual: &str) {
+    let path = snapshot_path(name);
+    if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = match fs::read_to_string(&path) {
+        Ok(expected) => expected,
+        Err(e) => panic!("Cannot read snapshot {}: {}\nRun the tests with UPDATE_SNAPSHOTS=1 to create it.",
+                         path.display(), e),
+    };
+    if expected == actual {
+        return;
+    }
+    // Render a simple diff, so that the test output shows what changed.
+    let mut diff = String::new();
+    let expected_lines: Vec<&str> = expected.lines().collect();
+    let actual_lines: Vec<&str> = actual.lines().collect();
+    for idx in 0..expected_lines.len().max(actual_lines.len()) {
+        match (expected_lines.get(idx), actual_lines.get(idx)) {
+            (Some(e), Some(a)) if e == a => {},
+            (e, a) => {
+                if let Some(e) = e {
+                    diff.push_str(&format!("{:>4} - {}\n", idx + 1, e));
+                }
+                if let Some(a) = a {
+                    diff.push_str(&format!("{:>4} + {}\n", idx + 1, a));
+                }
+            }
+        }
+    }
+    if diff.is_empty() {
+        // The lines match, so the difference must be in the line endings.
+        diff.push_str("(only trailing newlines differ)\n");
+    }
+    panic!("Snapshot {} does not match:\n{}Run the tests with UPDATE_SNAPSHOTS=1 to accept the new output.",
+           name, diff);
+}
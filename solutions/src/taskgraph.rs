@@ -0,0 +1,227 @@
+// A task graph: Every task computes a value from the values of the tasks it depends on. Tasks whose
+// dependencies are done run in parallel on a fixed set of worker threads. This is the fork/join
+// pattern, where rgrep only has a fixed pipeline of stages.
+//
+// The scheduling happens in the thread calling `run`. It hands out ready tasks to the workers over
+// one channel, and gets the results back over another one.
+
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use bigint::BigUint;
+
+/// Identifies a task within its graph.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TaskId(usize);
+
+type TaskFn<T> = Box<dyn FnOnce(&[&T]) -> T + Send>;
+
+struct Task<T> {
+    deps: Vec<TaskId>,
+    run: TaskFn<T>,
+}
+
+// A task that is ready to run, together with the values it needs.
+struct Job<T> {
+    id: usize,
+    inputs: Vec<Arc<T>>,
+    run: TaskFn<T>,
+}
+
+// What a worker sends back: the value computed by a task, or `None` if the task panicked.
+type JobResult<T> = (usize, Option<T>);
+
+/// A graph of tasks. Tasks can only depend on tasks that were added before, so there are no cycles.
+pub struct TaskGraph<T> {
+    tasks: Vec<Task<T>>,
+}
+
+impl<T: Send + Sync + 'static> TaskGraph<T> {
+    pub fn new() -> Self {
+        TaskGraph { tasks: Vec::new() }
+    }
+
+    /// Add a task computing its value with `run`, which is passed the values of `deps` (in that
+    /// order).
+    pub fn add<F>(&mut self, deps: &[TaskId], run: F) -> TaskId
+        where F: FnOnce(&[&T]) -> T + Send + 'static
+    {
+        assert!(deps.iter().all(|dep| dep.0 < self.tasks.len()), "Task depends on a task of another graph");
+        self.tasks.push(Task { deps: deps.to_vec(), run: Box::new(run) });
+        TaskId(self.tasks.len() - 1)
+    }
+
+    /// Run all tasks on `threads` worker threads, and return their values, indexed by `TaskId`. Panics
+    /// if any of the tasks panics.
+    pub fn run(self, threads: usize) -> Vec<T> {
+        assert!(threads > 0, "Need at least one thread to run the tasks");
+        let count = self.tasks.len();
+        // For every task, how many dependencies are still missing, and which tasks wait for it.
+        let mut missing: Vec<usize> = self.tasks.iter().map(|task| task.deps.len()).collect();
+        let mut dependents: Vec<Vec<usize>> = vec![Vec::new(); count];
+        for (id, task) in self.tasks.iter().enumerate() {
+            for dep in task.deps.iter() {
+                dependents[dep.0].push(id);
+            }
+        }
+        let mut deps: Vec<Vec<TaskId>> = Vec::with_capacity(count);
+        let mut runs: Vec<Option<TaskFn<T>>> = Vec::with_capacity(count);
+        for task in self.tasks {
+            deps.push(task.deps);
+            runs.push(Some(task.run));
+        }
+        let mut values: Vec<Option<Arc<T>>> = (0..count).map(|_| None).collect();
+
+        // Start the workers. They all take their jobs from the same channel.
+        let (job_sender, job_receiver) = channel::<Job<T>>();
+        let (result_sender, result_receiver) = channel::<JobResult<T>>();
+        let job_receiver = Arc::new(Mutex::new(job_receiver));
+        let workers: Vec<_> = (0..threads).map(|_| {
+            let job_receiver = job_receiver.clone();
+            let result_sender = result_sender.clone();
+            thread::spawn(move || work(job_receiver, result_sender))
+        }).collect();
+
+        let submit = |id: usize, runs: &mut Vec<Option<TaskFn<T>>>, values: &Vec<Option<Arc<T>>>| {
+            let inputs = deps[id].iter().map(|dep| values[dep.0].clone().unwrap()).collect();
+            job_sender.send(Job { id, inputs, run: runs[id].take().unwrap() }).unwrap();
+        };
+        let ready: Vec<usize> = (0..count).filter(|&id| missing[id] == 0).collect();
+        for id in ready {
+            submit(id, &mut runs, &values);
+        }
+        for _ in 0..count {
+            let (id, value) = result_receiver.recv().unwrap();
+            let value = value.expect("A task panicked");
+            values[id] = Some(Arc::new(value));
+            for &dependent in dependents[id].iter() {
+                missing[dependent] -= 1;
+                if missing[dependent] == 0 {
+                    submit(dependent, &mut runs, &values);
+                }
+            }
+        }
+        // Closing the job channel tells the workers to stop.
+        drop(job_sender);
+        for worker in workers {
+            worker.join().unwrap();
+        }
+        // All jobs are gone, so we hold the only reference to every value.
+        values.into_iter().map(|value| Arc::try_unwrap(value.unwrap()).ok().unwrap()).collect()
+    }
+}
+
+impl<T: Send + Sync + 'static> Default for TaskGraph<T> {
+    fn default() -> Self {
+        TaskGraph::new()
+    }
+}
+
+// The loop of a worker thread: Run jobs until the channel is closed.
+fn work<T>(jobs: Arc<Mutex<Receiver<Job<T>>>>, results: Sender<JobResult<T>>) {
+    loop {
+        // Only hold the lock while waiting for a job, not while running it.
+        let job = match jobs.lock().unwrap().recv() {
+            Ok(job) => job,
+            Err(_) => return,
+        };
+        let Job { id, inputs, run } = job;
+        let value = panic::catch_unwind(AssertUnwindSafe(|| {
+            let inputs: Vec<&T> = inputs.iter().map(|input| &**input).collect();
+            run(&inputs)
+        })).ok();
+        // Drop our references to the inputs before reporting back, so that `run` ends up owning all
+        // values.
+        drop(inputs);
+        if results.send((id, value)).is_err() {
+            return;
+        }
+    }
+}
+
+// Every leaf of the factorial's product tree multiplies this many numbers.
+const FACTORIAL_LEAF_SIZE: u64 = 256;
+
+/// Compute `n!` on `threads` threads. The product is split into a tree: The leaves multiply short
+/// runs of numbers, and the inner nodes multiply the values of their two children. All nodes of one
+/// level can run at the same time.
+pub fn parallel_factorial(n: u64, threads: usize) -> BigUint {
+    let mut graph = TaskGraph::new();
+    let mut level: Vec<TaskId> = Vec::new();
+    let mut lo = 1;
+    while lo <= n {
+        let hi = if n - lo < FACTORIAL_LEAF_SIZE { n } else { lo + FACTORIAL_LEAF_SIZE - 1 };
+        level.push(graph.add(&[], move |_| (lo..hi + 1).map(BigUint::new).product()));
+        lo = hi + 1;
+    }
+    if level.is_empty() {
+        return BigUint::one();
+    }
+    while level.len() > 1 {
+        level = level.chunks(2).map(|pair| {
+            if pair.len() == 1 {
+                pair[0]
+            } else {
+                graph.add(pair, |inputs| inputs[0] * inputs[1])
+            }
+        }).collect();
+    }
+    let root = level[0];
+    graph.run(threads).swap_remove(root.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TaskGraph, parallel_factorial};
+    use bigint::BigUint;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_diamond() {
+        // Record the order in which tasks run.
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut graph = TaskGraph::new();
+        let logged = |name: &'static str, log: &Arc<Mutex<Vec<&'static str>>>| {
+            let log = log.clone();
+            move |inputs: &[&u64]| {
+                log.lock().unwrap().push(name);
+                inputs.iter().map(|&&x| x).sum::<u64>() + 1
+            }
+        };
+        let a = graph.add(&[], logged("a", &log));
+        let b = graph.add(&[a], logged("b", &log));
+        let c = graph.add(&[a], logged("c", &log));
+        graph.add(&[b, c, a], logged("d", &log));
+        assert_eq!(graph.run(3), vec![1, 2, 2, 6]);
+        let log = log.lock().unwrap();
+        assert_eq!((log[0], log[3]), ("a", "d"));
+    }
+
+    #[test]
+    fn test_independent_tasks() {
+        let mut graph = TaskGraph::new();
+        for i in 0..100 {
+            graph.add(&[], move |_| i * i);
+        }
+        assert_eq!(graph.run(4), (0..100).map(|i| i * i).collect::<Vec<_>>());
+        assert!(TaskGraph::<u8>::new().run(2).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "A task panicked")]
+    fn test_panic() {
+        let mut graph = TaskGraph::new();
+        let a = graph.add(&[], |_| 1);
+        graph.add(&[a], |_: &[&i32]| panic!("Oops"));
+        graph.run(2);
+    }
+
+    #[test]
+    fn test_parallel_factorial() {
+        for &n in [0, 1, 2, 255, 256, 257, 3000].iter() {
+            assert_eq!(parallel_factorial(n, 4), BigUint::factorial(n), "{}!", n);
+        }
+        assert_eq!(parallel_factorial(1000, 1), BigUint::factorial(1000));
+    }
+}
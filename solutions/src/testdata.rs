@@ -0,0 +1,10 @@
+// Test data shared by the tests of the collections.
+
+#[cfg(not(feature = "std"))]
+use std::prelude::v1::*;
+
+/// A deterministic sequence of numbers in `0..1000`, with repetitions. As 7919 and 1000 have no
+/// common divisor, the first 1000 numbers are all different, so they are `0..1000` shuffled.
+pub fn numbers(len: usize) -> Vec<u64> {
+    (0..len as u64).map(|i| i * 7919 % 1000).collect()
+}
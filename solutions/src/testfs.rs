@@ -0,0 +1,209 @@
+// Real file trees for tests. A test describes the files, directories and symlinks it needs, and
+// `create` puts them into a fresh temporary directory, which is removed again when the returned
+// `TestDir` goes out of scope:
+//
+//     let dir = TestTree::new()
+//         .file("a.txt", "hello\n")
+//         .dir("empty")
+//         .symlink("link", "a.txt")
+//         .create();
+//     let path = dir.path("a.txt");
+//
+// Paths are relative to the root of the tree, and parent directories are created as needed.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// Distinguishes the trees of one test run.
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+enum Entry {
+    File(PathBuf, Vec<u8>),
+    Dir(PathBuf),
+    Symlink(PathBuf, PathBuf),
+    Mode(PathBuf, u32),
+}
+
+/// The description of a file tree.
+pub struct TestTree {
+    entries: Vec<Entry>,
+}
+
+impl TestTree {
+    pub fn new() -> Self {
+        TestTree { entries: Vec::new() }
+    }
+
+    /// Add a file with the given contents.
+    pub fn file<P: AsRef<Path>, C: AsRef<[u8]>>(mut self, path: P, contents: C) -> Self {
+        self.entries.push(Entry::File(path.as_ref().to_path_buf(), contents.as_ref().to_vec()));
+        self
+    }
+
+    /// Add an (empty) directory.
+    pub fn dir<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.entries.push(Entry::Dir(path.as_ref().to_path_buf()));
+        self
+    }
+
+    /// Add a symlink pointing to `target`. Relative targets are relative to the directory containing
+    /// the link, as usual.
+    pub fn symlink<P: AsRef<Path>, T: AsRef<Path>>(mut self, path: P, target: T) -> Self {
+        self.entries.push(Entry::Symlink(path.as_ref().to_path_buf(), target.as_ref().to_path_buf()));
+        self
+    }
+
+    /// Set the Unix permissions of an entry added before. This happens after all entries are created,
+    /// so it can also take away the permission to write into a directory.
+    pub fn mode<P: AsRef<Path>>(mut self, path: P, mode: u32) -> Self {
+        self.entries.push(Entry::Mode(path.as_ref().to_path_buf(), mode));
+        self
+    }
+
+    /// Create the tree in a new temporary directory. Panics if anything goes wrong.
+    pub fn create(self) -> TestDir {
+        let root = env::temp_dir().join(format!("solutions-testfs-{}-{}", process::id(), NEXT_ID.fetch_add(1, Ordering::Relaxed)));
+        fs::create_dir(&root).unwrap_or_else(|e| panic!("Cannot create {}: {}", root.display(), e));
+        let dir = TestDir { root, modes_changed: Vec::new() };
+        let mut modes = Vec::new();
+        for entry in self.entries {
+            match entry {
+                Entry::File(path, contents) => {
+                    let path = dir.prepare(&path);
+                    fs::write(&path, contents).unwrap_or_else(|e| panic!("Cannot write {}: {}", path.display(), e));
+                }
+                Entry::Dir(path) => {
+                    let path = dir.path(&path);
+                    fs::create_dir_all(&path).unwrap_or_else(|e| panic!("Cannot create {}: {}", path.display(), e));
+                }
+                Entry::Symlink(path, target) => {
+                    let path = dir.prepare(&path);
+                    symlink(&target, &path).unwrap_or_else(|e| panic!("Cannot create symlink {}: {}", path.display(), e));
+                }
+                Entry::Mode(path, mode) => modes.push((path, mode)),
+            }
+        }
+        let mut dir = dir;
+        for (path, mode) in modes {
+            let path = dir.path(&path);
+            set_mode(&path, mode);
+            dir.modes_changed.push(path);
+        }
+        dir
+    }
+}
+
+/// A file tree in a temporary directory, which is removed on drop.
+pub struct TestDir {
+    root: PathBuf,
+    // The entries whose permissions we changed; we have to give those back before we can remove
+    // everything.
+    modes_changed: Vec<PathBuf>,
+}
+
+impl TestDir {
+    /// The root of the tree.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The absolute path of an entry of the tree.
+    pub fn path<P: AsRef<Path>>(&self, path: P) -> PathBuf {
+        assert!(path.as_ref().is_relative(), "Paths in a test tree must be relative: {}", path.as_ref().display());
+        self.root.join(path)
+    }
+
+    // Compute the path of a new entry, and create its parent directories.
+    fn prepare(&self, path: &Path) -> PathBuf {
+        let path = self.path(path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).unwrap_or_else(|e| panic!("Cannot create {}: {}", parent.display(), e));
+        }
+        path
+    }
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        for path in self.modes_changed.iter() {
+            // Symlinks have no permissions of their own, and `set_mode` would follow them.
+            if fs::symlink_metadata(path).map(|m| !m.file_type().is_symlink()).unwrap_or(false) {
+                set_mode(path, 0o700);
+            }
+        }
+        // Failing to clean up should not hide the actual test result.
+        let _ = fs::remove_dir_all(&self.root);
+    }
+}
+
+#[cfg(unix)]
+fn symlink(target: &Path, path: &Path) -> ::std::io::Result<()> {
+    ::std::os::unix::fs::symlink(target, path)
+}
+
+#[cfg(windows)]
+fn symlink(target: &Path, path: &Path) -> ::std::io::Result<()> {
+    ::std::os::windows::fs::symlink_file(target, path)
+}
+
+#[cfg(unix)]
+fn set_mode(path: &Path, mode: u32) {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(mode))
+        .unwrap_or_else(|e| panic!("Cannot set permissions of {}: {}", path.display(), e));
+}
+
+#[cfg(not(unix))]
+fn set_mode(path: &Path, mode: u32) {
+    // All we can do elsewhere is toggling the read-only flag.
+    let mut permissions = fs::metadata(path).unwrap().permissions();
+    permissions.set_readonly(mode & 0o200 == 0);
+    fs::set_permissions(path, permissions).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use super::TestTree;
+
+    #[test]
+    fn test_create_and_cleanup() {
+        let dir = TestTree::new()
+            .file("a.txt", "hello\n")
+            .file("sub/deeper/b.txt", b"\xff")
+            .dir("empty")
+            .symlink("link", "a.txt")
+            .create();
+        let root = dir.root().to_path_buf();
+        assert_eq!(fs::read_to_string(dir.path("a.txt")).unwrap(), "hello\n");
+        assert_eq!(fs::read(dir.path("sub/deeper/b.txt")).unwrap(), vec![0xff]);
+        assert!(dir.path("empty").is_dir());
+        assert!(fs::symlink_metadata(dir.path("link")).unwrap().file_type().is_symlink());
+        assert_eq!(fs::read_to_string(dir.path("link")).unwrap(), "hello\n");
+        let other = TestTree::new().create();
+        assert!(other.root() != dir.root());
+        drop(dir);
+        assert!(!root.exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_modes() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = TestTree::new()
+            .file("locked/secret.txt", "")
+            .mode("locked/secret.txt", 0o400)
+            .mode("locked", 0o500)
+            .create();
+        let mode = |path: &str| fs::metadata(dir.path(path)).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode("locked/secret.txt"), 0o400);
+        assert_eq!(mode("locked"), 0o500);
+        let root = dir.root().to_path_buf();
+        // Cleaning up still works, even though the directory is not writable.
+        drop(dir);
+        assert!(!root.exists());
+    }
+}
@@ -0,0 +1,330 @@
+// A map from big integers to values, organized as a binary trie: Every node stands for a sequence of
+// bits, and has (up to) two children, for appending a 0 or a 1. A number is stored at the node of its
+// binary representation, most significant bit first; 0 has no bits at all and sits at the root.
+//
+// This makes it cheap to find the longest key that is a *prefix* of a number, that is, the number
+// shifted right by some amount. Going through the nodes level by level, left to right, visits the
+// keys in increasing order: All numbers with fewer bits are smaller, and within a level, the bits
+// are compared from the most significant one down.
+//
+// There is no `unsafe` code in here: Children are owned by their parents through a `Box`.
+
+#[cfg(not(feature = "std"))]
+use std::prelude::v1::*;
+use std::collections::VecDeque;
+use std::ops::{Bound, RangeBounds};
+use bigint::BigUint;
+
+struct Node<V> {
+    value: Option<V>,
+    children: [Option<Box<Node<V>>>; 2],
+}
+
+impl<V> Node<V> {
+    fn new() -> Self {
+        Node { value: None, children: [None, None] }
+    }
+}
+
+/// A map with `BigUint` keys, which supports finding the longest key that is a prefix (in binary) of
+/// a given number, and iterating over ranges of keys in increasing order.
+pub struct BigIntTrie<V> {
+    root: Node<V>,
+    len: usize,
+}
+
+// The bits of a key, most significant first.
+fn key_bits(key: &BigUint) -> impl Iterator<Item = usize> + '_ {
+    (0..key.bit_length()).rev().map(move |i| key.get_bit(i) as usize)
+}
+
+impl<V> BigIntTrie<V> {
+    pub fn new() -> Self {
+        BigIntTrie { root: Node::new(), len: 0 }
+    }
+
+    /// The number of keys in the map.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // Find the node of `key`, if there is one.
+    fn node(&self, key: &BigUint) -> Option<&Node<V>> {
+        let mut node = &self.root;
+        for bit in key_bits(key) {
+            node = node.children[bit].as_ref()?;
+        }
+        Some(node)
+    }
+
+    /// Associate `value` with `key`, returning the value that used to be there.
+    pub fn insert(&mut self, key: &BigUint, value: V) -> Option<V> {
+        let mut node = &mut self.root;
+        for bit in key_bits(key) {
+            node = node.children[bit].get_or_insert_with(|| Box::new(Node::new()));
+        }
+        let old = node.value.replace(value);
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    pub fn get(&self, key: &BigUint) -> Option<&V> {
+        self.node(key).and_then(|node| node.value.as_ref())
+    }
+
+    pub fn get_mut(&mut self, key: &BigUint) -> Option<&mut V> {
+        let mut node = &mut self.root;
+        for bit in key_bits(key) {
+            node = node.children[bit].as_mut()?;
+        }
+        node.value.as_mut()
+    }
+
+    pub fn contains_key(&self, key: &BigUint) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Remove `key` from the map, returning its value. Nodes that are no longer needed are freed.
+    pub fn remove(&mut self, key: &BigUint) -> Option<V> {
+        // First find the last node on the path that is needed even without the key, because it has a
+        // value or another child. If the node of the key has no children, the branch below that node
+        // goes away together with the value.
+        let mut cut = 0;
+        let mut node = &self.root;
+        for (depth, bit) in key_bits(key).enumerate() {
+            if node.value.is_some() || node.children[1 - bit].is_some() {
+                cut = depth;
+            }
+            node = node.children[bit].as_ref()?;
+        }
+        node.value.as_ref()?;
+        let is_leaf = node.children.iter().all(Option::is_none);
+        self.len -= 1;
+        // Now walk down again, and take away either the whole branch or just the value.
+        let mut node = &mut self.root;
+        for (depth, bit) in key_bits(key).enumerate() {
+            if is_leaf && depth == cut {
+                let mut branch = node.children[bit].take().unwrap();
+                let value = {
+                    let mut branch_node: &mut Node<V> = &mut branch;
+                    for bit in key_bits(key).skip(depth + 1) {
+                        branch_node = branch_node.children[bit].as_mut().unwrap();
+                    }
+                    branch_node.value.take()
+                };
+                drop_branch(branch);
+                return value;
+            }
+            node = node.children[bit].as_mut().unwrap();
+        }
+        node.value.take()
+    }
+
+    /// Find the longest key that is a prefix of `n` in binary, that is, the largest key that is `n`
+    /// shifted to the right by some amount. Returns that key and its value.
+    pub fn longest_prefix(&self, n: &BigUint) -> Option<(BigUint, &V)> {
+        // Remember the deepest node with a value, and compute its key only at the end.
+        let mut best = self.root.value.as_ref().map(|value| (0, value));
+        let mut node = &self.root;
+        for (depth, bit) in key_bits(n).enumerate() {
+            node = match node.children[bit].as_ref() {
+                Some(child) => child,
+                None => break,
+            };
+            if let Some(ref value) = node.value {
+                best = Some((depth + 1, value));
+            }
+        }
+        best.map(|(depth, value)| (n >> (n.bit_length() as usize - depth), value))
+    }
+
+    /// Iterate over the keys in `range` and their values, in increasing order of the keys.
+    pub fn range<R: RangeBounds<BigUint>>(&self, range: R) -> Range<'_, V> {
+        let mut queue = VecDeque::new();
+        queue.push_back((&self.root, BigUint::zero()));
+        Range { queue, start: range.start_bound().cloned(), end: range.end_bound().cloned() }
+    }
+
+    /// Iterate over all keys and their values, in increasing order of the keys.
+    pub fn iter(&self) -> Range<'_, V> {
+        self.range(..)
+    }
+}
+
+impl<V> Default for BigIntTrie<V> {
+    fn default() -> Self {
+        BigIntTrie::new()
+    }
+}
+
+// Dropping the nodes recursively could overflow the stack for keys with many bits, so we take the
+// tree apart one node at a time.
+fn drop_branch<V>(branch: Box<Node<V>>) {
+    let mut stack = vec![branch];
+    while let Some(mut node) = stack.pop() {
+        stack.extend(node.children.iter_mut().filter_map(Option::take));
+    }
+}
+
+impl<V> Drop for BigIntTrie<V> {
+    fn drop(&mut self) {
+        for child in self.root.children.iter_mut() {
+            if let Some(child) = child.take() {
+                drop_branch(child);
+            }
+        }
+    }
+}
+
+/// An iterator over a range of keys of a `BigIntTrie`, created by `range` and `iter`.
+pub struct Range<'a, V: 'a> {
+    // The nodes still to visit, level by level, with the keys they stand for.
+    queue: VecDeque<(&'a Node<V>, BigUint)>,
+    start: Bound<BigUint>,
+    end: Bound<BigUint>,
+}
+
+impl<'a, V> Range<'a, V> {
+    fn below_end(&self, key: &BigUint) -> bool {
+        match self.end {
+            Bound::Included(ref end) => key <= end,
+            Bound::Excluded(ref end) => key < end,
+            Bound::Unbounded => true,
+        }
+    }
+
+    fn above_start(&self, key: &BigUint) -> bool {
+        match self.start {
+            Bound::Included(ref start) => key >= start,
+            Bound::Excluded(ref start) => key > start,
+            Bound::Unbounded => true,
+        }
+    }
+}
+
+impl<'a, V> Iterator for Range<'a, V> {
+    type Item = (BigUint, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((node, key)) = self.queue.pop_front() {
+            // Everything below this node is larger than it, so we can skip the whole branch once
+            // the node is past the end. Then, since we go in increasing order, we are done.
+            if !self.below_end(&key) {
+                self.queue.clear();
+                return None;
+            }
+            for (bit, child) in node.children.iter().enumerate() {
+                if let Some(ref child) = *child {
+                    self.queue.push_back((child, (&key << 1).add_u64(bit as u64)));
+                }
+            }
+            if let Some(ref value) = node.value {
+                if self.above_start(&key) {
+                    return Some((key, value));
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use std::prelude::v1::*;
+    use bigint::BigUint;
+    use super::BigIntTrie;
+
+    fn keys<V>(iter: super::Range<V>) -> Vec<BigUint> {
+        iter.map(|(key, _)| key).collect()
+    }
+
+    #[test]
+    fn test_insert_get_remove() {
+        let mut t = BigIntTrie::new();
+        assert!(t.is_empty());
+        assert_eq!(t.insert(&BigUint::new(5), "five"), None);
+        assert_eq!(t.insert(&BigUint::zero(), "zero"), None);
+        assert_eq!(t.insert(&BigUint::power_of_2(100), "big"), None);
+        assert_eq!(t.insert(&BigUint::new(5), "FIVE"), Some("five"));
+        assert_eq!(t.len(), 3);
+        assert_eq!(t.get(&BigUint::new(5)), Some(&"FIVE"));
+        assert_eq!(t.get(&BigUint::zero()), Some(&"zero"));
+        assert_eq!(t.get(&BigUint::new(2)), None);
+        assert_eq!(t.get(&BigUint::new(11)), None);
+        *t.get_mut(&BigUint::power_of_2(100)).unwrap() = "huge";
+        assert_eq!(t.get(&BigUint::power_of_2(100)), Some(&"huge"));
+        assert_eq!(t.remove(&BigUint::new(2)), None);
+        assert_eq!(t.remove(&BigUint::power_of_2(100)), Some("huge"));
+        assert_eq!(t.remove(&BigUint::power_of_2(100)), None);
+        assert_eq!(t.remove(&BigUint::zero()), Some("zero"));
+        assert!(t.contains_key(&BigUint::new(5)));
+        assert_eq!(t.len(), 1);
+        assert_eq!(t.remove(&BigUint::new(5)), Some("FIVE"));
+        assert!(t.is_empty());
+        // All nodes are gone again.
+        assert!(t.root.children.iter().all(Option::is_none));
+    }
+
+    #[test]
+    fn test_remove_keeps_other_keys() {
+        // 2 = 10, 4 = 100, 5 = 101, 9 = 1001: 2 lies on the paths of all others.
+        let mut t = BigIntTrie::new();
+        for &n in [2, 4, 5, 9].iter() {
+            t.insert(&BigUint::new(n), n);
+        }
+        assert_eq!(t.remove(&BigUint::new(2)), Some(2));
+        assert_eq!(t.remove(&BigUint::new(9)), Some(9));
+        assert_eq!(keys(t.iter()), vec![BigUint::new(4), BigUint::new(5)]);
+        assert!(t.root.children[1].as_ref().unwrap().children[0].as_ref().unwrap().children[0].is_some());
+        assert_eq!(t.remove(&BigUint::new(4)), Some(4));
+        assert_eq!(t.get(&BigUint::new(5)), Some(&5));
+    }
+
+    #[test]
+    fn test_longest_prefix() {
+        let mut t = BigIntTrie::new();
+        // 6 = 110, 27 = 11011
+        t.insert(&BigUint::new(6), "6");
+        t.insert(&BigUint::new(27), "27");
+        assert_eq!(t.longest_prefix(&BigUint::new(27)), Some((BigUint::new(27), &"27")));
+        assert_eq!(t.longest_prefix(&BigUint::new(26)), Some((BigUint::new(6), &"6")));
+        assert_eq!(t.longest_prefix(&BigUint::new(27 * 1024 + 3)), Some((BigUint::new(27), &"27")));
+        assert_eq!(t.longest_prefix(&BigUint::new(7)), None);
+        t.insert(&BigUint::zero(), "0");
+        assert_eq!(t.longest_prefix(&BigUint::new(7)), Some((BigUint::zero(), &"0")));
+    }
+
+    #[test]
+    fn test_range() {
+        let mut t = BigIntTrie::new();
+        let numbers = [0, 1, 2, 3, 4, 7, 8, 100, 1000];
+        for &n in numbers.iter().rev() {
+            t.insert(&BigUint::new(n), n);
+        }
+        t.insert(&BigUint::power_of_2(70), 0);
+        let all: Vec<BigUint> = numbers.iter().map(|&n| BigUint::new(n)).chain(Some(BigUint::power_of_2(70))).collect();
+        assert_eq!(keys(t.iter()), all);
+        assert_eq!(keys(t.range(BigUint::new(3)..BigUint::new(100))), &all[3..7]);
+        assert_eq!(keys(t.range(BigUint::new(5)..=BigUint::new(100))), &all[5..8]);
+        assert_eq!(keys(t.range(BigUint::new(1001)..)), &all[9..]);
+        assert_eq!(keys(t.range(..BigUint::new(1))), &all[..1]);
+        assert!(keys(t.range(BigUint::new(9)..BigUint::new(99))).is_empty());
+        assert_eq!(t.iter().map(|(_, &v)| v).sum::<u64>(), numbers.iter().sum::<u64>());
+    }
+
+    #[test]
+    fn test_long_keys() {
+        // This would overflow the stack if the nodes were dropped recursively.
+        let mut t = BigIntTrie::new();
+        t.insert(&BigUint::power_of_2(200_000), ());
+        t.insert(&BigUint::power_of_2(300_000), ());
+        assert_eq!(t.longest_prefix(&BigUint::power_of_2(300_000)).map(|(k, _)| k), Some(BigUint::power_of_2(300_000)));
+    }
+}
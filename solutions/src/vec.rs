@@ -39,6 +39,7 @@ pub mod part01 {
 
 pub mod part0203 {
     // A polymorphic (generic) "some value, or no value"
+    #[derive(Debug, PartialEq, Eq, Hash)]
     pub enum SomethingOrNothing<T>  {
         Something(T),
         Nothing,
@@ -46,6 +47,212 @@ pub mod part0203 {
     pub use self::SomethingOrNothing::*;
     type NumberOrNothing = SomethingOrNothing<i32>;
 
+    use std::mem;
+    use bigint::{BigInt, BigUint};
+    // The combinators that make `Option` pleasant to use, so that the values can be passed along
+    // without a `match` at every step.
+    impl<T> SomethingOrNothing<T> {
+        /// Borrow the value, leaving it where it is.
+        pub fn as_ref(&self) -> SomethingOrNothing<&T> {
+            match *self {
+                Something(ref t) => Something(t),
+                Nothing => Nothing,
+            }
+        }
+
+        /// Borrow the value mutably, to change it in place.
+        pub fn as_mut(&mut self) -> SomethingOrNothing<&mut T> {
+            match *self {
+                Something(ref mut t) => Something(t),
+                Nothing => Nothing,
+            }
+        }
+
+        /// Take the value out, leaving `Nothing` behind.
+        pub fn take(&mut self) -> SomethingOrNothing<T> {
+            mem::replace(self, Nothing)
+        }
+
+        /// Put `value` in, and return what was there before.
+        pub fn replace(&mut self, value: T) -> SomethingOrNothing<T> {
+            mem::replace(self, Something(value))
+        }
+
+        /// Borrow the value mutably, after computing it with `f` if there is none yet.
+        pub fn get_or_insert_with<F: FnOnce() -> T>(&mut self, f: F) -> &mut T {
+            if let Nothing = *self {
+                *self = Something(f());
+            }
+            match *self {
+                Something(ref mut t) => t,
+                Nothing => unreachable!(),
+            }
+        }
+
+        /// Apply `f` to the value, if there is one.
+        pub fn map<U, F: FnOnce(T) -> U>(self, f: F) -> SomethingOrNothing<U> {
+            match self {
+                Something(t) => Something(f(t)),
+                Nothing => Nothing,
+            }
+        }
+
+        /// Apply `f` to the value, if there is one; `f` itself may come up with nothing.
+        pub fn and_then<U, F: FnOnce(T) -> SomethingOrNothing<U>>(self, f: F) -> SomethingOrNothing<U> {
+            match self {
+                Something(t) => f(t),
+                Nothing => Nothing,
+            }
+        }
+
+        /// Keep the value, or ask `f` for another try if there is none.
+        pub fn or_else<F: FnOnce() -> SomethingOrNothing<T>>(self, f: F) -> SomethingOrNothing<T> {
+            match self {
+                Something(t) => Something(t),
+                Nothing => f(),
+            }
+        }
+
+        /// Keep the value only if `pred` holds for it.
+        pub fn filter<P: FnOnce(&T) -> bool>(self, pred: P) -> SomethingOrNothing<T> {
+            match self {
+                Something(t) => if pred(&t) { Something(t) } else { Nothing },
+                Nothing => Nothing,
+            }
+        }
+
+        /// Pair up the two values, if there are both.
+        pub fn zip<U>(self, other: SomethingOrNothing<U>) -> SomethingOrNothing<(T, U)> {
+            match (self, other) {
+                (Something(t), Something(u)) => Something((t, u)),
+                _ => Nothing,
+            }
+        }
+
+        /// Return the value that is there, if exactly one of the two has one.
+        pub fn xor(self, other: SomethingOrNothing<T>) -> SomethingOrNothing<T> {
+            match (self, other) {
+                (Something(t), Nothing) | (Nothing, Something(t)) => Something(t),
+                _ => Nothing,
+            }
+        }
+
+        /// Return the value, or `default` if there is none.
+        pub fn unwrap_or(self, default: T) -> T {
+            match self {
+                Something(t) => t,
+                Nothing => default,
+            }
+        }
+
+        /// Return the value, or compute one with `f` if there is none.
+        pub fn unwrap_or_else<F: FnOnce() -> T>(self, f: F) -> T {
+            match self {
+                Something(t) => t,
+                Nothing => f(),
+            }
+        }
+
+        /// Return the value, or panic with `msg` if there is none.
+        pub fn expect(self, msg: &str) -> T {
+            match self {
+                Something(t) => t,
+                Nothing => panic!("{}", msg),
+            }
+        }
+
+        /// Return the value, or the default value of its type if there is none.
+        pub fn unwrap_or_default(self) -> T where T: Default {
+            match self {
+                Something(t) => t,
+                Nothing => T::default(),
+            }
+        }
+
+        /// Turn the value into `Ok`, and nothing into the error `err`. This lets `?` report a
+        /// missing value.
+        pub fn ok_or<E>(self, err: E) -> Result<T, E> {
+            match self {
+                Something(t) => Ok(t),
+                Nothing => Err(err),
+            }
+        }
+
+        /// Like `ok_or`, with the error only computed when it is needed.
+        pub fn ok_or_else<E, F: FnOnce() -> E>(self, f: F) -> Result<T, E> {
+            match self {
+                Something(t) => Ok(t),
+                Nothing => Err(f()),
+            }
+        }
+
+        /// Convert to the standard library's `Option`, to hand the value to code that expects one.
+        pub fn into_option(self) -> Option<T> {
+            match self {
+                Something(t) => Some(t),
+                Nothing => None,
+            }
+        }
+
+        /// Convert from the standard library's `Option`.
+        pub fn from_option(o: Option<T>) -> Self {
+            match o {
+                Some(t) => Something(t),
+                None => Nothing,
+            }
+        }
+    }
+
+    impl<T> SomethingOrNothing<SomethingOrNothing<T>> {
+        /// Remove one level of nesting.
+        pub fn flatten(self) -> SomethingOrNothing<T> {
+            self.and_then(|inner| inner)
+        }
+    }
+
+    impl<T: Clone> SomethingOrNothing<&T> {
+        /// Turn a borrowed value into an owned one, by cloning it.
+        pub fn cloned(self) -> SomethingOrNothing<T> {
+            self.map(T::clone)
+        }
+    }
+
+    impl<T: Copy> SomethingOrNothing<&T> {
+        /// Turn a borrowed value into an owned one, by copying it.
+        pub fn copied(self) -> SomethingOrNothing<T> {
+            self.map(|&t| t)
+        }
+    }
+
+    impl<T> From<Option<T>> for SomethingOrNothing<T> {
+        fn from(o: Option<T>) -> Self {
+            SomethingOrNothing::from_option(o)
+        }
+    }
+
+    impl<T> From<SomethingOrNothing<T>> for Option<T> {
+        fn from(s: SomethingOrNothing<T>) -> Self {
+            s.into_option()
+        }
+    }
+
+    /// Return the first element of the slice, if there is one. The result borrows from `v`: the
+    /// compiler connects the two lifetimes, so `v` cannot be changed while we hold on to it.
+    pub fn head<T>(v: &[T]) -> SomethingOrNothing<&T> {
+        match v.first() {
+            Some(t) => Something(t),
+            None => Nothing,
+        }
+    }
+
+    /// Return the last element of the slice, like `head`.
+    pub fn last<T>(v: &[T]) -> SomethingOrNothing<&T> {
+        match v.last() {
+            Some(t) => Something(t),
+            None => Nothing,
+        }
+    }
+
     /// This trait is used to compute the minimum of two elements of the given type
     pub trait Minimum : Copy {
         fn min(self, b: Self) -> Self;
@@ -63,6 +270,43 @@ pub mod part0203 {
         min
     }
 
+    /// Return the minimum element together with its position in the vector. If the minimum occurs
+    /// several times, this is the first position. We need `PartialEq` to see which of the two
+    /// elements `min` returned: only an element that is strictly smaller replaces the current one,
+    /// so with a NaN (that is not even equal to itself), we stay at the first position.
+    pub fn vec_min_with_index<T: Minimum + PartialEq>(v: Vec<T>) -> SomethingOrNothing<(usize, T)> {
+        let mut min = Nothing;
+        for (i, e) in v.into_iter().enumerate() {
+            min = Something(match min {
+                Nothing => (i, e),
+                Something((j, n)) => {
+                    let m = e.min(n);
+                    if m != n && m == e { (i, e) } else { (j, n) }
+                }
+            });
+        }
+        min
+    }
+
+    /// Return the element for which `key` is smallest, the first one if there are several. The
+    /// elements themselves do not need to be `Minimum`, or even `Copy`.
+    pub fn vec_min_by_key<T, K, F>(v: Vec<T>, mut key: F) -> SomethingOrNothing<T>
+        where K: Minimum + PartialEq, F: FnMut(&T) -> K
+    {
+        let mut min = Nothing;
+        for e in v {
+            let k = key(&e);
+            min = Something(match min {
+                Nothing => (k, e),
+                Something((m, n)) => {
+                    let l = k.min(m);
+                    if l != m && l == k { (k, e) } else { (m, n) }
+                }
+            });
+        }
+        min.map(|(_, e)| e)
+    }
+
     /// We can compute the minimum of two integers
     impl Minimum for i32 {
         fn min(self, b: Self) -> Self {
@@ -70,9 +314,58 @@ pub mod part0203 {
         }
     }
 
+    /// The counterpart of `Minimum`, to compute the maximum of two elements
+    pub trait Maximum : Copy {
+        fn max(self, b: Self) -> Self;
+    }
+
+    impl Maximum for i32 {
+        fn max(self, b: Self) -> Self {
+            if self > b { self } else { b }
+        }
+    }
+
+    /// Return the minimum element of anything we can iterate over: not just vectors, but also
+    /// ranges, iterators over slices, and chains of iterator adapters
+    pub fn fold_min<I>(iter: I) -> SomethingOrNothing<I::Item>
+        where I: IntoIterator, I::Item: Minimum
+    {
+        iter.into_iter().fold(Nothing, |min, e| Something(match min {
+            Nothing => e,
+            Something(n) => e.min(n)
+        }))
+    }
+
+    /// Return the maximum element, like `fold_min`
+    pub fn fold_max<I>(iter: I) -> SomethingOrNothing<I::Item>
+        where I: IntoIterator, I::Item: Maximum
+    {
+        iter.into_iter().fold(Nothing, |max, e| Something(match max {
+            Nothing => e,
+            Something(n) => e.max(n)
+        }))
+    }
+
+    /// The same as methods on iterators, so that the minimum comes at the end of a chain of
+    /// adapters: `v.iter().map(|&x| x * 2).minimum()`. There are no methods `min` and `max`,
+    /// because `Iterator` already has these, returning an `Option`.
+    pub trait MinimumExt : Iterator + Sized {
+        fn minimum(self) -> SomethingOrNothing<Self::Item> where Self::Item: Minimum {
+            fold_min(self)
+        }
+
+        fn maximum(self) -> SomethingOrNothing<Self::Item> where Self::Item: Maximum {
+            fold_max(self)
+        }
+    }
+
+    impl<I: Iterator> MinimumExt for I {}
+
     /// Sample program to call vec_min
     impl NumberOrNothing {
-        pub fn print(self) {
+        // Rust has no specialization, so this cannot also be called `print`: there would be two
+        // methods of that name for `SomethingOrNothing<i32>`.
+        pub fn print_i32(self) {
             match self {
                 Nothing => println!("The number is: <nothing>"),
                 Something(n) => println!("The number is: {}", n),
@@ -85,7 +378,19 @@ pub mod part0203 {
     pub fn main_i32() {
         let vec = read_vec();
         let min = vec_min(vec);
-        min.print();
+        min.print_i32();
+    }
+
+    impl Minimum for i64 {
+        fn min(self, b: Self) -> Self {
+            if self < b { self } else { b }
+        }
+    }
+
+    impl Maximum for i64 {
+        fn max(self, b: Self) -> Self {
+            if self > b { self } else { b }
+        }
     }
 
     // Now, all the same for calling it on f32
@@ -95,6 +400,53 @@ pub mod part0203 {
         }
     }
 
+    impl Maximum for f32 {
+        fn max(self, b: Self) -> Self {
+            if self > b { self } else { b }
+        }
+    }
+
+    // For `f64`, we follow `f64::min` and `f64::max`: NaN is ignored, so the result is only NaN if
+    // both numbers are. A NaN somewhere in a vector thus does not hide its actual minimum.
+    impl Minimum for f64 {
+        fn min(self, b: Self) -> Self {
+            f64::min(self, b)
+        }
+    }
+
+    impl Maximum for f64 {
+        fn max(self, b: Self) -> Self {
+            f64::max(self, b)
+        }
+    }
+
+    // Big numbers are not `Copy`, but references to them are. Comparing references means that
+    // finding the minimum does not clone a single number; the caller gets a reference into the
+    // vector, and can clone just that one if needed.
+    impl Minimum for &BigInt {
+        fn min(self, b: Self) -> Self {
+            if self <= b { self } else { b }
+        }
+    }
+
+    impl Maximum for &BigInt {
+        fn max(self, b: Self) -> Self {
+            if self >= b { self } else { b }
+        }
+    }
+
+    impl Minimum for &BigUint {
+        fn min(self, b: Self) -> Self {
+            if self <= b { self } else { b }
+        }
+    }
+
+    impl Maximum for &BigUint {
+        fn max(self, b: Self) -> Self {
+            if self >= b { self } else { b }
+        }
+    }
+
     impl SomethingOrNothing<f32> {
         pub fn print_f32(self) {
             match self {
@@ -113,8 +465,34 @@ pub mod part0203 {
         min.print_f32();
     }
 
+    // With the `serde` feature, the value is written and read exactly like an `Option`: `Nothing`
+    // becomes `null` in JSON, and `Something(t)` just `t`.
+    #[cfg(feature = "serde")]
+    impl<T: ::serde::Serialize> ::serde::Serialize for SomethingOrNothing<T> {
+        fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            self.as_ref().into_option().serialize(serializer)
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl<'de, T: ::serde::Deserialize<'de>> ::serde::Deserialize<'de> for SomethingOrNothing<T> {
+        fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            Option::deserialize(deserializer).map(SomethingOrNothing::from_option)
+        }
+    }
+
     /// Add a `Display` implementation to `SomethingOrNothing`
     use std::fmt;
+    impl<T: fmt::Display> SomethingOrNothing<T> {
+        /// Print the value, whatever its type, as long as it can be displayed.
+        pub fn print(&self) {
+            match *self {
+                Nothing => println!("The value is: <nothing>"),
+                Something(ref t) => println!("The value is: {}", t),
+            };
+        }
+    }
+
     impl<T: fmt::Display> fmt::Display for SomethingOrNothing<T> {
         fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
             match *self {
@@ -125,3 +503,319 @@ pub mod part0203 {
     }
 }
 
+pub mod part03 {
+    // The input of part 03, done properly: whatever the user enters, we never panic, and every
+    // complaint says which line it is about.
+    use std::io::prelude::*;
+    use std::io;
+    use std::fmt;
+    use std::str::FromStr;
+    use bigint::BigInt;
+    use super::part0203::vec_min;
+
+    /// What to do with a line that is not a number. Blank lines are always skipped.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum ParsePolicy {
+        /// Ignore the line, and go on with the next one
+        SkipInvalid,
+        /// Stop at the first invalid line, with an error about it
+        FailFast,
+        /// Read all the lines, then fail with an error that lists all the invalid ones
+        CollectErrors,
+    }
+
+    /// Read numbers from `input`, one per line. This can be standard input, a file, or just a
+    /// byte slice. Errors of the input itself are always returned right away; invalid numbers are
+    /// handled according to `policy`, and reported as `InvalidData` with their line number.
+    pub fn read_vec<R: BufRead>(input: R, policy: ParsePolicy) -> io::Result<Vec<i64>> {
+        read_numbers(input, policy)
+    }
+
+    /// Like `read_vec`, for numbers of any size. The error messages point at the invalid digit.
+    pub fn read_bigints<R: BufRead>(input: R, policy: ParsePolicy) -> io::Result<Vec<BigInt>> {
+        read_numbers(input, policy)
+    }
+
+    fn read_numbers<R, T>(input: R, policy: ParsePolicy) -> io::Result<Vec<T>>
+        where R: BufRead, T: FromStr, T::Err: fmt::Display
+    {
+        let mut vec = Vec::new();
+        let mut errors = Vec::new();
+        for (lineidx, line) in input.lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            match line.parse::<T>() {
+                Ok(num) => vec.push(num),
+                Err(e) => {
+                    let msg = format!("line {}: {:?} is not a number: {}", lineidx + 1, line, e);
+                    match policy {
+                        ParsePolicy::SkipInvalid => {}
+                        ParsePolicy::FailFast => return Err(io::Error::new(io::ErrorKind::InvalidData, msg)),
+                        ParsePolicy::CollectErrors => errors.push(msg),
+                    }
+                }
+            }
+        }
+        if !errors.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, errors.join("\n")));
+        }
+        Ok(vec)
+    }
+
+    pub fn main() {
+        println!("Enter a list of numbers, one per line. End with Ctrl-D (Linux) or Ctrl-Z (Windows).");
+        let stdin = io::stdin();
+        match read_vec(stdin.lock(), ParsePolicy::CollectErrors) {
+            Ok(vec) => vec_min(vec).print(),
+            Err(e) => println!("{}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::ptr;
+    use bigint::{BigInt, BigUint};
+    use super::part0203::*;
+
+    #[test]
+    fn test_combinators() {
+        let halve = |n: i32| if n % 2 == 0 { Something(n / 2) } else { Nothing };
+        assert_eq!(vec_min(vec![18, 5, 7]).map(|n| n * 2).unwrap_or(0), 10);
+        assert_eq!(vec_min(Vec::<i32>::new()).map(|n| n * 2).unwrap_or(0), 0);
+        assert_eq!(Something(12).and_then(halve).and_then(halve).unwrap_or(-1), 3);
+        assert_eq!(Something(6).and_then(halve).and_then(halve).unwrap_or(-1), -1);
+        assert_eq!(Nothing.or_else(|| Something(1)).unwrap_or(0), 1);
+        assert_eq!(Something(2).or_else(|| Something(1)).unwrap_or(0), 2);
+        assert_eq!(Nothing.unwrap_or_else(|| 42), 42);
+        assert_eq!(Something("x").map(str::len).unwrap_or_else(|| unreachable!()), 1);
+    }
+
+    #[test]
+    fn test_option() {
+        assert_eq!(Something(3).into_option(), Some(3));
+        assert_eq!(Nothing::<i32>.into_option(), None);
+        assert_eq!(SomethingOrNothing::from_option(Some(4)).unwrap_or(0), 4);
+        assert_eq!(SomethingOrNothing::from_option(None).unwrap_or(0), 0);
+        // Through `From` and `Into`, e.g. to use the methods of `Option`.
+        let s: SomethingOrNothing<&str> = "a,b".split(',').nth(1).into();
+        assert_eq!(Option::from(s).filter(|s: &&str| s.len() == 1), Some("b"));
+        let o: Option<i32> = vec_min(vec![3, 1, 2]).into();
+        assert_eq!(o, Some(1));
+    }
+
+    #[test]
+    fn test_expect_default() {
+        assert_eq!(vec_min(vec![2, 1]).expect("no numbers"), 1);
+        assert_eq!(Nothing::<String>.unwrap_or_default(), "");
+        assert_eq!(Something(5).unwrap_or_default(), 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "no numbers")]
+    fn test_expect_nothing() {
+        vec_min(Vec::<i32>::new()).expect("no numbers");
+    }
+
+    #[test]
+    fn test_result() {
+        fn smallest(v: Vec<i32>) -> Result<i32, String> {
+            let min = vec_min(v).ok_or_else(|| "no numbers".to_string())?;
+            Ok(min * 10)
+        }
+        assert_eq!(smallest(vec![3, 2]), Ok(20));
+        assert_eq!(smallest(vec![]), Err("no numbers".to_string()));
+        assert_eq!(Something(1).ok_or("missing"), Ok(1));
+        assert_eq!(Nothing::<i32>.ok_or("missing"), Err("missing"));
+    }
+
+    #[test]
+    fn test_views() {
+        let mut s = Something("hello".to_string());
+        assert_eq!(s.as_ref().map(|t| t.len()), Something(5));
+        if let Something(t) = s.as_mut() {
+            t.push('!');
+        }
+        assert_eq!(s, Something("hello!".to_string()));
+        let mut n: SomethingOrNothing<String> = Nothing;
+        assert_eq!(n.as_ref(), Nothing);
+        assert_eq!(n.as_mut(), Nothing);
+    }
+
+    #[test]
+    fn test_in_place() {
+        let mut s = Something(1);
+        assert_eq!(s.take(), Something(1));
+        assert_eq!(s, Nothing);
+        assert_eq!(s.take(), Nothing);
+        assert_eq!(s.replace(2), Nothing);
+        assert_eq!(s.replace(3), Something(2));
+        assert_eq!(s, Something(3));
+
+        // A cache: the value is only computed the first time.
+        let mut cache = Nothing;
+        let mut calls = 0;
+        for _ in 0..3 {
+            let value = cache.get_or_insert_with(|| { calls += 1; vec![1, 2] });
+            value.push(3);
+        }
+        assert_eq!(calls, 1);
+        assert_eq!(cache, Something(vec![1, 2, 3, 3, 3]));
+    }
+
+    #[test]
+    fn test_filter_zip_xor_flatten() {
+        // The same as for `Option`, for all combinations of values.
+        let samples = [Some(1), Some(2), None];
+        for &o in samples.iter() {
+            assert_eq!(SomethingOrNothing::from(o).filter(|&t| t > 1).into_option(), o.filter(|&t| t > 1));
+            for &p in samples.iter() {
+                assert_eq!(SomethingOrNothing::from(o).zip(SomethingOrNothing::from(p)).into_option(), o.zip(p));
+                assert_eq!(SomethingOrNothing::from(o).xor(SomethingOrNothing::from(p)).into_option(), o.xor(p));
+            }
+        }
+        assert_eq!(Something(Something(1)).flatten(), Something(1));
+        assert_eq!(Something(Nothing::<i32>).flatten(), Nothing);
+        assert_eq!(Nothing::<SomethingOrNothing<i32>>.flatten(), Nothing);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        use serde::Deserialize;
+        use serde::de::IntoDeserializer;
+        use serde::de::value::Error;
+        // A unit, which is what `null` is for `Option`, becomes `Nothing`.
+        let n: Result<SomethingOrNothing<i32>, Error> = SomethingOrNothing::deserialize(().into_deserializer());
+        assert_eq!(n, Ok(Nothing));
+    }
+
+    #[test]
+    fn test_fold() {
+        let v = vec![18, 5, 7, 3, 9, 27];
+        assert_eq!(fold_min(v.clone()), Something(3));
+        assert_eq!(fold_max(v.clone()), Something(27));
+        assert_eq!(fold_min(v[..3].iter().cloned()), Something(5));
+        assert_eq!(fold_max(v.iter().map(|&n| n % 10).filter(|&n| n != 9)), Something(8));
+        assert_eq!(fold_min(-3..4), Something(-3));
+        assert_eq!(fold_max(-3..4), Something(3));
+        assert_eq!(fold_max(0..0), Nothing);
+        assert_eq!(fold_max(vec![1.5f32, -2.0]), Something(1.5));
+    }
+
+    #[test]
+    fn test_minimum_ext() {
+        let v = [4, -2, 9, 3];
+        assert_eq!(v.iter().map(|&x| x * x).minimum(), Something(4));
+        assert_eq!(v.iter().cloned().filter(|&x| x > 3).maximum(), Something(9));
+        assert_eq!((1..1).minimum(), Nothing);
+        assert_eq!(v[1..3].iter().copied().minimum(), Something(-2));
+    }
+
+    #[test]
+    fn test_with_index_by_key() {
+        assert_eq!(vec_min_with_index(vec![18, 5, 7, 5, 9]), Something((1, 5)));
+        assert_eq!(vec_min_with_index(Vec::<i32>::new()), Nothing);
+        // A NaN in front does not stick.
+        assert_eq!(vec_min_with_index(vec![f64::NAN, 2.0, 1.0]), Something((2, 1.0)));
+        // With nothing but NaNs, the first one is kept.
+        match vec_min_with_index(vec![f64::NAN, f64::NAN]) {
+            Something((0, x)) => assert!(x.is_nan()),
+            _ => panic!("expected the NaN at index 0"),
+        }
+
+        let words = vec!["banana".to_string(), "fig".to_string(), "kiwi".to_string(), "pea".to_string()];
+        assert_eq!(vec_min_by_key(words, |w| w.len() as i32), Something("fig".to_string()));
+        assert_eq!(vec_min_by_key(vec![3, -7, 5], |&x: &i32| -x.abs()), Something(-7));
+        assert_eq!(vec_min_by_key(Vec::<String>::new(), |w| w.len() as i32), Nothing);
+        assert_eq!(vec_min_by_key(vec!["a", "b"], |_| f64::NAN), Something("a"));
+    }
+
+    #[test]
+    fn test_f64() {
+        assert_eq!(fold_min(vec![2.5, f64::NAN, -1.0, f64::NAN]), Something(-1.0));
+        assert_eq!(fold_max(vec![f64::NAN, 2.5, -1.0]), Something(2.5));
+        assert!(match fold_min(vec![f64::NAN, f64::NAN]) { Something(x) => x.is_nan(), Nothing => false });
+    }
+
+    #[test]
+    fn test_big_numbers() {
+        let v: Vec<BigInt> = ["5", "-100000000000000000000000", "7", "100000000000000000000000"].iter()
+            .map(|s| s.parse().unwrap()).collect();
+        // The results point into the vector.
+        match (vec_min(v.iter().collect()), fold_max(v.iter())) {
+            (Something(min), Something(max)) => assert!(ptr::eq(min, &v[1]) && ptr::eq(max, &v[3])),
+            _ => panic!("the vector is not empty"),
+        }
+        let u = vec![BigUint::new(3), BigUint::power_of_2(100), BigUint::new(2)];
+        assert_eq!(fold_min(u.iter()), Something(&u[2]));
+        assert_eq!(fold_max(&u), Something(&u[1]));
+    }
+
+    #[test]
+    fn test_head_last() {
+        let v = vec!["a".to_string(), "b".to_string()];
+        assert_eq!(head(&v), Something(&v[0]));
+        assert_eq!(last(&v), Something(&v[1]));
+        assert_eq!(head(&v[1..]).cloned(), Something("b".to_string()));
+        assert_eq!(head::<i32>(&[]), Nothing);
+        assert_eq!(last(&[0u8; 0]), Nothing);
+    }
+
+    #[test]
+    fn test_cloned_copied() {
+        let v = vec![BigInt::from(-3), BigInt::from(7)];
+        let min: SomethingOrNothing<BigInt> = fold_min(&v).cloned();
+        assert_eq!(min, Something(BigInt::from(-3)));
+        let v = [4, 2, 9];
+        assert_eq!(Something(&v[1]).copied(), Something(2));
+        assert_eq!(Nothing::<&i32>.copied(), Nothing);
+        assert_eq!(Nothing::<&String>.cloned(), Nothing);
+    }
+
+    #[test]
+    fn test_read_vec() {
+        use std::io::{self, Read};
+        use super::part03::{read_vec, ParsePolicy};
+        let input: &[u8] = b"3\n x \n\n-12\n12345678901234567890\n 7 ";
+        assert_eq!(read_vec(input, ParsePolicy::SkipInvalid).unwrap(), vec![3, -12, 7]);
+        let e = read_vec(io::Cursor::new(input), ParsePolicy::FailFast).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(e.to_string(), "line 2: \"x\" is not a number: invalid digit found in string");
+        let e = read_vec(input, ParsePolicy::CollectErrors).unwrap_err();
+        assert_eq!(e.to_string().lines().map(|l| &l[..7]).collect::<Vec<_>>(), ["line 2:", "line 5:"]);
+        // Errors of the input itself come first: here, a line that is not UTF-8.
+        let broken: &[u8] = b"1\n\xff\nx\n";
+        let e = read_vec(broken, ParsePolicy::CollectErrors).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+        assert!(!e.to_string().starts_with("line"));
+        // Anything that reads bytes can be buffered.
+        let chained = io::BufReader::new(b"1\n2".chain(&b"0\n-5\n"[..]));
+        assert_eq!(read_vec(chained, ParsePolicy::FailFast).unwrap(), vec![1, 20, -5]);
+    }
+
+    #[test]
+    fn test_read_bigints() {
+        use super::part03::{read_bigints, ParsePolicy};
+        let input: &[u8] = b"-123456789012345678901234567890\n42\n12x4\n";
+        let v = read_bigints(input, ParsePolicy::SkipInvalid).unwrap();
+        assert_eq!(v, ["-123456789012345678901234567890", "42"].iter().map(|s| s.parse().unwrap()).collect::<Vec<BigInt>>());
+        assert_eq!(fold_min(&v), Something(&v[0]));
+        let e = read_bigints(input, ParsePolicy::FailFast).unwrap_err();
+        assert!(e.to_string().starts_with("line 3: \"12x4\" is not a number: invalid digit 'x' for base 10 at byte 2"));
+    }
+
+    #[test]
+    fn test_traits() {
+        assert_eq!(vec_min(vec![4, 2]), Something(2));
+        assert_ne!(Something(2), Nothing);
+        assert_eq!(format!("{:?} {:?}", Something("a"), Nothing::<i32>), "Something(\"a\") Nothing");
+        let seen: HashSet<SomethingOrNothing<i32>> = vec![Something(1), Nothing, Something(1), Nothing].into_iter().collect();
+        assert_eq!(seen.len(), 2);
+        assert!(seen.contains(&Nothing) && seen.contains(&Something(1)));
+    }
+}